@@ -111,15 +111,37 @@ fn main() -> Result<()> {
                     let cell_name = canvas.get_cell(entry.cell_id)
                         .and_then(|c| c.name.clone())
                         .unwrap_or_else(|| format!("{}", entry.cell_id));
+                    let step = if entry.iteration > 0 {
+                        format!("step {}, iteration {}", entry.step, entry.iteration)
+                    } else {
+                        format!("step {}", entry.step)
+                    };
                     match &entry.error {
-                        None => println!("     ✓ {} (step {})", cell_name, entry.step),
-                        Some(err) => println!("     ❌ {} (step {}): {}", cell_name, entry.step, err),
+                        None => println!("     ✓ {} ({})", cell_name, step),
+                        Some(err) => println!("     ❌ {} ({}): {}", cell_name, step, err),
+                    }
+                    for line in entry.stdout.lines() {
+                        println!("       │ {}", line);
+                    }
+                    for line in entry.stderr.lines() {
+                        println!("       ! {}", line);
+                    }
+                    for skipped in &entry.branches_skipped {
+                        let skipped_name = canvas.get_cell(*skipped)
+                            .map(|c| c.label())
+                            .unwrap_or_else(|| format!("{}", skipped));
+                        println!("       ↷ skipped branch to {}", skipped_name);
                     }
                 }
             }
         }
         Err(e) => {
             println!("  ❌ Execution failed: {}", e);
+            if let Some(traceback) = engine.log().last().and_then(|entry| entry.traceback.as_ref()) {
+                for line in traceback.to_string().lines() {
+                    println!("     {}", line);
+                }
+            }
             return Err(e);
         }
     }
@@ -12,6 +12,10 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "Graph Cell Editor",
         options,
-        Box::new(|_cc| Ok(Box::new(GraphCellEditorApp::new()))),
+        Box::new(|cc| {
+            // PNG, JPEG and SVG decoding for Image cells
+            egui_extras::install_image_loaders(&cc.egui_ctx);
+            Ok(Box::new(GraphCellEditorApp::new()))
+        }),
     )
 }
@@ -1,9 +1,9 @@
 use crate::{
-    Cell, CellContent, CellType, EventType, GraphEvent, IdGenerator, Rectangle, Relationship,
-    SplitDirection,
+    Cell, CellContent, CellType, EventType, GraphEvent, Guard, IdGenerator, LoopConfig,
+    Rectangle, Relationship, SplitDirection, Transform,
 };
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use ulid::Ulid;
 
 /// Visual guide showing snap alignment
@@ -144,6 +144,17 @@ impl Canvas {
             .map(|c| c.id)
     }
 
+    /// Get a cell by name
+    pub fn get_cell_by_name(&self, name: &str) -> Option<&Cell> {
+        self.cells.values().find(|c| c.name.as_deref() == Some(name))
+    }
+
+    /// Resolve a cell reference by name first, then by short ID
+    pub fn resolve_cell_reference(&self, reference: &str) -> Option<&Cell> {
+        self.get_cell_by_name(reference)
+            .or_else(|| self.get_cell_by_short_id(reference))
+    }
+
     /// Snapping threshold in canvas units
     const SNAP_THRESHOLD: f32 = 10.0;
 
@@ -453,6 +464,62 @@ impl Canvas {
         Ok(())
     }
 
+    /// Name a cell as an entry point, or clear its entry point with `None`
+    ///
+    /// Unlike the start point, a canvas can have any number of entry points; names must be
+    /// unique.
+    pub fn set_entry_point(&mut self, id: Ulid, name: Option<String>) -> Result<()> {
+        let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        if let Some(name) = &name {
+            if let Some(other) = self.get_entry_point(name).filter(|c| c.id != id) {
+                return Err(anyhow!(
+                    "Entry point '{}' already starts at cell {}",
+                    name,
+                    other.label()
+                ));
+            }
+        }
+
+        let cell = self
+            .cells
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("Cell not found: {}", id))?;
+        cell.entry_point = name.clone();
+
+        self.log_event(EventType::EntryPointChanged { id, name });
+
+        Ok(())
+    }
+
+    /// Get the cell an entry point starts at
+    pub fn get_entry_point(&self, name: &str) -> Option<&Cell> {
+        self.cells
+            .values()
+            .find(|c| c.entry_point.as_deref() == Some(name))
+    }
+
+    /// All entry points as (name, cell), sorted by name
+    pub fn entry_points(&self) -> Vec<(String, Ulid)> {
+        let mut entries: Vec<_> = self
+            .cells
+            .values()
+            .filter_map(|c| c.entry_point.clone().map(|name| (name, c.id)))
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    /// Cells execution can begin at: the start point and every entry point
+    pub fn start_cells(&self) -> Vec<Ulid> {
+        let mut starts: Vec<Ulid> = self.get_start_point().map(|c| c.id).into_iter().collect();
+        for (_, id) in self.entry_points() {
+            if !starts.contains(&id) {
+                starts.push(id);
+            }
+        }
+        starts
+    }
+
     // ========== Relationship CRUD Operations ==========
 
     /// Create a relationship between two cells
@@ -467,7 +534,14 @@ impl Canvas {
             return Err(anyhow!("Cannot create self-referential relationship"));
         }
 
-        let relationship = Relationship::new(from, to);
+        // Re-creating a relationship leaves it (ports, transform, guard, position) as it is
+        if self.relationships.contains_key(&(from, to)) {
+            return Ok(());
+        }
+
+        // New inputs go last
+        let mut relationship = Relationship::new(from, to);
+        relationship.input_order = self.get_incoming_relationships(to).len();
         self.relationships.insert((from, to), relationship);
 
         self.log_event(EventType::RelationshipCreated { from, to });
@@ -475,6 +549,40 @@ impl Canvas {
         Ok(())
     }
 
+    /// Set the named output/input ports of a relationship
+    pub fn set_relationship_ports(
+        &mut self,
+        from: Ulid,
+        to: Ulid,
+        source_key: Option<String>,
+        target_input: Option<String>,
+    ) -> Result<()> {
+        if let Some(name) = &target_input {
+            if !Relationship::is_valid_port_name(name) {
+                return Err(anyhow!("Invalid input port name: {}", name));
+            }
+            if Relationship::is_reserved_port_name(name) {
+                return Err(anyhow!("Input port name is reserved: {}", name));
+            }
+        }
+
+        let relationship = self
+            .relationships
+            .get_mut(&(from, to))
+            .ok_or_else(|| anyhow!("Relationship not found: {} -> {}", from, to))?;
+        relationship.source_key = source_key.clone();
+        relationship.target_input = target_input.clone();
+
+        self.log_event(EventType::RelationshipPortsChanged {
+            from,
+            to,
+            source_key,
+            target_input,
+        });
+
+        Ok(())
+    }
+
     /// Get a relationship
     pub fn get_relationship(&self, from: Ulid, to: Ulid) -> Option<&Relationship> {
         self.relationships.get(&(from, to))
@@ -495,6 +603,7 @@ impl Canvas {
         if self.relationships.remove(&(from, to)).is_none() {
             return Err(anyhow!("Relationship not found: {} -> {}", from, to));
         }
+        self.renumber_inputs(to);
 
         self.log_event(EventType::RelationshipDeleted { from, to });
 
@@ -509,9 +618,169 @@ impl Canvas {
             .collect()
     }
 
-    /// Get all relationships ending at a cell
+    /// Get all relationships ending at a cell, in input order
     pub fn get_incoming_relationships(&self, to: Ulid) -> Vec<&Relationship> {
-        self.relationships.values().filter(|r| r.to == to).collect()
+        let mut incoming: Vec<_> = self.relationships.values().filter(|r| r.to == to).collect();
+        // Ties (e.g. projects saved before input ordering) fall back to source ULID
+        incoming.sort_by_key(|r| (r.input_order, r.from));
+        incoming
+    }
+
+    /// Cells reachable from a cell by following relationships (including the cell itself)
+    pub fn downstream_cells(&self, id: Ulid) -> HashSet<Ulid> {
+        self.reachable_cells(id, |r| (r.from, r.to))
+    }
+
+    /// Cells a cell is reachable from by following relationships (including the cell itself)
+    pub fn upstream_cells(&self, id: Ulid) -> HashSet<Ulid> {
+        self.reachable_cells(id, |r| (r.to, r.from))
+    }
+
+    /// Walk relationships from a cell, each oriented as (from, to) by `edge`
+    fn reachable_cells(
+        &self,
+        id: Ulid,
+        edge: impl Fn(&Relationship) -> (Ulid, Ulid),
+    ) -> HashSet<Ulid> {
+        let mut reached = HashSet::from([id]);
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            for (from, to) in self.relationships.values().map(&edge) {
+                if from == current && reached.insert(to) {
+                    stack.push(to);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Set or clear the transform applied along a relationship
+    pub fn set_relationship_transform(
+        &mut self,
+        from: Ulid,
+        to: Ulid,
+        transform: Option<Transform>,
+    ) -> Result<()> {
+        let relationship = self
+            .relationships
+            .get_mut(&(from, to))
+            .ok_or_else(|| anyhow!("Relationship not found: {} -> {}", from, to))?;
+        relationship.transform = transform.clone();
+
+        self.log_event(EventType::RelationshipTransformChanged {
+            from,
+            to,
+            transform,
+        });
+
+        Ok(())
+    }
+
+    /// Set or clear the guard condition on a relationship
+    pub fn set_relationship_guard(
+        &mut self,
+        from: Ulid,
+        to: Ulid,
+        guard: Option<Guard>,
+    ) -> Result<()> {
+        let relationship = self
+            .relationships
+            .get_mut(&(from, to))
+            .ok_or_else(|| anyhow!("Relationship not found: {} -> {}", from, to))?;
+        relationship.guard = guard.clone();
+
+        self.log_event(EventType::RelationshipGuardChanged { from, to, guard });
+
+        Ok(())
+    }
+
+    /// Mark a relationship as a loop back edge, or clear the mark
+    pub fn set_relationship_loop(
+        &mut self,
+        from: Ulid,
+        to: Ulid,
+        loop_config: Option<LoopConfig>,
+    ) -> Result<()> {
+        if matches!(&loop_config, Some(config) if config.max_iterations == 0) {
+            return Err(anyhow!("Loop must allow at least one iteration"));
+        }
+
+        let relationship = self
+            .relationships
+            .get_mut(&(from, to))
+            .ok_or_else(|| anyhow!("Relationship not found: {} -> {}", from, to))?;
+        relationship.loop_config = loop_config.clone();
+
+        self.log_event(EventType::RelationshipLoopChanged {
+            from,
+            to,
+            loop_config,
+        });
+
+        Ok(())
+    }
+
+    /// Set the order of a cell's inputs; `order` lists every source cell exactly once
+    pub fn set_input_order(&mut self, to: Ulid, order: &[Ulid]) -> Result<()> {
+        let mut current: Vec<Ulid> = self
+            .get_incoming_relationships(to)
+            .iter()
+            .map(|r| r.from)
+            .collect();
+        let mut requested = order.to_vec();
+        current.sort();
+        requested.sort();
+        if current != requested {
+            return Err(anyhow!(
+                "Input order for {} must list each of its {} source cells once",
+                to,
+                current.len()
+            ));
+        }
+
+        for (index, from) in order.iter().enumerate() {
+            if let Some(rel) = self.relationships.get_mut(&(*from, to)) {
+                rel.input_order = index;
+            }
+        }
+
+        self.log_event(EventType::InputsReordered {
+            cell_id: to,
+            order: order.to_vec(),
+        });
+
+        Ok(())
+    }
+
+    /// Move one input of a cell to a new position
+    pub fn move_input(&mut self, to: Ulid, from: Ulid, new_index: usize) -> Result<()> {
+        let mut order: Vec<Ulid> = self
+            .get_incoming_relationships(to)
+            .iter()
+            .map(|r| r.from)
+            .collect();
+        let old_index = order
+            .iter()
+            .position(|&id| id == from)
+            .ok_or_else(|| anyhow!("Relationship not found: {} -> {}", from, to))?;
+
+        let id = order.remove(old_index);
+        order.insert(new_index.min(order.len()), id);
+        self.set_input_order(to, &order)
+    }
+
+    /// Close gaps in a cell's input order (after a relationship is removed)
+    fn renumber_inputs(&mut self, to: Ulid) {
+        let order: Vec<Ulid> = self
+            .get_incoming_relationships(to)
+            .iter()
+            .map(|r| r.from)
+            .collect();
+        for (index, from) in order.into_iter().enumerate() {
+            if let Some(rel) = self.relationships.get_mut(&(from, to)) {
+                rel.input_order = index;
+            }
+        }
     }
 
     // ========== Cell Splitting ==========
@@ -591,6 +860,7 @@ impl Canvas {
             bounds: bounds1,
             content: cell.content.clone(), // Inherits content
             is_start_point: cell.is_start_point,
+            entry_point: None, // Entry point names stay unique
             parent: Some(cell_id),
             children: vec![],
             chunk_id: cell.chunk_id,
@@ -600,6 +870,13 @@ impl Canvas {
             result_target_cell: cell.result_target_cell,
             currency_symbol: cell.currency_symbol.clone(),
             decimal_precision: cell.decimal_precision,
+            date_format: cell.date_format.clone(),
+            time_zone: cell.time_zone.clone(),
+            shell_timeout_secs: cell.shell_timeout_secs,
+            python_timeout_secs: cell.python_timeout_secs,
+            working_dir: cell.working_dir.clone(),
+            json_schema: cell.json_schema.clone(),
+            error_policy: cell.error_policy,
         };
 
         let child2 = Cell {
@@ -610,6 +887,7 @@ impl Canvas {
             bounds: bounds2,
             content: CellContent::Inline(String::new()), // Empty
             is_start_point: false,
+            entry_point: None,
             parent: Some(cell_id),
             children: vec![],
             chunk_id: cell.chunk_id,
@@ -619,6 +897,13 @@ impl Canvas {
             result_target_cell: None,
             currency_symbol: "$".to_string(),
             decimal_precision: 0,
+            date_format: crate::dates::DEFAULT_DATE_FORMAT.to_string(),
+            time_zone: crate::dates::DEFAULT_TIME_ZONE.to_string(),
+            shell_timeout_secs: None,
+            python_timeout_secs: None,
+            working_dir: None,
+            json_schema: None,
+            error_policy: None,
         };
 
         // Update parent cell's children list
@@ -925,11 +1210,108 @@ mod tests {
         assert_eq!(rel.from, id1);
         assert_eq!(rel.to, id2);
 
+        // Set ports
+        canvas
+            .set_relationship_ports(id1, id2, Some("train".to_string()), Some("data".to_string()))
+            .unwrap();
+        let rel = canvas.get_relationship(id1, id2).unwrap();
+        assert_eq!(rel.source_key.as_deref(), Some("train"));
+        assert_eq!(rel.target_input.as_deref(), Some("data"));
+        assert!(canvas
+            .set_relationship_ports(id1, id2, None, Some("not valid".to_string()))
+            .is_err());
+        assert!(canvas
+            .set_relationship_ports(id1, id2, None, Some("params".to_string()))
+            .is_err());
+
+        // Re-creating the relationship keeps its ports
+        canvas.create_relationship(id1, id2).unwrap();
+        let rel = canvas.get_relationship(id1, id2).unwrap();
+        assert_eq!(rel.target_input.as_deref(), Some("data"));
+        assert_eq!(canvas.relationship_count(), 1);
+
         // Delete relationship
         canvas.delete_relationship(id1, id2).unwrap();
         assert_eq!(canvas.relationship_count(), 0);
     }
 
+    #[test]
+    fn test_input_order() {
+        let mut canvas = Canvas::new();
+
+        let target = canvas.create_cell(
+            CellType::Text,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("Join"),
+        );
+        let sources: Vec<Ulid> = (0..3)
+            .map(|i| {
+                canvas.create_cell(
+                    CellType::Text,
+                    Rectangle::new(0.0, 150.0 * (i + 1) as f32, 100.0, 100.0),
+                    CellContent::inline(format!("Source {}", i)),
+                )
+            })
+            .collect();
+        // Connect in reverse so creation order differs from ULID order
+        for &source in sources.iter().rev() {
+            canvas.create_relationship(source, target).unwrap();
+        }
+
+        let order = |canvas: &Canvas| -> Vec<Ulid> {
+            canvas
+                .get_incoming_relationships(target)
+                .iter()
+                .map(|r| r.from)
+                .collect()
+        };
+        assert_eq!(order(&canvas), vec![sources[2], sources[1], sources[0]]);
+
+        canvas.move_input(target, sources[0], 0).unwrap();
+        assert_eq!(order(&canvas), vec![sources[0], sources[2], sources[1]]);
+
+        canvas
+            .set_input_order(target, &[sources[1], sources[0], sources[2]])
+            .unwrap();
+        assert_eq!(order(&canvas), vec![sources[1], sources[0], sources[2]]);
+        assert!(canvas.set_input_order(target, &[sources[1]]).is_err());
+
+        // Removing an input closes the gap
+        canvas.delete_relationship(sources[1], target).unwrap();
+        assert_eq!(order(&canvas), vec![sources[0], sources[2]]);
+        let orders: Vec<usize> = canvas
+            .get_incoming_relationships(target)
+            .iter()
+            .map(|r| r.input_order)
+            .collect();
+        assert_eq!(orders, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_reachable_cells() {
+        let mut canvas = Canvas::new();
+        let ids: Vec<Ulid> = (0..4)
+            .map(|i| {
+                canvas.create_cell(
+                    CellType::Text,
+                    Rectangle::new(150.0 * i as f32, 0.0, 100.0, 100.0),
+                    CellContent::inline(format!("Cell {}", i)),
+                )
+            })
+            .collect();
+        // 0 → 1 → 2, and 3 → 2
+        canvas.create_relationship(ids[0], ids[1]).unwrap();
+        canvas.create_relationship(ids[1], ids[2]).unwrap();
+        canvas.create_relationship(ids[3], ids[2]).unwrap();
+
+        assert_eq!(canvas.downstream_cells(ids[1]), HashSet::from([ids[1], ids[2]]));
+        assert_eq!(
+            canvas.upstream_cells(ids[2]),
+            HashSet::from([ids[0], ids[1], ids[2], ids[3]])
+        );
+        assert_eq!(canvas.upstream_cells(ids[0]), HashSet::from([ids[0]]));
+    }
+
     #[test]
     fn test_split_cell_horizontal() {
         let mut canvas = Canvas::new();
@@ -1114,6 +1496,38 @@ mod tests {
         assert!(canvas.get_cell(id2).unwrap().is_start_point);
     }
 
+    #[test]
+    fn test_entry_points() {
+        let mut canvas = Canvas::new();
+
+        let train = canvas.create_cell(
+            CellType::Text,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("Train"),
+        );
+        let report = canvas.create_cell(
+            CellType::Text,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("Report"),
+        );
+        canvas.set_start_point(train).unwrap();
+
+        canvas.set_entry_point(train, Some("train".to_string())).unwrap();
+        canvas.set_entry_point(report, Some(" report ".to_string())).unwrap();
+        assert_eq!(
+            canvas.entry_points(),
+            vec![("report".to_string(), report), ("train".to_string(), train)]
+        );
+        assert_eq!(canvas.get_entry_point("report").unwrap().id, report);
+        assert_eq!(canvas.start_cells(), vec![train, report]);
+
+        // Names are unique; clearing a name removes the entry point
+        assert!(canvas.set_entry_point(report, Some("train".to_string())).is_err());
+        canvas.set_entry_point(report, None).unwrap();
+        assert!(canvas.get_entry_point("report").is_none());
+        assert_eq!(canvas.start_cells(), vec![train]);
+    }
+
     #[test]
     fn test_event_logging() {
         let mut canvas = Canvas::new();
@@ -1,4 +1,4 @@
-use crate::SplitDirection;
+use crate::{ErrorPolicy, SplitDirection};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use ulid::Ulid;
@@ -27,6 +27,9 @@ pub struct Cell {
     /// Execution starting point flag
     pub is_start_point: bool,
 
+    /// Name of the entry point starting at this cell (e.g. "train", "report")
+    pub entry_point: Option<String>,
+
     /// Parent cell if this was created by split
     pub parent: Option<Ulid>,
 
@@ -53,6 +56,39 @@ pub struct Cell {
 
     /// Decimal precision for Number cells
     pub decimal_precision: u8,
+
+    /// strftime format Date cells show and write their value in
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+
+    /// IANA time zone Date cells read and show their value in
+    #[serde(default = "default_time_zone")]
+    pub time_zone: String,
+
+    /// Seconds a Shell cell's command may run (None = `DEFAULT_SHELL_TIMEOUT_SECS`)
+    pub shell_timeout_secs: Option<u64>,
+
+    /// Seconds a Python cell may run in a project interpreter
+    /// (None = `DEFAULT_PYTHON_TIMEOUT_SECS`); in-process runs can't be stopped
+    pub python_timeout_secs: Option<u64>,
+
+    /// Directory a Shell cell runs in, relative to the project directory
+    pub working_dir: Option<PathBuf>,
+
+    /// JSON Schema a JSON cell's content and inputs must match
+    #[serde(default)]
+    pub json_schema: Option<serde_json::Value>,
+
+    /// What happens downstream when this cell fails (None = the run's policy)
+    pub error_policy: Option<ErrorPolicy>,
+}
+
+fn default_date_format() -> String {
+    crate::dates::DEFAULT_DATE_FORMAT.to_string()
+}
+
+fn default_time_zone() -> String {
+    crate::dates::DEFAULT_TIME_ZONE.to_string()
 }
 
 /// Markdown preview mode for text cells
@@ -88,6 +124,7 @@ impl Cell {
             bounds,
             content,
             is_start_point: false,
+            entry_point: None,
             parent: None,
             children: Vec::new(),
             chunk_id: None,
@@ -101,6 +138,13 @@ impl Cell {
                 CellType::NumberFloat => 2,
                 _ => 0,
             },
+            date_format: default_date_format(),
+            time_zone: default_time_zone(),
+            shell_timeout_secs: None,
+            python_timeout_secs: None,
+            working_dir: None,
+            json_schema: None,
+            error_policy: None,
         }
     }
 
@@ -120,6 +164,7 @@ impl Cell {
             bounds,
             content,
             is_start_point: false,
+            entry_point: None,
             parent: None,
             children: Vec::new(),
             chunk_id: None,
@@ -133,6 +178,13 @@ impl Cell {
                 CellType::NumberFloat => 2,
                 _ => 0,
             },
+            date_format: default_date_format(),
+            time_zone: default_time_zone(),
+            shell_timeout_secs: None,
+            python_timeout_secs: None,
+            working_dir: None,
+            json_schema: None,
+            error_policy: None,
         }
     }
 
@@ -141,6 +193,11 @@ impl Cell {
         self.name = name;
     }
 
+    /// Name if set, otherwise short ID (for messages)
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.short_id.clone())
+    }
+
     /// Set the cell as a start point
     pub fn set_start_point(&mut self, is_start: bool) {
         self.is_start_point = is_start;
@@ -227,7 +284,16 @@ pub enum CellType {
     NumberInt,
     NumberFloat,
     NumberCurrency,
-    // Future: Markdown, Json, Csv, Image, VisualBlock, etc.
+    Shell,
+    Sql,
+    Script,
+    Wasm,
+    Json,
+    Csv,
+    Image,
+    Chart,
+    Date,
+    // Future: Markdown, VisualBlock, etc.
 }
 
 /// Cell content can be inline or reference an external file
@@ -0,0 +1,605 @@
+//! Chart cells: numeric series built from a cell's inputs
+//!
+//! The GUI draws charts in their cells with egui_plot. `layout` and `render_svg` draw
+//! them for the headless SVG export, where there is no UI to plot in.
+
+use crate::{CellData, ColumnValues, Table};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Size charts are rendered at when exported to SVG
+pub const DEFAULT_SVG_SIZE: [f32; 2] = [480.0, 320.0];
+
+const MARGIN_LEFT: f32 = 48.0;
+const MARGIN_RIGHT: f32 = 12.0;
+const MARGIN_TOP: f32 = 28.0;
+const MARGIN_BOTTOM: f32 = 28.0;
+const FONT_SIZE: f32 = 11.0;
+const AXIS_COLOR: [u8; 3] = [90, 90, 90];
+const GRID_COLOR: [u8; 3] = [225, 225, 225];
+const PALETTE: [[u8; 3]; 6] = [
+    [31, 119, 180],
+    [255, 127, 14],
+    [44, 160, 44],
+    [214, 39, 40],
+    [148, 103, 189],
+    [140, 86, 75],
+];
+
+/// How a chart draws its series
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartKind {
+    #[default]
+    Line,
+    Bar,
+    Scatter,
+}
+
+/// What a Chart cell draws, stored as JSON in its content
+///
+/// `x` and `y` pick table columns (by default the row number and every numeric column);
+/// `cells` lists Math or Number cells whose values form one more series.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChartSpec {
+    pub kind: ChartKind,
+    pub title: Option<String>,
+    pub x: Option<String>,
+    pub y: Vec<String>,
+    pub cells: Vec<String>,
+}
+
+impl ChartSpec {
+    /// Parse a Chart cell's content; empty content is a line chart of every input
+    pub fn parse(content: &str) -> Result<Self> {
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(content).map_err(|e| anyhow!("Invalid chart spec: {}", e))
+    }
+}
+
+/// Named (x, y) points
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Series {
+    pub name: String,
+    pub points: Vec<[f64; 2]>,
+}
+
+/// Series ready to draw; a Chart cell's output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chart {
+    pub kind: ChartKind,
+    pub title: Option<String>,
+    pub series: Vec<Series>,
+    /// Labels of x positions 0, 1, ... when the x axis is categorical
+    pub categories: Vec<String>,
+}
+
+impl Chart {
+    /// Build a chart from a spec, the cell's inputs and the values of the cells it lists
+    ///
+    /// Inputs can be tables, JSON arrays (of numbers, `[x, y]` pairs or row objects) and
+    /// JSON objects mapping series names to arrays of numbers.
+    pub fn build(spec: &ChartSpec, inputs: &[CellData], cells: &[(String, f64)]) -> Result<Self> {
+        let mut chart = Chart {
+            kind: spec.kind,
+            title: spec.title.clone(),
+            series: Vec::new(),
+            categories: Vec::new(),
+        };
+        for (i, input) in inputs.iter().enumerate() {
+            let name = format!("input_{}", i);
+            match input {
+                CellData::Table(table) => chart.add_table(spec, table)?,
+                CellData::Json(value) => chart.add_json(spec, &name, value)?,
+                other => return Err(anyhow!("Can't chart {:?} input", other.data_type())),
+            }
+        }
+        if !cells.is_empty() {
+            chart.categories = cells.iter().map(|(label, _)| label.clone()).collect();
+            chart.series.push(Series {
+                name: "cells".to_string(),
+                points: cells
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, value))| [i as f64, *value])
+                    .collect(),
+            });
+        }
+        if chart.series.is_empty() {
+            return Err(anyhow!("No data to chart"));
+        }
+        Ok(chart)
+    }
+
+    fn add_json(&mut self, spec: &ChartSpec, name: &str, value: &serde_json::Value) -> Result<()> {
+        use serde_json::Value;
+        match value {
+            Value::Array(items) if items.iter().all(Value::is_object) && !items.is_empty() => {
+                self.add_table(spec, &Table::from_json_rows(value)?)
+            }
+            Value::Array(items) => {
+                let points = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| match item {
+                        Value::Number(y) => Ok([i as f64, y.as_f64().unwrap_or_default()]),
+                        Value::Array(pair) => match pair.as_slice() {
+                            [x, y] if x.is_number() && y.is_number() => Ok([
+                                x.as_f64().unwrap_or_default(),
+                                y.as_f64().unwrap_or_default(),
+                            ]),
+                            _ => Err(anyhow!("Expected an [x, y] pair, found {}", item)),
+                        },
+                        _ => Err(anyhow!("Expected a number or [x, y] pair, found {}", item)),
+                    })
+                    .collect::<Result<_>>()?;
+                self.series.push(Series {
+                    name: name.to_string(),
+                    points,
+                });
+                Ok(())
+            }
+            Value::Object(map) => {
+                for (key, series) in map {
+                    self.add_json(spec, key, series)?;
+                }
+                Ok(())
+            }
+            other => Err(anyhow!("Can't chart {}", other)),
+        }
+    }
+
+    fn add_table(&mut self, spec: &ChartSpec, table: &Table) -> Result<()> {
+        let column = |name: &str| {
+            table
+                .column(name)
+                .ok_or_else(|| anyhow!("Table has no column '{}'", name))
+        };
+
+        let xs: Vec<Option<f64>> = match spec.x.as_deref() {
+            None => (0..table.row_count()).map(|row| Some(row as f64)).collect(),
+            Some(name) => match &column(name)?.values {
+                ColumnValues::Number(values) => values.clone(),
+                ColumnValues::Text(values) => {
+                    self.categories = values
+                        .iter()
+                        .map(|v| v.clone().unwrap_or_default())
+                        .collect();
+                    (0..values.len()).map(|row| Some(row as f64)).collect()
+                }
+                ColumnValues::Boolean(_) => {
+                    return Err(anyhow!("Column '{}' can't be an x axis", name))
+                }
+            },
+        };
+
+        let names: Vec<&str> = if spec.y.is_empty() {
+            table
+                .columns()
+                .iter()
+                .filter(|c| matches!(c.values, ColumnValues::Number(_)))
+                .map(|c| c.name.as_str())
+                .filter(|name| Some(*name) != spec.x.as_deref())
+                .collect()
+        } else {
+            spec.y.iter().map(String::as_str).collect()
+        };
+        for name in names {
+            let ColumnValues::Number(ys) = &column(name)?.values else {
+                return Err(anyhow!("Column '{}' isn't numeric", name));
+            };
+            let points = xs
+                .iter()
+                .zip(ys)
+                .filter_map(|(x, y)| Some([(*x)?, (*y)?]))
+                .collect();
+            self.series.push(Series {
+                name: name.to_string(),
+                points,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Where a label sits relative to its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// An SVG drawing primitive, in pixels from the chart's top left corner
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mark {
+    Line {
+        points: Vec<[f32; 2]>,
+        color: [u8; 3],
+        width: f32,
+    },
+    Rect {
+        min: [f32; 2],
+        max: [f32; 2],
+        color: [u8; 3],
+    },
+    Circle {
+        center: [f32; 2],
+        radius: f32,
+        color: [u8; 3],
+    },
+    Text {
+        pos: [f32; 2],
+        text: String,
+        anchor: Anchor,
+        color: [u8; 3],
+    },
+}
+
+/// Color of the nth series
+pub fn series_color(index: usize) -> [u8; 3] {
+    PALETTE[index % PALETTE.len()]
+}
+
+/// Lay out a chart as marks for `render_svg`
+pub fn layout(chart: &Chart, size: [f32; 2]) -> Vec<Mark> {
+    let mut marks = Vec::new();
+    let plot_min = [MARGIN_LEFT, MARGIN_TOP];
+    let plot_max = [size[0] - MARGIN_RIGHT, size[1] - MARGIN_BOTTOM];
+    if plot_max[0] <= plot_min[0] || plot_max[1] <= plot_min[1] {
+        return marks;
+    }
+
+    let points = chart.series.iter().flat_map(|s| &s.points);
+    let (mut x_range, mut y_range) = ([f64::INFINITY, f64::NEG_INFINITY], [0.0_f64, 0.0_f64]);
+    let mut first = true;
+    for [x, y] in points {
+        x_range = [x_range[0].min(*x), x_range[1].max(*x)];
+        y_range = if first && chart.kind != ChartKind::Bar {
+            [*y, *y]
+        } else {
+            [y_range[0].min(*y), y_range[1].max(*y)]
+        };
+        first = false;
+    }
+    if !x_range[0].is_finite() {
+        x_range = [0.0, 1.0];
+    }
+
+    // Bars and categories sit at whole positions with half a slot either side
+    let categorical = chart.kind == ChartKind::Bar || !chart.categories.is_empty();
+    let slot = if chart.kind == ChartKind::Bar {
+        min_spacing(chart)
+    } else {
+        1.0
+    };
+    if categorical {
+        x_range = [x_range[0] - slot / 2.0, x_range[1] + slot / 2.0];
+    }
+    let x_range = widen(x_range);
+    let y_ticks = nice_ticks(widen(y_range));
+    let y_range = [y_ticks[0], y_ticks[y_ticks.len() - 1]];
+
+    let to_x = |x: f64| {
+        plot_min[0]
+            + ((x - x_range[0]) / (x_range[1] - x_range[0])) as f32 * (plot_max[0] - plot_min[0])
+    };
+    let to_y = |y: f64| {
+        plot_max[1]
+            - ((y - y_range[0]) / (y_range[1] - y_range[0])) as f32 * (plot_max[1] - plot_min[1])
+    };
+
+    // Grid and y axis labels
+    for tick in &y_ticks {
+        let y = to_y(*tick);
+        marks.push(Mark::Line {
+            points: vec![[plot_min[0], y], [plot_max[0], y]],
+            color: GRID_COLOR,
+            width: 1.0,
+        });
+        marks.push(Mark::Text {
+            pos: [plot_min[0] - 4.0, y + FONT_SIZE / 3.0],
+            text: format_tick(*tick, y_ticks[1] - y_ticks[0]),
+            anchor: Anchor::End,
+            color: AXIS_COLOR,
+        });
+    }
+
+    // X axis labels: categories, or ticks over the range
+    let label_y = plot_max[1] + FONT_SIZE + 4.0;
+    if chart.categories.is_empty() {
+        let x_ticks = nice_ticks(x_range);
+        for tick in x_ticks
+            .iter()
+            .filter(|t| (x_range[0]..=x_range[1]).contains(*t))
+        {
+            marks.push(Mark::Text {
+                pos: [to_x(*tick), label_y],
+                text: format_tick(*tick, x_ticks[1] - x_ticks[0]),
+                anchor: Anchor::Middle,
+                color: AXIS_COLOR,
+            });
+        }
+    } else {
+        let every = chart.categories.len().div_ceil(12).max(1);
+        for (i, category) in chart.categories.iter().enumerate().step_by(every) {
+            marks.push(Mark::Text {
+                pos: [to_x(i as f64), label_y],
+                text: category.clone(),
+                anchor: Anchor::Middle,
+                color: AXIS_COLOR,
+            });
+        }
+    }
+
+    marks.push(Mark::Line {
+        points: vec![
+            [plot_min[0], plot_min[1]],
+            [plot_min[0], plot_max[1]],
+            plot_max,
+        ],
+        color: AXIS_COLOR,
+        width: 1.0,
+    });
+
+    // Series
+    let bar_width = (to_x(slot) - to_x(0.0)) * 0.8 / chart.series.len().max(1) as f32;
+    for (index, series) in chart.series.iter().enumerate() {
+        let color = series_color(index);
+        match chart.kind {
+            ChartKind::Line => marks.push(Mark::Line {
+                points: series
+                    .points
+                    .iter()
+                    .map(|[x, y]| [to_x(*x), to_y(*y)])
+                    .collect(),
+                color,
+                width: 2.0,
+            }),
+            ChartKind::Scatter => marks.extend(series.points.iter().map(|[x, y]| Mark::Circle {
+                center: [to_x(*x), to_y(*y)],
+                radius: 3.0,
+                color,
+            })),
+            ChartKind::Bar => {
+                let offset = (index as f32 - (chart.series.len() as f32 - 1.0) / 2.0) * bar_width;
+                marks.extend(series.points.iter().map(|[x, y]| {
+                    let center = to_x(*x) + offset;
+                    let (top, bottom) = (to_y(y.max(0.0)), to_y(y.min(0.0)));
+                    Mark::Rect {
+                        min: [center - bar_width / 2.0, top],
+                        max: [center + bar_width / 2.0, bottom],
+                        color,
+                    }
+                }))
+            }
+        }
+    }
+
+    // Title and legend
+    if let Some(title) = &chart.title {
+        marks.push(Mark::Text {
+            pos: [size[0] / 2.0, MARGIN_TOP / 2.0 + FONT_SIZE / 3.0],
+            text: title.clone(),
+            anchor: Anchor::Middle,
+            color: [0, 0, 0],
+        });
+    }
+    if chart.series.len() > 1 {
+        for (index, series) in chart.series.iter().enumerate() {
+            let y = plot_min[1] + 4.0 + index as f32 * (FONT_SIZE + 4.0);
+            marks.push(Mark::Rect {
+                min: [plot_max[0] - 10.0, y],
+                max: [plot_max[0] - 2.0, y + 8.0],
+                color: series_color(index),
+            });
+            marks.push(Mark::Text {
+                pos: [plot_max[0] - 14.0, y + 8.0],
+                text: series.name.clone(),
+                anchor: Anchor::End,
+                color: AXIS_COLOR,
+            });
+        }
+    }
+
+    marks
+}
+
+/// Render a chart as a standalone SVG document
+pub fn render_svg(chart: &Chart, size: [f32; 2]) -> String {
+    fn rgb([r, g, b]: [u8; 3]) -> String {
+        format!("rgb({},{},{})", r, g, b)
+    }
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"{f}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        w = size[0],
+        h = size[1],
+        f = FONT_SIZE
+    );
+    for mark in layout(chart, size) {
+        // Writing to a String can't fail
+        let _ = match mark {
+            Mark::Line {
+                points,
+                color,
+                width,
+            } => {
+                let points: Vec<String> = points
+                    .iter()
+                    .map(|[x, y]| format!("{:.1},{:.1}", x, y))
+                    .collect();
+                writeln!(
+                    svg,
+                    "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                    points.join(" "),
+                    rgb(color),
+                    width
+                )
+            }
+            Mark::Rect { min, max, color } => writeln!(
+                svg,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+                min[0],
+                min[1],
+                max[0] - min[0],
+                max[1] - min[1],
+                rgb(color)
+            ),
+            Mark::Circle {
+                center,
+                radius,
+                color,
+            } => writeln!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\"/>",
+                center[0],
+                center[1],
+                radius,
+                rgb(color)
+            ),
+            Mark::Text {
+                pos,
+                text,
+                anchor,
+                color,
+            } => {
+                let anchor = match anchor {
+                    Anchor::Start => "start",
+                    Anchor::Middle => "middle",
+                    Anchor::End => "end",
+                };
+                writeln!(
+                    svg,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" fill=\"{}\">{}</text>",
+                    pos[0],
+                    pos[1],
+                    anchor,
+                    rgb(color),
+                    escape_xml(&text)
+                )
+            }
+        };
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Smallest gap between distinct x values (1 with fewer than two), the width of a bar slot
+pub fn min_spacing(chart: &Chart) -> f64 {
+    let mut xs: Vec<f64> = chart
+        .series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|p| p[0]))
+        .collect();
+    xs.sort_by(f64::total_cmp);
+    xs.dedup();
+    xs.windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .min_by(f64::total_cmp)
+        .unwrap_or(1.0)
+}
+
+/// Give an empty range some extent
+fn widen([min, max]: [f64; 2]) -> [f64; 2] {
+    if max > min {
+        [min, max]
+    } else {
+        [min - 1.0, max + 1.0]
+    }
+}
+
+/// Round tick values (steps of 1, 2 or 5 times a power of ten) covering a range
+fn nice_ticks([min, max]: [f64; 2]) -> Vec<f64> {
+    let rough = (max - min) / 5.0;
+    let magnitude = 10_f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).floor() as i64;
+    let last = (max / step).ceil() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// Format a tick with as many decimals as its step needs
+fn format_tick(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_chart() {
+        let spec = ChartSpec::parse(r#"{"kind": "bar", "x": "month"}"#).unwrap();
+        let table = Table::from_json_rows(&json!([
+            {"month": "Jan", "sales": 3, "costs": 1, "note": "x"},
+            {"month": "Feb", "sales": null, "costs": 2, "note": "y"},
+        ]))
+        .unwrap();
+        let inputs = [
+            CellData::Table(table),
+            CellData::Json(json!({"trend": [[0, 1], [1, 2.5]]})),
+        ];
+        let chart = Chart::build(&spec, &inputs, &[("A1".to_string(), 4.0)]).unwrap();
+
+        assert_eq!(chart.kind, ChartKind::Bar);
+        assert_eq!(chart.categories, vec!["A1"]);
+        let names: Vec<&str> = chart.series.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["costs", "sales", "trend", "cells"]);
+        assert_eq!(chart.series[1].points, vec![[0.0, 3.0]]);
+        assert_eq!(chart.series[2].points, vec![[0.0, 1.0], [1.0, 2.5]]);
+
+        assert_eq!(ChartSpec::parse("").unwrap().kind, ChartKind::Line);
+        assert!(ChartSpec::parse(r#"{"kind": "pie"}"#).is_err());
+        let bad_y = ChartSpec {
+            y: vec!["note".to_string()],
+            ..ChartSpec::default()
+        };
+        assert!(Chart::build(&bad_y, &inputs[..1], &[]).is_err());
+        assert!(Chart::build(&ChartSpec::default(), &[CellData::Number(1.0)], &[]).is_err());
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let chart = Chart {
+            kind: ChartKind::Line,
+            title: Some("Growth <%>".to_string()),
+            series: vec![Series {
+                name: "y".to_string(),
+                points: vec![[0.0, 0.0], [1.0, 5.0], [2.0, 10.0]],
+            }],
+            categories: Vec::new(),
+        };
+        let svg = render_svg(&chart, DEFAULT_SVG_SIZE);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"480\""));
+        assert!(svg.contains(">Growth &lt;%&gt;</text>"));
+        // The series runs from the bottom left to the top right of the plot area
+        assert!(svg.contains("points=\"48.0,292.0 258.0,160.0 468.0,28.0\""));
+        assert_eq!(
+            crate::images::detect_format(svg.as_bytes()),
+            Some(crate::images::ImageFormat::Svg)
+        );
+
+        assert_eq!(nice_ticks([0.0, 10.0]), vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(format_tick(0.25, 0.05), "0.25");
+    }
+}
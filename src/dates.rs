@@ -0,0 +1,207 @@
+//! Date cells: reading and showing date-times in a cell's format and time zone
+
+use crate::{Cell, CellContent, CellType};
+use anyhow::{anyhow, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Format new cells show dates in
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Time zone new cells read and show dates in
+pub const DEFAULT_TIME_ZONE: &str = "UTC";
+
+/// Layouts also accepted when content doesn't match the cell's format
+const FALLBACK_FORMATS: [&str; 3] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%d"];
+
+const MILLIS_PER_DAY: f64 = 86_400_000.0;
+
+/// Look up an IANA time zone such as "Europe/Paris" or "UTC"
+pub fn parse_time_zone(name: &str) -> Result<Tz> {
+    name.trim()
+        .parse()
+        .map_err(|_| anyhow!("Unknown time zone '{}'", name))
+}
+
+/// Check that a strftime format is valid (chrono panics when formatting with a bad one)
+pub fn check_format(format: &str) -> Result<()> {
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(anyhow!("Invalid date format '{}'", format));
+    }
+    Ok(())
+}
+
+/// Read a date-time written in `format` (or RFC 3339, or one of the ISO layouts) as
+/// local time in `tz`; a date alone is midnight
+pub fn parse_datetime(text: &str, format: &str, tz: Tz) -> Result<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(value) = DateTime::parse_from_rfc3339(text) {
+        return Ok(value.with_timezone(&Utc));
+    }
+
+    let naive = std::iter::once(format)
+        .chain(FALLBACK_FORMATS)
+        .find_map(|format| {
+            NaiveDateTime::parse_from_str(text, format)
+                .ok()
+                .or_else(|| {
+                    NaiveDate::parse_from_str(text, format)
+                        .ok()
+                        .map(|date| date.and_time(Default::default()))
+                })
+        })
+        .ok_or_else(|| anyhow!("Cannot read '{}' as a date in format '{}'", text, format))?;
+    from_local(naive, tz)
+}
+
+/// Write a date-time as local time in `tz`
+pub fn format_datetime(value: DateTime<Utc>, format: &str, tz: Tz) -> Result<String> {
+    check_format(format)?;
+    Ok(value.with_timezone(&tz).format(format).to_string())
+}
+
+/// Days since 1970-01-01 of a date-time's local time in `tz`; Math formulas see dates
+/// this way, so subtracting two dates counts calendar days
+pub fn to_day_number(value: DateTime<Utc>, tz: Tz) -> f64 {
+    let local = value.with_timezone(&tz).naive_local();
+    (local - epoch()).num_milliseconds() as f64 / MILLIS_PER_DAY
+}
+
+/// Date-time of a day number in `tz` (the inverse of `to_day_number`)
+pub fn from_day_number(days: f64, tz: Tz) -> Result<DateTime<Utc>> {
+    if !days.is_finite() {
+        return Err(anyhow!("{} is not a date", days));
+    }
+    let naive = TimeDelta::try_milliseconds((days * MILLIS_PER_DAY).round() as i64)
+        .and_then(|offset| epoch().checked_add_signed(offset))
+        .ok_or_else(|| anyhow!("{} days is out of range", days))?;
+    from_local(naive, tz)
+}
+
+/// Day number of a calendar date
+pub fn date_to_day_number(date: NaiveDate) -> f64 {
+    (date - epoch().date()).num_days() as f64
+}
+
+/// Calendar date a day number falls on
+pub fn day_number_to_date(days: f64) -> Option<NaiveDate> {
+    TimeDelta::try_days(days.floor() as i64)
+        .and_then(|offset| epoch().date().checked_add_signed(offset))
+}
+
+fn epoch() -> NaiveDateTime {
+    DateTime::UNIX_EPOCH.naive_utc()
+}
+
+/// Local time in a zone as an instant; the earlier one when clocks go back
+fn from_local(naive: NaiveDateTime, tz: Tz) -> Result<DateTime<Utc>> {
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|value| value.with_timezone(&Utc))
+        .ok_or_else(|| anyhow!("{} doesn't exist in {}", naive, tz))
+}
+
+/// A Date cell's value, read from its content in its format and time zone
+pub fn cell_datetime(cell: &Cell) -> Result<DateTime<Utc>> {
+    let content = cell
+        .content
+        .as_str()
+        .ok_or_else(|| anyhow!("Date cell has no inline content"))?;
+    parse_datetime(
+        content,
+        &cell.date_format,
+        parse_time_zone(&cell.time_zone)?,
+    )
+}
+
+/// A date-time in a cell's format and time zone
+pub fn format_for_cell(value: DateTime<Utc>, cell: &Cell) -> Result<String> {
+    format_datetime(value, &cell.date_format, parse_time_zone(&cell.time_zone)?)
+}
+
+/// Write a Math result (a day number) into a Date cell's content; other cells are left
+/// alone, as is a Date cell whose format or time zone is invalid
+pub fn store_day_number(cell: &mut Cell, days: f64) {
+    if cell.cell_type != CellType::Date {
+        return;
+    }
+    let formatted = parse_time_zone(&cell.time_zone)
+        .and_then(|tz| from_day_number(days, tz))
+        .and_then(|value| format_for_cell(value, cell));
+    if let Ok(formatted) = formatted {
+        cell.content = CellContent::inline(formatted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rectangle;
+
+    #[test]
+    fn test_parse_and_format() {
+        let paris = parse_time_zone("Europe/Paris").unwrap();
+        let value = parse_datetime("31/03/2024 09:30", "%d/%m/%Y %H:%M", paris).unwrap();
+        assert_eq!(value.to_rfc3339(), "2024-03-31T07:30:00+00:00");
+        assert_eq!(
+            format_datetime(value, "%Y-%m-%d %H:%M %Z", paris).unwrap(),
+            "2024-03-31 09:30 CEST"
+        );
+        assert_eq!(
+            format_datetime(value, DEFAULT_DATE_FORMAT, Tz::UTC).unwrap(),
+            "2024-03-31"
+        );
+
+        // ISO layouts and RFC 3339 are read whatever the format
+        let midnight = value.date_naive().and_time(Default::default()).and_utc();
+        assert_eq!(
+            parse_datetime("2024-03-31", "%d/%m/%Y", Tz::UTC).unwrap(),
+            midnight
+        );
+        assert_eq!(
+            parse_datetime("2024-03-31T07:30:00Z", "%d/%m/%Y", paris).unwrap(),
+            value
+        );
+
+        assert!(parse_datetime("someday", DEFAULT_DATE_FORMAT, Tz::UTC).is_err());
+        // Clocks in Paris skip from 02:00 to 03:00 that night
+        assert!(parse_datetime("2024-03-31 02:30", DEFAULT_DATE_FORMAT, paris).is_err());
+        assert!(check_format("%Y-%Q").is_err());
+        assert!(parse_time_zone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_day_numbers() {
+        let paris = parse_time_zone("Europe/Paris").unwrap();
+        let before = parse_datetime("2024-03-30", DEFAULT_DATE_FORMAT, paris).unwrap();
+        let after = parse_datetime("2024-04-01", DEFAULT_DATE_FORMAT, paris).unwrap();
+        // Whole days across the change to summer time
+        assert_eq!(
+            to_day_number(after, paris) - to_day_number(before, paris),
+            2.0
+        );
+        assert_eq!(to_day_number(before, Tz::UTC), 19811.958333333332);
+
+        let days = to_day_number(after, paris) + 0.5;
+        assert_eq!(
+            from_day_number(days, paris).unwrap().to_rfc3339(),
+            "2024-04-01T10:00:00+00:00"
+        );
+        assert!(from_day_number(f64::NAN, paris).is_err());
+
+        let mut cell = Cell::new(
+            CellType::Date,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline(""),
+            "A1".to_string(),
+        );
+        cell.date_format = "%d %b %Y".to_string();
+        store_day_number(&mut cell, 19_813.0);
+        assert_eq!(cell.content.as_str(), Some("31 Mar 2024"));
+        assert_eq!(
+            cell_datetime(&cell).unwrap(),
+            before + TimeDelta::days(1) + TimeDelta::hours(1)
+        );
+    }
+}
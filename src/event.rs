@@ -1,4 +1,4 @@
-use crate::{CellContent, CellType, Rectangle};
+use crate::{CellContent, CellType, Guard, LoopConfig, Rectangle, Transform};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use ulid::Ulid;
@@ -74,11 +74,46 @@ pub enum EventType {
         to: Ulid,
     },
 
+    RelationshipPortsChanged {
+        from: Ulid,
+        to: Ulid,
+        source_key: Option<String>,
+        target_input: Option<String>,
+    },
+
+    RelationshipTransformChanged {
+        from: Ulid,
+        to: Ulid,
+        transform: Option<Transform>,
+    },
+
+    RelationshipGuardChanged {
+        from: Ulid,
+        to: Ulid,
+        guard: Option<Guard>,
+    },
+
+    RelationshipLoopChanged {
+        from: Ulid,
+        to: Ulid,
+        loop_config: Option<LoopConfig>,
+    },
+
+    InputsReordered {
+        cell_id: Ulid,
+        order: Vec<Ulid>,
+    },
+
     StartPointChanged {
         old_id: Option<Ulid>,
         new_id: Ulid,
     },
 
+    EntryPointChanged {
+        id: Ulid,
+        name: Option<String>,
+    },
+
     /// For future undo/redo and time-travel debugging
     SnapshotCreated {
         snapshot_id: Ulid,
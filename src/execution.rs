@@ -1,9 +1,27 @@
-use crate::{Canvas, Cell, CellContent, CellType};
+use crate::chart::{Chart, ChartSpec};
+use crate::dates::{cell_datetime, parse_datetime, parse_time_zone};
+use crate::images::{decode_inline, ensure_image, markdown_image, read_image_content};
+use crate::params::{redact, redact_data};
+use crate::python_env::{run_python, DEFAULT_PYTHON_TIMEOUT_SECS};
+use crate::schema::{ensure_matches, parse_json_content};
+use crate::script::{check_script, run_script, ScriptLimits};
+use crate::shell::{missing_commands, run_shell_command, DEFAULT_SHELL_TIMEOUT_SECS};
+use crate::sql::{check_query, load_table, open_database, run_query};
+use crate::table::read_csv_content;
+use crate::transform::{as_json, evaluate_condition};
+use crate::validation::{
+    python_type_annotations, ValidationIssue, ValidationIssueType, ValidationSeverity,
+};
+use crate::wasm::{check_module, run_module, WasmSandbox};
+use crate::{Canvas, Cell, CellContent, CellType, ColumnValues, Relationship, Table};
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyDict, PyList};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use ulid::Ulid;
 
 /// Data types that can be passed between cells
@@ -15,6 +33,9 @@ pub enum CellData {
     Boolean(bool),
     Json(serde_json::Value),
     Binary(Vec<u8>),
+    Table(Table),
+    /// An instant; Date cells show it in their own format and time zone
+    DateTime(DateTime<Utc>),
 }
 
 impl CellData {
@@ -30,6 +51,32 @@ impl CellData {
                 // Output as hex with warning
                 format!("[Binary data: {} bytes]", b.len())
             }
+            CellData::Table(t) => t.to_csv(),
+            CellData::DateTime(d) => d.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        }
+    }
+
+    /// One-line human-readable form for reports: text and numbers as they are, long or
+    /// multi-line text cut short, and placeholders for images, other bytes and tables
+    pub fn summary(&self) -> String {
+        const MAX_CHARS: usize = 80;
+        match self {
+            CellData::None => "None".to_string(),
+            CellData::Binary(b) => match crate::images::detect_format(b) {
+                Some(_) => format!("<image {} bytes>", b.len()),
+                None => format!("<binary {} bytes>", b.len()),
+            },
+            CellData::Table(t) => format!("<table {}×{}>", t.row_count(), t.columns().len()),
+            other => {
+                let text = other.coerce_to_string();
+                let line = text.lines().next().unwrap_or_default();
+                if line.len() < text.len() || line.chars().count() > MAX_CHARS {
+                    let cut: String = line.chars().take(MAX_CHARS).collect();
+                    format!("{}…", cut)
+                } else {
+                    text
+                }
+            }
         }
     }
 
@@ -37,6 +84,74 @@ impl CellData {
     pub fn is_none(&self) -> bool {
         matches!(self, CellData::None)
     }
+
+    /// Type of this value
+    pub fn data_type(&self) -> DataType {
+        match self {
+            CellData::None => DataType::None,
+            CellData::Text(_) => DataType::Text,
+            CellData::Number(_) => DataType::Number,
+            CellData::Boolean(_) => DataType::Boolean,
+            CellData::Json(_) => DataType::Json,
+            CellData::Binary(_) => DataType::Binary,
+            CellData::Table(_) => DataType::Table,
+            CellData::DateTime(_) => DataType::DateTime,
+        }
+    }
+
+    /// Python-style truthiness (used by relationship guards)
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            CellData::None => false,
+            CellData::Text(s) => !s.is_empty(),
+            CellData::Number(n) => *n != 0.0,
+            CellData::Boolean(b) => *b,
+            CellData::Json(v) => match v {
+                serde_json::Value::Null => false,
+                serde_json::Value::Bool(b) => *b,
+                serde_json::Value::Number(n) => n.as_f64() != Some(0.0),
+                serde_json::Value::String(s) => !s.is_empty(),
+                serde_json::Value::Array(items) => !items.is_empty(),
+                serde_json::Value::Object(map) => !map.is_empty(),
+            },
+            CellData::Binary(b) => !b.is_empty(),
+            CellData::Table(t) => t.row_count() > 0,
+            CellData::DateTime(_) => true,
+        }
+    }
+}
+
+/// Type of the data a cell produces or expects, inferred by dry run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DataType {
+    /// Unknown, or anything goes
+    Any,
+    None,
+    Text,
+    Number,
+    Boolean,
+    Json,
+    Binary,
+    Table,
+    DateTime,
+}
+
+impl DataType {
+    /// Check if data of the `actual` type satisfies this expected type
+    ///
+    /// Tables reach Python as lists of row dicts, so they satisfy `Json` too.
+    pub fn accepts(self, actual: DataType) -> bool {
+        self == DataType::Any
+            || actual == DataType::Any
+            || self == actual
+            || (self == DataType::Json && actual == DataType::Table)
+    }
+}
+
+impl std::fmt::Display for DataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
 }
 
 /// Execution mode
@@ -56,17 +171,149 @@ pub enum ExecutionStatus {
     Complete,
     DryRunComplete,
     Error(String),
+    /// A loop wanted more passes than its `max_iterations`; the back edge wasn't followed
+    IterationLimitReached {
+        from: Ulid,
+        to: Ulid,
+        max_iterations: usize,
+    },
+}
+
+/// What happens downstream when a cell fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ErrorPolicy {
+    /// Abort the whole run (default)
+    #[default]
+    FailFast,
+    /// Record the error and keep running; downstream cells get `None` in the failed cell's
+    /// input slot, so the other inputs keep their positions
+    Continue,
+    /// Record the error and skip every cell downstream of the failed cell
+    SkipDownstream,
+}
+
+impl ErrorPolicy {
+    /// All policies, in UI order
+    pub const ALL: [ErrorPolicy; 3] = [
+        ErrorPolicy::FailFast,
+        ErrorPolicy::Continue,
+        ErrorPolicy::SkipDownstream,
+    ];
+
+    /// Short human-readable name
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorPolicy::FailFast => "Fail fast",
+            ErrorPolicy::Continue => "Continue",
+            ErrorPolicy::SkipDownstream => "Skip downstream",
+        }
+    }
+}
+
+/// Outcome of a cell in an execution run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellOutcome {
+    Succeeded,
+    Failed(String),
+    /// Not run because a cell upstream of it failed (skip-downstream policy)
+    Skipped,
 }
 
 /// Single execution log entry
 #[derive(Debug, Clone)]
 pub struct ExecutionLogEntry {
     pub step: usize,
+    /// Loop pass the cell ran in (0 until a loop back edge is followed)
+    pub iteration: usize,
     pub cell_id: Ulid,
     pub cell_name: Option<String>,
     pub output: CellData,
     pub dry_run: bool,
     pub error: Option<String>,
+    /// Text written to `sys.stdout` while the cell ran
+    pub stdout: String,
+    /// Text written to `sys.stderr` while the cell ran
+    pub stderr: String,
+    /// Python traceback if the cell raised an exception
+    pub traceback: Option<PythonTraceback>,
+    /// Cells read through the `cells` API (implicit dependencies), in first-access order
+    pub cell_reads: Vec<Ulid>,
+    /// Every value set with `set_output(value, key=...)`, by key
+    pub output_ports: BTreeMap<String, CellData>,
+    /// Downstream cells whose relationship was followed
+    pub branches_taken: Vec<Ulid>,
+    /// Downstream cells whose relationship guard did not hold
+    pub branches_skipped: Vec<Ulid>,
+}
+
+impl ExecutionLogEntry {
+    /// Check if the cell produced any console output or traceback
+    pub fn has_console_output(&self) -> bool {
+        !self.stdout.is_empty() || !self.stderr.is_empty() || self.traceback.is_some()
+    }
+}
+
+/// Console output captured while a single cell executes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CapturedOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub traceback: Option<PythonTraceback>,
+    pub cell_reads: Vec<Ulid>,
+    pub output_ports: BTreeMap<String, CellData>,
+}
+
+/// Python exception with its stack, line numbers mapped back to the cell content
+#[derive(Debug, Clone, PartialEq)]
+pub struct PythonTraceback {
+    /// Exception class name (e.g. "ZeroDivisionError")
+    pub exception_type: String,
+    /// Exception message
+    pub message: String,
+    /// Stack frames, outermost first
+    pub frames: Vec<TracebackFrame>,
+}
+
+impl PythonTraceback {
+    /// Line in the cell content where the exception was raised (innermost cell frame)
+    pub fn cell_line(&self) -> Option<usize> {
+        self.frames.iter().rev().find_map(|f| f.cell_line)
+    }
+}
+
+impl std::fmt::Display for PythonTraceback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Traceback (most recent call last):")?;
+        for frame in &self.frames {
+            match frame.cell_line {
+                Some(line) => writeln!(f, "  cell line {}, in {}", line, frame.function)?,
+                None => writeln!(
+                    f,
+                    "  File \"{}\", line {}, in {}",
+                    frame.filename, frame.line, frame.function
+                )?,
+            }
+            if let Some(source) = &frame.source {
+                writeln!(f, "    {}", source)?;
+            }
+        }
+        write!(f, "{}: {}", self.exception_type, self.message)
+    }
+}
+
+/// Single frame of a Python traceback
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracebackFrame {
+    /// File name reported by Python
+    pub filename: String,
+    /// Line number reported by Python
+    pub line: usize,
+    /// Line in the cell content (1-based), if this frame is the cell's own code
+    pub cell_line: Option<usize>,
+    /// Function name ("<module>" for top-level cell code)
+    pub function: String,
+    /// Source text of the line, if available
+    pub source: Option<String>,
 }
 
 /// Complete execution report
@@ -76,6 +323,107 @@ pub struct ExecutionReport {
     pub step: usize,
     pub log: Vec<ExecutionLogEntry>,
     pub total_cells_executed: usize,
+    /// Dry run only: relationships (from, to) whose guard can never hold
+    pub unreachable_branches: Vec<(Ulid, Ulid)>,
+    /// Cell a breakpoint paused execution at (before it ran)
+    pub paused_at: Option<Ulid>,
+    /// Latest outcome of every cell that ran, failed or was skipped
+    pub outcomes: BTreeMap<Ulid, CellOutcome>,
+    /// Dry run only: inputs whose inferred type doesn't match what the consumer expects
+    pub type_mismatches: Vec<ValidationIssue>,
+}
+
+impl ExecutionReport {
+    /// Cells that ran successfully
+    pub fn succeeded(&self) -> Vec<Ulid> {
+        self.cells_where(|outcome| matches!(outcome, CellOutcome::Succeeded))
+    }
+
+    /// Cells that failed
+    pub fn failed(&self) -> Vec<Ulid> {
+        self.cells_where(|outcome| matches!(outcome, CellOutcome::Failed(_)))
+    }
+
+    /// Cells skipped because a cell upstream failed
+    pub fn skipped(&self) -> Vec<Ulid> {
+        self.cells_where(|outcome| matches!(outcome, CellOutcome::Skipped))
+    }
+
+    /// The run as a Markdown document, a section per executed cell with its output or
+    /// error; images are embedded as `data:` URIs
+    pub fn to_markdown(&self, canvas: &Canvas) -> String {
+        let mut document = String::new();
+        for entry in &self.log {
+            let label = canvas.get_cell(entry.cell_id).map(|c| c.label()).unwrap_or_default();
+            let body = match (&entry.error, &entry.output) {
+                (Some(error), _) => format!("**Error:** {}", error),
+                (None, CellData::Binary(bytes)) => markdown_image(&label, bytes)
+                    .unwrap_or_else(|_| entry.output.coerce_to_string()),
+                (None, CellData::Table(table)) => format!("```csv\n{}\n```", table.to_csv()),
+                (None, output) => output.coerce_to_string(),
+            };
+            document.push_str(&format!("## {} (step {})\n\n{}\n\n", label, entry.step, body));
+        }
+        document
+    }
+
+    fn cells_where(&self, predicate: impl Fn(&CellOutcome) -> bool) -> Vec<Ulid> {
+        self.outcomes
+            .iter()
+            .filter(|(_, outcome)| predicate(outcome))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+/// Breakpoint on a cell; execution pauses before the cell runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// Math expression over the cell's inputs (`input_0`, `input_1`, ..., `x` = `input_0`);
+    /// the breakpoint only pauses when it evaluates to true
+    pub condition: Option<String>,
+}
+
+impl Breakpoint {
+    /// Breakpoint that always pauses
+    pub fn new() -> Self {
+        Self { condition: None }
+    }
+
+    /// Breakpoint that pauses when a condition over the inputs holds
+    pub fn conditional(condition: impl Into<String>) -> Self {
+        Self {
+            condition: Some(condition.into()),
+        }
+    }
+
+    /// Check if execution should pause for a cell receiving these inputs
+    pub fn should_pause(&self, inputs: &[CellData]) -> Result<bool> {
+        let Some(condition) = self.condition.as_deref().filter(|c| !c.trim().is_empty()) else {
+            return Ok(true);
+        };
+
+        let mut variables: Vec<(String, CellData)> = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, input)| (format!("input_{}", i), input.clone()))
+            .collect();
+        if let Some(first) = inputs.first() {
+            variables.push(("x".to_string(), first.clone()));
+        }
+        evaluate_condition(condition, &variables)
+            .map_err(|e| anyhow!("Breakpoint condition: {}", e))
+    }
+}
+
+/// Outgoing relationships of an executed cell, split by guard outcome
+#[derive(Debug, Default)]
+struct Branches {
+    taken: Vec<Ulid>,
+    skipped: Vec<Ulid>,
+    unreachable: Vec<Ulid>,
+    /// Taken loop back edges: (loop start, max iterations)
+    loop_backs: Vec<(Ulid, usize)>,
 }
 
 /// Execution engine state
@@ -98,6 +446,77 @@ pub struct ExecutionEngine {
     /// Cell outputs (stored between steps)
     cell_outputs: HashMap<Ulid, CellData>,
 
+    /// Named output ports per cell (stored between steps)
+    port_outputs: HashMap<Ulid, BTreeMap<String, CellData>>,
+
+    /// Relationships (from, to) not followed because their guard didn't hold
+    skipped_edges: HashSet<(Ulid, Ulid)>,
+
+    /// Dry run: guarded relationships that can never be followed
+    unreachable_branches: Vec<(Ulid, Ulid)>,
+
+    /// Times each loop back edge (from, to) has been followed
+    loop_iterations: HashMap<(Ulid, Ulid), usize>,
+
+    /// Loop pass each queued cell will run in
+    cell_iterations: HashMap<Ulid, usize>,
+
+    /// Breakpoints by cell
+    breakpoints: HashMap<Ulid, Breakpoint>,
+
+    /// Cells of the current step not yet executed when a breakpoint paused execution
+    pending_step_cells: Vec<Ulid>,
+
+    /// Cell a breakpoint paused execution at, and the inputs it will receive
+    paused_at: Option<Ulid>,
+    paused_inputs: Vec<CellData>,
+
+    /// Cell whose breakpoint was just continued from (not checked again on resume)
+    resume_past: Option<Ulid>,
+
+    /// Cells a partial run is limited to (None follows every relationship)
+    scope: Option<HashSet<Ulid>>,
+
+    /// Error policy for cells without their own
+    error_policy: ErrorPolicy,
+
+    /// Latest outcome per cell in the current run
+    outcomes: BTreeMap<Ulid, CellOutcome>,
+
+    /// Cells downstream of a failure that must not run (skip-downstream policy)
+    skipped_cells: HashSet<Ulid>,
+
+    /// Dry run: inferred output type per cell
+    inferred_types: HashMap<Ulid, DataType>,
+
+    /// Dry run: type mismatches found so far
+    type_mismatches: Vec<ValidationIssue>,
+
+    /// Resolved run parameters (Python `params`, Math variables)
+    params: BTreeMap<String, CellData>,
+
+    /// Parameters that are secrets, masked in the log and kept from Shell cells
+    secret_names: HashSet<String>,
+
+    /// Pass secrets to Shell cells anyway
+    shell_secrets: bool,
+
+    /// Interpreter Python cells run in as a subprocess (None runs them in-process)
+    python_interpreter: Option<PathBuf>,
+
+    /// Project directory Shell cells run in (None = the current directory)
+    project_dir: Option<PathBuf>,
+
+    /// SQLite database SQL cells query, relative to the project directory
+    /// (None = a fresh in-memory database per cell)
+    sql_database: Option<PathBuf>,
+
+    /// Resource limits Script cells run under
+    script_limits: ScriptLimits,
+
+    /// Limits and capabilities WASM cells run with
+    wasm_sandbox: WasmSandbox,
+
     /// Execution status
     status: ExecutionStatus,
 }
@@ -112,6 +531,30 @@ impl ExecutionEngine {
             executed_this_step: HashSet::new(),
             log: Vec::new(),
             cell_outputs: HashMap::new(),
+            port_outputs: HashMap::new(),
+            skipped_edges: HashSet::new(),
+            unreachable_branches: Vec::new(),
+            loop_iterations: HashMap::new(),
+            cell_iterations: HashMap::new(),
+            breakpoints: HashMap::new(),
+            pending_step_cells: Vec::new(),
+            paused_at: None,
+            paused_inputs: Vec::new(),
+            resume_past: None,
+            scope: None,
+            error_policy: ErrorPolicy::FailFast,
+            outcomes: BTreeMap::new(),
+            skipped_cells: HashSet::new(),
+            inferred_types: HashMap::new(),
+            type_mismatches: Vec::new(),
+            params: BTreeMap::new(),
+            secret_names: HashSet::new(),
+            shell_secrets: false,
+            python_interpreter: None,
+            project_dir: None,
+            sql_database: None,
+            script_limits: ScriptLimits::default(),
+            wasm_sandbox: WasmSandbox::default(),
             status: ExecutionStatus::NotStarted,
         }
     }
@@ -122,17 +565,130 @@ impl ExecutionEngine {
         let start_cell = canvas
             .get_start_point()
             .ok_or_else(|| anyhow!("No start point set"))?;
+        self.execute_all_from(canvas, start_cell.id)
+    }
+
+    /// Start execution from a named entry point
+    pub fn execute_entry_point(&mut self, canvas: &Canvas, name: &str) -> Result<ExecutionReport> {
+        let entry = canvas
+            .get_entry_point(name)
+            .ok_or_else(|| anyhow!("No entry point named '{}'", name))?;
+        self.execute_all_from(canvas, entry.id)
+    }
+
+    /// Clear all cached outputs and execute everything reachable from a cell
+    fn execute_all_from(&mut self, canvas: &Canvas, start: Ulid) -> Result<ExecutionReport> {
+        self.cell_outputs.clear();
+        self.port_outputs.clear();
+        self.inferred_types.clear();
+        self.skipped_edges.clear();
+        self.loop_iterations.clear();
+        self.cell_iterations.clear();
+        self.scope = None;
+
+        self.start_run(canvas, start)
+    }
+
+    /// Start execution from any cell, reusing cached outputs of the cells upstream of it
+    ///
+    /// Only the cell and the cells downstream of it run; everything else keeps the output
+    /// of the previous run.
+    pub fn execute_from(&mut self, canvas: &Canvas, cell_id: Ulid) -> Result<ExecutionReport> {
+        let scope = canvas.downstream_cells(cell_id);
+        self.start_partial_run(canvas, cell_id, scope)
+    }
+
+    /// Run only the subgraph induced by the cells on paths from `from` to `to`
+    ///
+    /// Inputs from cells outside the subgraph come from the previous run's cached outputs.
+    pub fn execute_subgraph(
+        &mut self,
+        canvas: &Canvas,
+        from: Ulid,
+        to: Ulid,
+    ) -> Result<ExecutionReport> {
+        let downstream = canvas.downstream_cells(from);
+        if !downstream.contains(&to) {
+            return Err(anyhow!(
+                "Cell {} is not downstream of {}",
+                relationship_label(canvas, to),
+                relationship_label(canvas, from)
+            ));
+        }
+        let upstream = canvas.upstream_cells(to);
+        let scope = downstream.intersection(&upstream).copied().collect();
+        self.start_partial_run(canvas, from, scope)
+    }
+
+    /// Invalidate cached outputs inside `scope` and run it starting at `start`
+    fn start_partial_run(
+        &mut self,
+        canvas: &Canvas,
+        start: Ulid,
+        scope: HashSet<Ulid>,
+    ) -> Result<ExecutionReport> {
+        canvas
+            .get_cell(start)
+            .ok_or_else(|| anyhow!("Cell not found: {}", start))?;
+        if matches!(self.status, ExecutionStatus::DryRunComplete)
+            && !matches!(self.mode, ExecutionMode::DryRun)
+        {
+            return Err(anyhow!("Cached outputs come from a dry run; run the pipeline first"));
+        }
+
+        // The start cell's inputs from outside the run must have been computed before
+        for rel in canvas.get_incoming_relationships(start) {
+            let cached = self.cell_outputs.contains_key(&rel.from)
+                || self.skipped_edges.contains(&(rel.from, rel.to));
+            if !scope.contains(&rel.from) && !cached {
+                return Err(anyhow!(
+                    "No cached output for {} (input of {}); run it first",
+                    relationship_label(canvas, rel.from),
+                    relationship_label(canvas, start)
+                ));
+            }
+        }
+
+        for cell_id in &scope {
+            self.cell_outputs.remove(cell_id);
+            self.port_outputs.remove(cell_id);
+            self.inferred_types.remove(cell_id);
+            self.cell_iterations.remove(cell_id);
+        }
+        self.skipped_edges.retain(|(from, _)| !scope.contains(from));
+        self.loop_iterations.retain(|(from, _), _| !scope.contains(from));
+        self.scope = Some(scope);
 
+        self.start_run(canvas, start)
+    }
+
+    /// Reset per-run state and execute starting at a cell
+    fn start_run(&mut self, canvas: &Canvas, start: Ulid) -> Result<ExecutionReport> {
         self.status = ExecutionStatus::Running;
-        self.execution_queue = vec![start_cell.id];
+        self.execution_queue = vec![start];
         self.current_step = 0;
         self.executed_this_step.clear();
-        self.cell_outputs.clear();
+        self.unreachable_branches.clear();
         self.log.clear();
+        self.outcomes.clear();
+        self.skipped_cells.clear();
+        self.type_mismatches.clear();
+        self.pending_step_cells.clear();
+        self.paused_at = None;
+        self.paused_inputs.clear();
+        self.resume_past = None;
+
+        self.run_until_pause(canvas)
+    }
 
-        // Execute until queue is empty or step mode pauses
-        while !self.execution_queue.is_empty() {
-            self.current_step += 1;
+    /// Execute steps until the queue is empty, a breakpoint or step mode pauses, or a loop
+    /// hits its bound
+    fn run_until_pause(&mut self, canvas: &Canvas) -> Result<ExecutionReport> {
+        while !self.execution_queue.is_empty() || !self.pending_step_cells.is_empty() {
+            // Resuming from a breakpoint finishes the interrupted step first
+            if self.pending_step_cells.is_empty() {
+                self.current_step += 1;
+            }
 
             // Execute one step
             if let Err(e) = self.execute_step(canvas) {
@@ -140,6 +696,13 @@ impl ExecutionEngine {
                 return Err(e);
             }
 
+            // A loop hit its bound, or a breakpoint paused execution
+            if matches!(self.status, ExecutionStatus::IterationLimitReached { .. })
+                || self.paused_at.is_some()
+            {
+                return Ok(self.create_report());
+            }
+
             // In step mode, pause after each step
             if self.mode == ExecutionMode::Step {
                 self.status = ExecutionStatus::Paused;
@@ -159,160 +722,1059 @@ impl ExecutionEngine {
 
     /// Execute one step of the execution graph
     fn execute_step(&mut self, canvas: &Canvas) -> Result<()> {
-        // Current step cells (sorted by ULID for deterministic order)
-        let mut current_step_cells = self.execution_queue.clone();
-        current_step_cells.sort();
-        self.execution_queue.clear();
-        self.executed_this_step.clear();
+        let current_step_cells = if self.pending_step_cells.is_empty() {
+            // Current step cells (sorted by ULID for deterministic order)
+            let mut cells = self.execution_queue.clone();
+            cells.sort();
+            self.execution_queue.clear();
+            self.executed_this_step.clear();
+            cells
+        } else {
+            std::mem::take(&mut self.pending_step_cells)
+        };
 
-        for cell_id in current_step_cells {
+        for (index, &cell_id) in current_step_cells.iter().enumerate() {
             let cell = canvas
                 .get_cell(cell_id)
                 .ok_or_else(|| anyhow!("Cell not found: {}", cell_id))?;
+            let iteration = self.cell_iterations.get(&cell_id).copied().unwrap_or(0);
+
+            // A cell upstream failed under the skip-downstream policy
+            if self.skipped_cells.contains(&cell_id) {
+                self.outcomes.insert(cell_id, CellOutcome::Skipped);
+                continue;
+            }
 
             // Gather inputs from upstream cells
-            let inputs = self.gather_inputs(canvas, cell_id);
+            let inputs = if matches!(self.mode, ExecutionMode::DryRun) {
+                self.validate_transforms(canvas, cell_id)
+            } else {
+                Ok(())
+            }
+            .and_then(|()| self.gather_inputs(canvas, cell_id));
+            let inputs = match inputs {
+                Ok(inputs) => inputs,
+                Err(e) => {
+                    if self.cell_failed(canvas, cell, iteration, CapturedOutput::default(), e)? {
+                        return Ok(());
+                    }
+                    continue;
+                }
+            };
+
+            // Pause before a cell with a breakpoint (once; continuing runs the cell)
+            if self.resume_past.take() != Some(cell_id) && self.hits_breakpoint(cell_id, &inputs)?
+            {
+                self.pending_step_cells = current_step_cells[index..].to_vec();
+                self.paused_at = Some(cell_id);
+                self.paused_inputs = inputs;
+                self.status = ExecutionStatus::Paused;
+                return Ok(());
+            }
 
             // Execute cell
-            let output = if matches!(self.mode, ExecutionMode::DryRun) {
-                // Dry-run: validate without executing
-                self.validate_cell(cell, &inputs)?
+            let mut captured = CapturedOutput::default();
+            let result = if matches!(self.mode, ExecutionMode::DryRun) {
+                // Dry-run: validate and infer the output type without executing
+                self.validate_cell(canvas, cell).map(|data_type| {
+                    self.inferred_types.insert(cell_id, data_type);
+                    CellData::Text(format!("(dry-run: {})", data_type))
+                })
             } else {
-                self.execute_cell(canvas, cell, &inputs)?
+                self.execute_cell(canvas, cell, &inputs, &mut captured)
+            };
+
+            // Decide which outgoing relationships to follow
+            let result = result.and_then(|output| {
+                let branches =
+                    self.evaluate_branches(canvas, cell, &output, &captured.output_ports)?;
+                Ok((output, branches))
+            });
+
+            let (output, branches) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    if self.cell_failed(canvas, cell, iteration, captured, e)? {
+                        return Ok(());
+                    }
+                    continue;
+                }
             };
 
             // Log execution
-            self.log.push(ExecutionLogEntry {
+            let entry = self.redact_entry(ExecutionLogEntry {
                 step: self.current_step,
+                iteration,
                 cell_id,
                 cell_name: cell.name.clone(),
                 output: output.clone(),
                 dry_run: matches!(self.mode, ExecutionMode::DryRun),
                 error: None,
+                stdout: captured.stdout,
+                stderr: captured.stderr,
+                traceback: captured.traceback,
+                cell_reads: captured.cell_reads,
+                output_ports: captured.output_ports.clone(),
+                branches_taken: branches.taken.clone(),
+                branches_skipped: branches.skipped.clone(),
             });
+            self.log.push(entry);
 
             // Store output
             self.cell_outputs.insert(cell_id, output.clone());
+            self.port_outputs.insert(cell_id, captured.output_ports);
+            self.outcomes.insert(cell_id, CellOutcome::Succeeded);
 
-            // Find downstream cells
-            let downstream = canvas.get_outgoing_relationships(cell_id);
+            if self.follow_branches(cell_id, iteration, branches)? {
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }
 
-            for rel in downstream {
-                let target_id = rel.to;
+    /// Record a failed cell and apply its error policy
+    ///
+    /// Fails the run under fail-fast; otherwise returns whether execution must stop because a
+    /// loop hit its bound.
+    fn cell_failed(
+        &mut self,
+        canvas: &Canvas,
+        cell: &Cell,
+        iteration: usize,
+        captured: CapturedOutput,
+        error: anyhow::Error,
+    ) -> Result<bool> {
+        // Keep the failing cell's console output and traceback in the log
+        let error = self.redact_error(error);
+        let entry = self.redact_entry(ExecutionLogEntry {
+            step: self.current_step,
+            iteration,
+            cell_id: cell.id,
+            cell_name: cell.name.clone(),
+            output: CellData::None,
+            dry_run: matches!(self.mode, ExecutionMode::DryRun),
+            error: Some(error.to_string()),
+            stdout: captured.stdout,
+            stderr: captured.stderr,
+            traceback: captured.traceback,
+            cell_reads: captured.cell_reads,
+            output_ports: captured.output_ports,
+            branches_taken: Vec::new(),
+            branches_skipped: Vec::new(),
+        });
+        self.log.push(entry);
+        self.cell_outputs.remove(&cell.id);
+        self.port_outputs.remove(&cell.id);
+        self.outcomes.insert(cell.id, CellOutcome::Failed(error.to_string()));
 
-                // Conflict detection: check if target already executed this step
-                if self.executed_this_step.contains(&target_id) {
-                    return Err(anyhow!(
-                        "Conflict: Cell {} written twice in step {}",
-                        target_id,
-                        self.current_step
-                    ));
+        match cell.error_policy.unwrap_or(self.error_policy) {
+            ErrorPolicy::FailFast => Err(error),
+            ErrorPolicy::SkipDownstream => {
+                for id in canvas.downstream_cells(cell.id) {
+                    if id != cell.id && self.skipped_cells.insert(id) {
+                        self.outcomes.entry(id).or_insert(CellOutcome::Skipped);
+                    }
                 }
+                Ok(false)
+            }
+            ErrorPolicy::Continue => {
+                // Without an output, guards can't hold and loops don't start another pass
+                let mut branches = Branches::default();
+                for rel in canvas.get_outgoing_relationships(cell.id) {
+                    if rel.guard.is_some() || rel.loop_config.is_some() {
+                        branches.skipped.push(rel.to);
+                    } else {
+                        branches.taken.push(rel.to);
+                    }
+                }
+                self.follow_branches(cell.id, iteration, branches)
+            }
+        }
+    }
+
+    /// Mark a cell's skipped relationships and queue the downstream cells of the branches
+    /// taken; returns whether execution must stop because a loop hit its bound
+    fn follow_branches(
+        &mut self,
+        cell_id: Ulid,
+        iteration: usize,
+        branches: Branches,
+    ) -> Result<bool> {
+        for &target_id in branches.skipped.iter().chain(&branches.unreachable) {
+            self.skipped_edges.insert((cell_id, target_id));
+        }
+        for &target_id in &branches.unreachable {
+            self.unreachable_branches.push((cell_id, target_id));
+        }
 
-                self.executed_this_step.insert(target_id);
+        for target_id in branches.taken {
+            self.skipped_edges.remove(&(cell_id, target_id));
+
+            // A partial run doesn't leave its subgraph
+            if self.scope.as_ref().is_some_and(|scope| !scope.contains(&target_id)) {
+                continue;
+            }
 
-                // Queue for next step
-                if !self.execution_queue.contains(&target_id) {
-                    self.execution_queue.push(target_id);
+            // Following a loop back edge starts the next pass, within the loop's bound
+            let mut target_iteration = iteration;
+            if let Some(&(_, max_iterations)) =
+                branches.loop_backs.iter().find(|(to, _)| *to == target_id)
+            {
+                let passes = self.loop_iterations.entry((cell_id, target_id)).or_insert(0);
+                *passes += 1;
+                if *passes >= max_iterations {
+                    self.status = ExecutionStatus::IterationLimitReached {
+                        from: cell_id,
+                        to: target_id,
+                        max_iterations,
+                    };
+                    self.execution_queue.clear();
+                    return Ok(true);
                 }
+                target_iteration += 1;
+            }
+            self.cell_iterations.insert(target_id, target_iteration);
+            // Conflict detection: check if target already executed this step
+            if self.executed_this_step.contains(&target_id) {
+                return Err(anyhow!(
+                    "Conflict: Cell {} written twice in step {}",
+                    target_id,
+                    self.current_step
+                ));
+            }
+
+            self.executed_this_step.insert(target_id);
+
+            // Queue for next step
+            if !self.execution_queue.contains(&target_id) {
+                self.execution_queue.push(target_id);
             }
         }
 
-        Ok(())
+        Ok(false)
     }
 
     /// Gather inputs from upstream cells
-    fn gather_inputs(&self, canvas: &Canvas, cell_id: Ulid) -> Vec<CellData> {
-        let incoming = canvas.get_incoming_relationships(cell_id);
-        incoming
-            .iter()
-            .filter_map(|rel| self.cell_outputs.get(&rel.from).cloned())
-            .collect()
-    }
-
-    /// Execute a single cell
-    fn execute_cell(&self, canvas: &Canvas, cell: &Cell, inputs: &[CellData]) -> Result<CellData> {
-        match cell.cell_type {
-            CellType::Text => execute_text_cell(cell, inputs),
-            CellType::Python => execute_python_cell(canvas, cell, inputs),
-            CellType::Math => execute_math_cell(canvas, cell),
-            CellType::NumberInt | CellType::NumberFloat | CellType::NumberCurrency => {
-                execute_number_cell(cell)
+    fn gather_inputs(&self, canvas: &Canvas, cell_id: Ulid) -> Result<Vec<CellData>> {
+        let mut inputs = Vec::new();
+        for rel in canvas.get_incoming_relationships(cell_id) {
+            if let Some(value) = self.relationship_value(canvas, rel)? {
+                inputs.push(value);
             }
         }
+        Ok(inputs)
     }
 
-    /// Validate a cell (dry-run mode)
-    fn validate_cell(&self, cell: &Cell, inputs: &[CellData]) -> Result<CellData> {
-        match cell.cell_type {
-            CellType::Text => Ok(CellData::Text("(dry-run)".to_string())),
-            CellType::Python => validate_python_cell(cell, inputs),
-            CellType::Math => Ok(CellData::Text("(dry-run-math)".to_string())),
-            CellType::NumberInt | CellType::NumberFloat | CellType::NumberCurrency => {
-                Ok(CellData::Text("(dry-run-number)".to_string()))
+    /// Gather inputs from relationships with a target input name
+    fn gather_named_inputs(
+        &self,
+        canvas: &Canvas,
+        cell_id: Ulid,
+    ) -> Result<Vec<(String, CellData)>> {
+        let mut inputs = Vec::new();
+        for rel in canvas.get_incoming_relationships(cell_id) {
+            if let Some(name) = &rel.target_input {
+                if let Some(value) = self.relationship_value(canvas, rel)? {
+                    inputs.push((name.clone(), value));
+                }
             }
         }
+        Ok(inputs)
     }
 
-    /// Create execution report
-    fn create_report(&self) -> ExecutionReport {
-        ExecutionReport {
-            status: self.status.clone(),
-            step: self.current_step,
-            log: self.log.clone(),
-            total_cells_executed: self.log.len(),
+    /// Value carried by a relationship: the source's named output port, or its main output,
+    /// passed through the relationship's transform (not applied in dry run)
+    fn relationship_value(&self, canvas: &Canvas, rel: &Relationship) -> Result<Option<CellData>> {
+        // A relationship whose guard didn't hold carries no data
+        if self.skipped_edges.contains(&(rel.from, rel.to)) {
+            return Ok(None);
+        }
+        // A source that failed under the continue policy still takes its input slot
+        if matches!(self.outcomes.get(&rel.from), Some(CellOutcome::Failed(_))) {
+            return Ok(Some(CellData::None));
         }
-    }
 
-    /// Get current status
-    pub fn status(&self) -> &ExecutionStatus {
-        &self.status
-    }
+        let value = match (&rel.source_key, self.port_outputs.get(&rel.from)) {
+            // A source that ran without setting the port is an error; dry run sets no ports
+            (Some(key), Some(ports)) if !matches!(self.mode, ExecutionMode::DryRun) => {
+                Some(ports.get(key).ok_or_else(|| {
+                    anyhow!(
+                        "{} set no output port '{}' (used by the relationship to {})",
+                        relationship_label(canvas, rel.from),
+                        key,
+                        relationship_label(canvas, rel.to)
+                    )
+                })?)
+            }
+            (Some(key), ports) => ports.and_then(|ports| ports.get(key)),
+            (None, _) => self.cell_outputs.get(&rel.from),
+        };
+        let (Some(value), Some(transform)) = (value, &rel.transform) else {
+            return Ok(value.cloned());
+        };
+        if matches!(self.mode, ExecutionMode::DryRun) {
+            return Ok(Some(value.clone()));
+        }
 
-    /// Get execution log
-    pub fn log(&self) -> &[ExecutionLogEntry] {
-        &self.log
+        transform.apply(value).map(Some).map_err(|e| {
+            anyhow!(
+                "Transform on relationship {} → {} failed: {}",
+                relationship_label(canvas, rel.from),
+                relationship_label(canvas, rel.to),
+                e
+            )
+        })
     }
 
-    /// Continue execution (for step mode)
-    pub fn continue_execution(&mut self, canvas: &Canvas) -> Result<ExecutionReport> {
-        if !matches!(self.status, ExecutionStatus::Paused) {
-            return Err(anyhow!("Execution is not paused"));
-        }
+    /// Evaluate the guards on a cell's outgoing relationships
+    ///
+    /// Guards see the value the relationship would carry before its transform. In dry run,
+    /// guards are only evaluated when the source's output is known statically (Number
+    /// cells); a guard that fails there marks the branch unreachable.
+    fn evaluate_branches(
+        &self,
+        canvas: &Canvas,
+        cell: &Cell,
+        output: &CellData,
+        output_ports: &BTreeMap<String, CellData>,
+    ) -> Result<Branches> {
+        let dry_run = matches!(self.mode, ExecutionMode::DryRun);
+        let static_output = match cell.cell_type {
+            CellType::NumberInt | CellType::NumberFloat | CellType::NumberCurrency if dry_run => {
+                execute_number_cell(cell).ok()
+            }
+            _ => None,
+        };
 
-        self.status = ExecutionStatus::Running;
+        let mut branches = Branches::default();
+        let mut outgoing = canvas.get_outgoing_relationships(cell.id);
+        outgoing.sort_by_key(|rel| rel.to);
 
-        // Execute until queue is empty or step mode pauses again
-        while !self.execution_queue.is_empty() {
-            self.current_step += 1;
+        for rel in outgoing {
+            let edge_error = |what: &str, e: anyhow::Error| {
+                anyhow!(
+                    "{} on relationship {} → {}: {}",
+                    what,
+                    cell.label(),
+                    relationship_label(canvas, rel.to),
+                    e
+                )
+            };
 
-            if let Err(e) = self.execute_step(canvas) {
-                self.status = ExecutionStatus::Error(e.to_string());
-                return Err(e);
+            if dry_run {
+                if let Some(guard) = &rel.guard {
+                    guard.validate().map_err(|e| edge_error("Guard", e))?;
+                    if let Some(value) = &static_output {
+                        if !guard.evaluate(value).map_err(|e| edge_error("Guard", e))? {
+                            branches.unreachable.push(rel.to);
+                            continue;
+                        }
+                    }
+                }
+                if let Some(config) = &rel.loop_config {
+                    if let Some(exit) = &config.exit_when {
+                        exit.validate().map_err(|e| edge_error("Loop exit", e))?;
+                    }
+                    // One pass checks the loop body; back edges aren't followed
+                    continue;
+                }
+                branches.taken.push(rel.to);
+                continue;
             }
 
-            // In step mode, pause after each step
-            if self.mode == ExecutionMode::Step {
-                self.status = ExecutionStatus::Paused;
-                return Ok(self.create_report());
+            let value = match &rel.source_key {
+                Some(key) => output_ports.get(key).unwrap_or(&CellData::None),
+                None => output,
+            };
+            if let Some(guard) = &rel.guard {
+                if !guard.evaluate(value).map_err(|e| edge_error("Guard", e))? {
+                    branches.skipped.push(rel.to);
+                    continue;
+                }
+            }
+            if let Some(config) = &rel.loop_config {
+                if let Some(exit) = &config.exit_when {
+                    if exit.evaluate(value).map_err(|e| edge_error("Loop exit", e))? {
+                        branches.skipped.push(rel.to);
+                        continue;
+                    }
+                }
+                branches.loop_backs.push((rel.to, config.max_iterations));
             }
+            branches.taken.push(rel.to);
         }
 
-        // Execution complete
-        self.status = if matches!(self.mode, ExecutionMode::DryRun) {
-            ExecutionStatus::DryRunComplete
-        } else {
-            ExecutionStatus::Complete
-        };
+        Ok(branches)
+    }
 
-        Ok(self.create_report())
+    /// Check the transforms on a cell's incoming relationships (dry run)
+    fn validate_transforms(&self, canvas: &Canvas, cell_id: Ulid) -> Result<()> {
+        for rel in canvas.get_incoming_relationships(cell_id) {
+            if let Some(transform) = &rel.transform {
+                transform.validate().map_err(|e| {
+                    anyhow!(
+                        "Transform on relationship {} → {}: {}",
+                        relationship_label(canvas, rel.from),
+                        relationship_label(canvas, rel.to),
+                        e
+                    )
+                })?;
+            }
+        }
+        Ok(())
     }
 
-    /// Recalculate Math cells that depend on a changed cell
-    /// Returns the list of recalculated cells and any errors
-    pub fn recalculate_dependents(
-        &mut self,
-        changed_cell_id: Ulid,
+    /// Check if a relationship was not followed because its guard didn't hold
+    pub fn was_skipped(&self, from: Ulid, to: Ulid) -> bool {
+        self.skipped_edges.contains(&(from, to))
+    }
+
+    /// Get a named output port of an executed cell
+    pub fn port_output(&self, cell_id: Ulid, key: &str) -> Option<&CellData> {
+        self.port_outputs.get(&cell_id)?.get(key)
+    }
+
+    /// Execute a single cell
+    fn execute_cell(
+        &self,
+        canvas: &Canvas,
+        cell: &Cell,
+        inputs: &[CellData],
+        captured: &mut CapturedOutput,
+    ) -> Result<CellData> {
+        match cell.cell_type {
+            CellType::Text => execute_text_cell(cell, inputs),
+            CellType::Python => {
+                let named_inputs = self.gather_named_inputs(canvas, cell.id)?;
+                if let Some(interpreter) = &self.python_interpreter {
+                    let request = PythonRequest {
+                        outputs: &self.cell_outputs,
+                        inputs,
+                        named_inputs: &named_inputs,
+                        params: &self.params,
+                    };
+                    return execute_python_subprocess(interpreter, canvas, cell, request, captured);
+                }
+                execute_python_cell(
+                    canvas,
+                    &self.cell_outputs,
+                    cell,
+                    inputs,
+                    &named_inputs,
+                    &self.params,
+                    captured,
+                )
+            }
+            CellType::Math => execute_math_cell(canvas, cell, &self.params, &self.cell_outputs),
+            CellType::NumberInt | CellType::NumberFloat | CellType::NumberCurrency => {
+                execute_number_cell(cell)
+            }
+            CellType::Shell => self.execute_shell_cell(canvas, cell, inputs, captured),
+            CellType::Sql => self.execute_sql_cell(canvas, cell),
+            CellType::Script => {
+                let script = cell
+                    .content
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Script cell has no inline content"))?;
+                let named_inputs = self.gather_named_inputs(canvas, cell.id)?;
+                run_script(
+                    script,
+                    inputs,
+                    &named_inputs,
+                    &self.params,
+                    &self.script_limits,
+                    captured,
+                )
+                .map_err(|e| anyhow!("Script error in cell {}: {}", cell.short_id, e))
+            }
+            CellType::Wasm => self.execute_wasm_cell(canvas, cell, inputs, captured),
+            CellType::Json => self.execute_json_cell(cell, inputs),
+            CellType::Csv => self.csv_table(cell).map(CellData::Table),
+            CellType::Image => self.execute_image_cell(cell, inputs),
+            CellType::Chart => self.execute_chart_cell(canvas, cell, inputs),
+            CellType::Date => execute_date_cell(cell, inputs),
+        }
+    }
+
+    /// Execute a Shell cell's command line
+    ///
+    /// The first input arrives on stdin. Inputs are also passed as environment variables:
+    /// `INPUT_0`, `INPUT_1`, ... by position, `INPUT_<NAME>` for named inputs and
+    /// `PARAM_<NAME>` for run parameters. Stdout that parses as a JSON object or array
+    /// becomes `Json`, anything else `Text`; a non-zero exit status fails the cell.
+    fn execute_shell_cell(
+        &self,
+        canvas: &Canvas,
+        cell: &Cell,
+        inputs: &[CellData],
+        captured: &mut CapturedOutput,
+    ) -> Result<CellData> {
+        let command = cell
+            .content
+            .as_str()
+            .ok_or_else(|| anyhow!("Shell cell has no inline content"))?;
+
+        let stdin = match inputs.first() {
+            Some(CellData::Binary(bytes)) => bytes.clone(),
+            Some(input) => input.coerce_to_string().into_bytes(),
+            None => Vec::new(),
+        };
+        let mut env = vec![("INPUT_COUNT".to_string(), inputs.len().to_string())];
+        for (i, input) in inputs.iter().enumerate() {
+            env.push((format!("INPUT_{}", i), input.coerce_to_string()));
+        }
+        for (name, input) in self.gather_named_inputs(canvas, cell.id)? {
+            env.push((format!("INPUT_{}", name.to_uppercase()), input.coerce_to_string()));
+        }
+        for (name, value) in &self.params {
+            if self.secret_names.contains(name) && !self.shell_secrets {
+                continue;
+            }
+            env.push((format!("PARAM_{}", name.to_uppercase()), value.coerce_to_string()));
+        }
+
+        let timeout =
+            Duration::from_secs(cell.shell_timeout_secs.unwrap_or(DEFAULT_SHELL_TIMEOUT_SECS));
+        let output = run_shell_command(command, &self.shell_working_dir(cell), stdin, &env, timeout)
+            .map_err(|e| anyhow!("Shell command in cell {} failed: {:#}", cell.short_id, e))?;
+        captured.stderr = output.stderr;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Shell command in cell {} failed with {}",
+                cell.short_id,
+                output.status
+            ));
+        }
+
+        let stdout = output.stdout.trim_end_matches(['\n', '\r']);
+        Ok(match serde_json::from_str(stdout) {
+            Ok(value @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) => {
+                CellData::Json(value)
+            }
+            _ => CellData::Text(stdout.to_string()),
+        })
+    }
+
+    /// Execute a SQL cell's query against the project database
+    ///
+    /// Inputs that are tables or JSON arrays of objects are loaded as TEMP tables named by
+    /// the relationship's target input, else by the source cell's short ID. Other inputs
+    /// and run parameters are bound to named SQL parameters (`:name`).
+    fn execute_sql_cell(&self, canvas: &Canvas, cell: &Cell) -> Result<CellData> {
+        let sql = cell
+            .content
+            .as_str()
+            .ok_or_else(|| anyhow!("SQL cell has no inline content"))?;
+        let connection = open_database(self.sql_database_path().as_deref(), false)?;
+
+        let mut values = self.params.clone();
+        for rel in canvas.get_incoming_relationships(cell.id) {
+            let Some(value) = self.relationship_value(canvas, rel)? else {
+                continue;
+            };
+            let name = match (&rel.target_input, canvas.get_cell(rel.from)) {
+                (Some(name), _) => name.clone(),
+                (None, Some(source)) => source.short_id.clone(),
+                (None, None) => rel.from.to_string(),
+            };
+            let table = match &value {
+                CellData::Table(table) => Some(table.clone()),
+                CellData::Json(rows @ serde_json::Value::Array(_)) => {
+                    Table::from_json_rows(rows).ok()
+                }
+                _ => None,
+            };
+            match table {
+                Some(table) => load_table(&connection, &name, &table)?,
+                None => {
+                    values.insert(name, value);
+                }
+            }
+        }
+
+        run_query(&connection, sql, &values)
+            .map_err(|e| anyhow!("SQL query in cell {} failed: {:#}", cell.short_id, e))
+    }
+
+    /// Execute a WASM cell's module in the sandbox
+    ///
+    /// The module receives `{"inputs": [...], "named_inputs": {...}, "params": {...}}`
+    /// as JSON; see `wasm::run_module` for the interface it implements.
+    fn execute_wasm_cell(
+        &self,
+        canvas: &Canvas,
+        cell: &Cell,
+        inputs: &[CellData],
+        captured: &mut CapturedOutput,
+    ) -> Result<CellData> {
+        let to_json = |data: &CellData| as_json(data);
+        let named_inputs = self
+            .gather_named_inputs(canvas, cell.id)?
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), to_json(value)?)))
+            .collect::<Result<serde_json::Map<_, _>>>()?;
+        let params = self
+            .params
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), to_json(value)?)))
+            .collect::<Result<serde_json::Map<_, _>>>()?;
+        let input = serde_json::json!({
+            "inputs": inputs.iter().map(to_json).collect::<Result<Vec<_>>>()?,
+            "named_inputs": named_inputs,
+            "params": params,
+        });
+
+        let source = self.content_bytes(cell)?;
+        let project_dir = self.project_dir.as_deref();
+        run_module(&source, &input, &self.wasm_sandbox, project_dir, captured)
+            .map_err(|e| anyhow!("WASM cell {} failed: {:#}", cell.short_id, e))
+    }
+
+    /// Execute a JSON cell: its parsed content, once the content and every input match
+    /// the cell's schema
+    fn execute_json_cell(&self, cell: &Cell, inputs: &[CellData]) -> Result<CellData> {
+        let value = self.json_content(cell)?;
+        if let Some(schema) = &cell.json_schema {
+            ensure_matches(schema, &value, &format!("Content of cell {}", cell.short_id))?;
+            for (i, input) in inputs.iter().enumerate() {
+                let what = format!("Input {} of cell {}", i, cell.short_id);
+                ensure_matches(schema, &as_json(input)?, &what)?;
+            }
+        }
+        Ok(CellData::Json(value))
+    }
+
+    /// A JSON cell's content, parsed
+    fn json_content(&self, cell: &Cell) -> Result<serde_json::Value> {
+        let content = String::from_utf8(self.content_bytes(cell)?)
+            .map_err(|_| anyhow!("JSON cell {} is not valid UTF-8", cell.short_id))?;
+        parse_json_content(&content).map_err(|e| anyhow!("JSON cell {}: {}", cell.short_id, e))
+    }
+
+    /// Execute an Image cell: an upstream image (bytes, or base64 or SVG text) passes
+    /// through; without one the cell outputs its own
+    fn execute_image_cell(&self, cell: &Cell, inputs: &[CellData]) -> Result<CellData> {
+        let bytes = match inputs.first() {
+            Some(CellData::Binary(bytes)) => Ok(bytes.clone()),
+            Some(CellData::Text(text)) => decode_inline(text),
+            Some(other) => Err(anyhow!("Can't show {:?} input", other.data_type())),
+            None => read_image_content(&cell.content, self.project_dir.as_deref()),
+        };
+        bytes
+            .and_then(|bytes| ensure_image(&bytes).map(|_| CellData::Binary(bytes)))
+            .map_err(|e| anyhow!("Image cell {}: {}", cell.short_id, e))
+    }
+
+    /// Execute a Chart cell: its spec applied to the inputs and the listed cells' values
+    fn execute_chart_cell(
+        &self,
+        canvas: &Canvas,
+        cell: &Cell,
+        inputs: &[CellData],
+    ) -> Result<CellData> {
+        let chart = chart_spec(cell).and_then(|spec| {
+            let values = spec
+                .cells
+                .iter()
+                .map(|reference| {
+                    let short_id = reference.trim_start_matches("[[").trim_end_matches("]]");
+                    let referenced = canvas
+                        .get_cell_by_short_id(short_id)
+                        .ok_or_else(|| anyhow!("Cell {} not found", short_id))?;
+                    let value = match self.cell_outputs.get(&referenced.id) {
+                        Some(CellData::Number(value)) => *value,
+                        _ => crate::math_eval::resolve_cell_value(referenced, canvas)
+                            .map_err(|e| anyhow!(e))?,
+                    };
+                    Ok((referenced.label(), value))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Chart::build(&spec, inputs, &values)
+        });
+        let chart = chart.map_err(|e| anyhow!("Chart cell {}: {}", cell.short_id, e))?;
+        Ok(CellData::Json(serde_json::to_value(chart)?))
+    }
+
+    /// A CSV cell's table
+    fn csv_table(&self, cell: &Cell) -> Result<Table> {
+        read_csv_content(&cell.content, self.project_dir.as_deref())
+            .map_err(|e| anyhow!("CSV cell {}: {}", cell.short_id, e))
+    }
+
+    /// A cell's content: inline text, or an external file relative to the project
+    /// directory (WASM modules, JSON documents)
+    fn content_bytes(&self, cell: &Cell) -> Result<Vec<u8>> {
+        match &cell.content {
+            CellContent::Inline(text) => Ok(text.clone().into_bytes()),
+            CellContent::External { path, .. } => {
+                let path = match &self.project_dir {
+                    Some(project_dir) => project_dir.join(path),
+                    None => path.clone(),
+                };
+                std::fs::read(&path)
+                    .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))
+            }
+        }
+    }
+
+    /// Database file SQL cells query, resolved against the project directory
+    fn sql_database_path(&self) -> Option<PathBuf> {
+        let database = self.sql_database.as_ref()?;
+        Some(match &self.project_dir {
+            Some(project_dir) => project_dir.join(database),
+            None => database.clone(),
+        })
+    }
+
+    /// Directory a Shell cell runs in: its working directory under the project directory
+    fn shell_working_dir(&self, cell: &Cell) -> PathBuf {
+        let project_dir = self.project_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+        match &cell.working_dir {
+            Some(dir) => project_dir.join(dir),
+            None => project_dir,
+        }
+    }
+
+    /// Validate a cell and infer its output type (dry-run mode)
+    ///
+    /// Python cells declare types with module-level annotations: `input_0: float` or
+    /// `data: list` for inputs, `result: str` for the output.
+    fn validate_cell(&mut self, canvas: &Canvas, cell: &Cell) -> Result<DataType> {
+        let input_types = self.input_types(canvas, cell.id);
+        match cell.cell_type {
+            // Text cells pass their first input through
+            CellType::Text => Ok(input_types.first().map_or(DataType::None, |(_, t)| *t)),
+            CellType::Python => {
+                validate_python_cell(cell)?;
+                let annotations = python_type_annotations(cell.content.as_str().unwrap_or(""));
+                let declared = |name: &str| {
+                    annotations
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map(|(_, data_type)| *data_type)
+                };
+
+                for (i, (rel, actual)) in input_types.iter().enumerate() {
+                    let names = [Some(format!("input_{}", i)), rel.target_input.clone()];
+                    for name in names.into_iter().flatten() {
+                        match declared(&name) {
+                            Some(expected) if !expected.accepts(*actual) => {
+                                let message = format!(
+                                    "Cell {} expects {}: {} but {} provides {}",
+                                    cell.label(),
+                                    name,
+                                    expected,
+                                    relationship_label(canvas, rel.from),
+                                    actual
+                                );
+                                self.type_mismatch(rel, message);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                Ok(declared("result").unwrap_or(DataType::Any))
+            }
+            CellType::Math
+            | CellType::NumberInt
+            | CellType::NumberFloat
+            | CellType::NumberCurrency => Ok(DataType::Number),
+            // Stdout may or may not parse as JSON
+            CellType::Shell => {
+                let command = cell.content.as_str().unwrap_or("");
+                let missing = missing_commands(command, &self.shell_working_dir(cell));
+                if !missing.is_empty() {
+                    return Err(anyhow!("Command not found on PATH: {}", missing.join(", ")));
+                }
+                Ok(DataType::Any)
+            }
+            CellType::Sql => {
+                let connection = open_database(self.sql_database_path().as_deref(), true)?;
+                check_query(&connection, cell.content.as_str().unwrap_or(""))?;
+                Ok(DataType::Table)
+            }
+            CellType::Script => {
+                check_script(cell.content.as_str().unwrap_or(""))?;
+                Ok(DataType::Any)
+            }
+            CellType::Wasm => {
+                check_module(&self.content_bytes(cell)?)
+                    .map_err(|e| anyhow!("WASM cell {}: {}", cell.short_id, e))?;
+                Ok(DataType::Any)
+            }
+            // Schemas are checked statically by `Validator::check_json_cells`
+            CellType::Json => {
+                self.json_content(cell)?;
+                Ok(DataType::Json)
+            }
+            CellType::Csv => {
+                self.csv_table(cell)?;
+                Ok(DataType::Table)
+            }
+            // An empty cell with incoming relationships shows the image it receives
+            CellType::Image => {
+                let fed = !canvas.get_incoming_relationships(cell.id).is_empty();
+                if !(fed && cell.content.is_empty()) {
+                    read_image_content(&cell.content, self.project_dir.as_deref())
+                        .and_then(|bytes| ensure_image(&bytes))
+                        .map_err(|e| anyhow!("Image cell {}: {}", cell.short_id, e))?;
+                }
+                Ok(DataType::Binary)
+            }
+            CellType::Chart => {
+                chart_spec(cell).map_err(|e| anyhow!("Chart cell {}: {}", cell.short_id, e))?;
+                Ok(DataType::Json)
+            }
+            // An empty cell with incoming relationships shows the date it receives
+            CellType::Date => {
+                let fed = !canvas.get_incoming_relationships(cell.id).is_empty();
+                let checked = if fed && cell.content.is_empty() {
+                    parse_time_zone(&cell.time_zone)
+                        .and_then(|_| crate::dates::check_format(&cell.date_format))
+                } else {
+                    cell_datetime(cell).map(|_| ())
+                };
+                checked.map_err(|e| anyhow!("Date cell {}: {}", cell.short_id, e))?;
+                Ok(DataType::DateTime)
+            }
+        }
+    }
+
+    /// Types arriving on a cell's incoming relationships in input order (dry run), after
+    /// transforms; a transform that can't take its input's type is a mismatch
+    fn input_types<'c>(
+        &mut self,
+        canvas: &'c Canvas,
+        cell_id: Ulid,
+    ) -> Vec<(&'c Relationship, DataType)> {
+        let mut types = Vec::new();
+        for rel in canvas.get_incoming_relationships(cell_id) {
+            if self.skipped_edges.contains(&(rel.from, rel.to)) {
+                continue;
+            }
+            let Some(&source_type) = self.inferred_types.get(&rel.from) else {
+                continue;
+            };
+
+            // Named output ports aren't typed
+            let mut data_type = match rel.source_key {
+                Some(_) => DataType::Any,
+                None => source_type,
+            };
+            if let Some(transform) = &rel.transform {
+                if !transform.accepts(data_type) {
+                    let message = format!(
+                        "Transform [{}] on relationship {} → {} can't take {}",
+                        transform,
+                        relationship_label(canvas, rel.from),
+                        relationship_label(canvas, rel.to),
+                        data_type
+                    );
+                    self.type_mismatch(rel, message);
+                }
+                data_type = transform.output_type();
+            }
+            types.push((rel, data_type));
+        }
+        types
+    }
+
+    /// Record a type mismatch on a relationship (dry run)
+    fn type_mismatch(&mut self, rel: &Relationship, message: String) {
+        self.type_mismatches.push(ValidationIssue {
+            severity: ValidationSeverity::Error,
+            message,
+            affected_cells: vec![rel.to, rel.from],
+            issue_type: ValidationIssueType::TypeMismatch,
+        });
+    }
+
+    /// Type mismatches found by the last dry run
+    pub fn type_mismatches(&self) -> &[ValidationIssue] {
+        &self.type_mismatches
+    }
+
+    /// Create execution report
+    fn create_report(&self) -> ExecutionReport {
+        ExecutionReport {
+            status: self.status.clone(),
+            step: self.current_step,
+            log: self.log.clone(),
+            total_cells_executed: self.log.len(),
+            unreachable_branches: self.unreachable_branches.clone(),
+            paused_at: self.paused_at,
+            outcomes: self.outcomes.clone(),
+            type_mismatches: self.type_mismatches.clone(),
+        }
+    }
+
+    /// Set the error policy for cells that don't set their own
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.error_policy = policy;
+    }
+
+    /// Set the parameters cells see, e.g. from `Parameters::resolve`
+    pub fn set_params(&mut self, params: BTreeMap<String, CellData>) {
+        self.params = params;
+    }
+
+    /// Mark which parameters are secrets: their values are masked in the log and in
+    /// errors, and Shell cells only see them when `to_shell` is set
+    pub fn set_secrets(&mut self, names: impl IntoIterator<Item = String>, to_shell: bool) {
+        self.secret_names = names.into_iter().collect();
+        self.shell_secrets = to_shell;
+    }
+
+    /// Values of the secret parameters
+    fn secret_values(&self) -> Vec<String> {
+        self.secret_names
+            .iter()
+            .filter_map(|name| self.params.get(name))
+            .map(CellData::coerce_to_string)
+            .collect()
+    }
+
+    /// A log entry with secret values masked
+    fn redact_entry(&self, mut entry: ExecutionLogEntry) -> ExecutionLogEntry {
+        let secrets = self.secret_values();
+        if secrets.is_empty() {
+            return entry;
+        }
+        entry.output = redact_data(&entry.output, &secrets);
+        entry.error = entry.error.map(|error| redact(&error, &secrets));
+        entry.stdout = redact(&entry.stdout, &secrets);
+        entry.stderr = redact(&entry.stderr, &secrets);
+        for value in entry.output_ports.values_mut() {
+            *value = redact_data(value, &secrets);
+        }
+        if let Some(traceback) = &mut entry.traceback {
+            traceback.message = redact(&traceback.message, &secrets);
+            for frame in &mut traceback.frames {
+                frame.source = frame.source.as_deref().map(|source| redact(source, &secrets));
+            }
+        }
+        entry
+    }
+
+    /// An error with secret values masked
+    fn redact_error(&self, error: anyhow::Error) -> anyhow::Error {
+        let message = format!("{:#}", error);
+        let redacted = redact(&message, &self.secret_values());
+        if redacted == message {
+            error
+        } else {
+            anyhow!(redacted)
+        }
+    }
+
+    /// Run Python cells in a subprocess of this interpreter instead of the linked one
+    ///
+    /// See `PythonEnvironment::executable` for resolving a project's interpreter.
+    pub fn set_python_interpreter(&mut self, interpreter: Option<PathBuf>) {
+        self.python_interpreter = interpreter;
+    }
+
+    /// Set the project directory Shell cells run in
+    pub fn set_project_dir(&mut self, project_dir: Option<PathBuf>) {
+        self.project_dir = project_dir;
+    }
+
+    /// Set the resource limits Script cells run under
+    pub fn set_script_limits(&mut self, limits: ScriptLimits) {
+        self.script_limits = limits;
+    }
+
+    /// Set the limits and capabilities WASM cells run with
+    pub fn set_wasm_sandbox(&mut self, sandbox: WasmSandbox) {
+        self.wasm_sandbox = sandbox;
+    }
+
+    /// Set the SQLite database file SQL cells query (None = in-memory)
+    ///
+    /// Relative paths are resolved against the project directory.
+    pub fn set_sql_database(&mut self, database: Option<PathBuf>) {
+        self.sql_database = database;
+    }
+
+    /// Change the execution mode used by the next run
+    pub fn set_mode(&mut self, mode: ExecutionMode) {
+        self.mode = mode;
+    }
+
+    /// Get current status
+    pub fn status(&self) -> &ExecutionStatus {
+        &self.status
+    }
+
+    /// Get execution log
+    pub fn log(&self) -> &[ExecutionLogEntry] {
+        &self.log
+    }
+
+    /// Implicit dependencies recorded during execution as (reader, read cell) pairs
+    pub fn implicit_dependencies(&self) -> Vec<(Ulid, Ulid)> {
+        let mut deps = Vec::new();
+        for entry in &self.log {
+            for &source in &entry.cell_reads {
+                if !deps.contains(&(entry.cell_id, source)) {
+                    deps.push((entry.cell_id, source));
+                }
+            }
+        }
+        deps
+    }
+
+    /// Continue execution (for step mode)
+    pub fn continue_execution(&mut self, canvas: &Canvas) -> Result<ExecutionReport> {
+        if !matches!(self.status, ExecutionStatus::Paused) {
+            return Err(anyhow!("Execution is not paused"));
+        }
+
+        self.status = ExecutionStatus::Running;
+        self.resume_past = self.paused_at.take();
+        self.paused_inputs.clear();
+
+        // Execute until queue is empty or execution pauses again
+        self.run_until_pause(canvas)
+    }
+
+    /// Set a breakpoint on a cell (replacing any existing one)
+    pub fn set_breakpoint(&mut self, cell_id: Ulid, breakpoint: Breakpoint) {
+        self.breakpoints.insert(cell_id, breakpoint);
+    }
+
+    /// Remove a cell's breakpoint
+    pub fn remove_breakpoint(&mut self, cell_id: Ulid) {
+        self.breakpoints.remove(&cell_id);
+    }
+
+    /// Get all breakpoints
+    pub fn breakpoints(&self) -> &HashMap<Ulid, Breakpoint> {
+        &self.breakpoints
+    }
+
+    /// Cell where execution is paused by a breakpoint, with the inputs it will receive
+    pub fn paused_cell(&self) -> Option<(Ulid, &[CellData])> {
+        self.paused_at.map(|id| (id, self.paused_inputs.as_slice()))
+    }
+
+    /// Check if a cell's breakpoint should pause execution (never in dry run)
+    fn hits_breakpoint(&self, cell_id: Ulid, inputs: &[CellData]) -> Result<bool> {
+        match self.breakpoints.get(&cell_id) {
+            Some(breakpoint) if !matches!(self.mode, ExecutionMode::DryRun) => {
+                breakpoint.should_pause(inputs)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Recalculate Math cells that depend on a changed cell
+    /// Returns the list of recalculated cells and any errors
+    pub fn recalculate_dependents(
+        &mut self,
+        changed_cell_id: Ulid,
         canvas: &mut Canvas,
     ) -> Result<Vec<(Ulid, Result<f64, String>)>> {
         let mut results = Vec::new();
@@ -328,7 +1790,7 @@ impl ExecutionEngine {
             };
 
             // Execute the math cell
-            let result = match execute_math_cell(canvas, cell) {
+            let result = match execute_math_cell(canvas, cell, &self.params, &self.cell_outputs) {
                 Ok(CellData::Number(value)) => {
                     // Get target cell ID before mutable borrow
                     let target_cell_id = canvas.get_cell(cell_id)
@@ -354,6 +1816,7 @@ impl ExecutionEngine {
                                 };
                                 target_cell.content = CellContent::inline(formatted);
                             }
+                            crate::dates::store_day_number(target_cell, value);
                         }
                     }
 
@@ -379,12 +1842,199 @@ fn execute_text_cell(_cell: &Cell, inputs: &[CellData]) -> Result<CellData> {
     Ok(inputs.first().cloned().unwrap_or(CellData::None))
 }
 
-/// Execute a Python cell
-fn execute_python_cell(_canvas: &Canvas, cell: &Cell, inputs: &[CellData]) -> Result<CellData> {
-    let code = cell
+/// Python helper cells call to set their output (or a named output port)
+const SET_OUTPUT_CODE: &str = r#"
+def set_output(value, key='result'):
+    __output__[key] = value
+"#;
+
+/// Python helpers backing the read-only `cells` API
+///
+/// `__cell_registry__` maps cell names and short IDs to plain dicts built from Rust;
+/// every lookup appends the cell's ULID to `__cell_reads__`.
+const CELLS_API_CODE: &str = r#"
+import copy as _copy
+
+class CellView:
+    __slots__ = ('_data',)
+
+    def __init__(self, data):
+        object.__setattr__(self, '_data', data)
+
+    def __getattr__(self, attr):
+        try:
+            return _copy.deepcopy(self._data[attr])
+        except KeyError:
+            raise AttributeError(attr) from None
+
+    def __setattr__(self, attr, value):
+        raise AttributeError('cells are read-only')
+
+    def __repr__(self):
+        return '<cell {}>'.format(self._data['name'] or self._data['short_id'])
+
+class CellsApi:
+    __slots__ = ('_registry', '_reads')
+
+    def __init__(self, registry, reads):
+        object.__setattr__(self, '_registry', registry)
+        object.__setattr__(self, '_reads', reads)
+
+    def _lookup(self, key):
+        data = self._registry.get(key)
+        if data is None:
+            data = self._registry.get(str(key).upper())
+        if data is None:
+            raise KeyError('cell not found: {}'.format(key))
+        self._reads.append(data['id'])
+        return CellView(data)
+
+    def __getitem__(self, key):
+        return self._lookup(key)
+
+    def __getattr__(self, key):
+        try:
+            return self._lookup(key)
+        except KeyError as e:
+            raise AttributeError(str(e)) from None
+
+    def __contains__(self, key):
+        return key in self._registry or str(key).upper() in self._registry
+
+    def get(self, key, default=None):
+        try:
+            return self._lookup(key)
+        except KeyError:
+            return default
+
+    def import_from(self, key, *names):
+        output = self._lookup(key).output
+        values = []
+        for name in names:
+            if isinstance(output, dict) and name in output:
+                values.append(output[name])
+            else:
+                raise ImportError('cannot import {!r} from cell:{}'.format(name, key))
+        return values[0] if len(values) == 1 else tuple(values)
+
+    def __setattr__(self, attr, value):
+        raise AttributeError('cells are read-only')
+
+    def __setitem__(self, key, value):
+        raise TypeError('cells are read-only')
+
+cells = CellsApi(__cell_registry__, __cell_reads__)
+"#;
+
+/// Rewrite `cell:Name` syntax into calls on the `cells` API
+///
+/// Handles `import cell:Name [as alias]`, `from cell:Name import a, b as c` and bare
+/// `cell:Name` expressions. Rewrites stay on one line so traceback line numbers still
+/// match the cell content.
+fn rewrite_cell_imports(code: &str) -> String {
+    let import_re =
+        regex::Regex::new(r"^(\s*)import\s+cell:(\w+)(?:\s+as\s+(\w+))?\s*$").unwrap();
+    let from_re = regex::Regex::new(r"^(\s*)from\s+cell:(\w+)\s+import\s+(.+?)\s*$").unwrap();
+    let expr_re = regex::Regex::new(r#"(^|[^\w.'"])cell:(\w+)"#).unwrap();
+
+    let mut lines = Vec::new();
+    for line in code.lines() {
+        let rewritten = if let Some(caps) = import_re.captures(line) {
+            let alias = caps.get(3).unwrap_or_else(|| caps.get(2).unwrap()).as_str();
+            format!("{}{} = cells[{:?}]", &caps[1], alias, &caps[2])
+        } else if let Some(caps) = from_re.captures(line) {
+            let mut names = Vec::new();
+            let mut targets = Vec::new();
+            for item in caps[3].trim_matches(|c| c == '(' || c == ')').split(',') {
+                let mut parts = item.split_whitespace();
+                if let Some(name) = parts.next() {
+                    let target = match (parts.next(), parts.next()) {
+                        (Some("as"), Some(alias)) => alias,
+                        _ => name,
+                    };
+                    names.push(format!("{:?}", name));
+                    targets.push(target.to_string());
+                }
+            }
+            format!(
+                "{}{} = cells.import_from({:?}, {})",
+                &caps[1],
+                targets.join(", "),
+                &caps[2],
+                names.join(", ")
+            )
+        } else {
+            expr_re
+                .replace_all(line, "${1}cells[\"${2}\"].output")
+                .into_owned()
+        };
+        lines.push(rewritten);
+    }
+    lines.join("\n")
+}
+
+/// The cells Python code names, by name or by short ID: the only ones it can read
+fn cells_named_in<'a>(canvas: &'a Canvas, source: &str) -> impl Iterator<Item = &'a Cell> {
+    let words: HashSet<String> = source
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(str::to_uppercase)
+        .collect();
+    let source = source.to_string();
+    canvas.cells().values().filter(move |cell| {
+        cell.name.as_deref().is_some_and(|name| source.contains(name))
+            || words.contains(&cell.short_id.to_uppercase())
+    })
+}
+
+/// Build the `cells` registry: one entry per cell the code names, reachable by name and
+/// by short ID. Other cells' outputs are never converted to Python objects.
+fn build_cell_registry<'py>(
+    py: Python<'py>,
+    canvas: &Canvas,
+    outputs: &HashMap<Ulid, CellData>,
+    source: &str,
+) -> PyResult<Bound<'py, PyDict>> {
+    let registry = PyDict::new_bound(py);
+
+    for cell in cells_named_in(canvas, source) {
+        let data = PyDict::new_bound(py);
+        data.set_item("id", cell.id.to_string())?;
+        data.set_item("short_id", &cell.short_id)?;
+        data.set_item("name", cell.name.as_deref())?;
+        data.set_item("cell_type", format!("{:?}", cell.cell_type))?;
+        data.set_item("content", cell.content.as_str())?;
+        data.set_item("computed_result", cell.computed_result)?;
+        let output = match outputs.get(&cell.id) {
+            Some(output) => celldata_to_python(py, output)?,
+            None => py.None(),
+        };
+        data.set_item("output", output)?;
+
+        registry.set_item(cell.short_id.to_uppercase(), &data)?;
+        if let Some(name) = &cell.name {
+            registry.set_item(name, &data)?;
+        }
+    }
+
+    Ok(registry)
+}
+
+/// Execute a Python cell, capturing stdout/stderr and any traceback
+fn execute_python_cell(
+    canvas: &Canvas,
+    outputs: &HashMap<Ulid, CellData>,
+    cell: &Cell,
+    inputs: &[CellData],
+    named_inputs: &[(String, CellData)],
+    params: &BTreeMap<String, CellData>,
+    captured: &mut CapturedOutput,
+) -> Result<CellData> {
+    let source = cell
         .content
         .as_str()
         .ok_or_else(|| anyhow!("Python cell has no inline content"))?;
+    let code = rewrite_cell_imports(source);
+    let code = code.as_str();
 
     Python::with_gil(|py| {
         // Create globals dict for execution
@@ -404,20 +2054,83 @@ fn execute_python_cell(_canvas: &Canvas, cell: &Cell, inputs: &[CellData]) -> Re
         }
         globals.set_item("inputs", inputs_list)?;
 
+        // Inputs from named ports are exposed under their port name
+        for (name, input) in named_inputs {
+            globals.set_item(name, celldata_to_python(py, input)?)?;
+        }
+
+        // Run parameters are exposed as the 'params' dict
+        let params_dict = PyDict::new_bound(py);
+        for (name, value) in params {
+            params_dict.set_item(name, celldata_to_python(py, value)?)?;
+        }
+        globals.set_item("params", params_dict)?;
+
         // Create output storage
         let output_dict = PyDict::new_bound(py);
         globals.set_item("__output__", &output_dict)?;
 
         // Add helper function for setting output
-        let set_output_code = r#"
-def set_output(value, key='result'):
-    __output__[key] = value
-"#;
-        py.run_bound(set_output_code, Some(&globals), None)?;
+        py.run_bound(SET_OUTPUT_CODE, Some(&globals), None)?;
+
+        // Read-only access to other cells; reads are recorded as implicit dependencies
+        let cell_reads = PyList::empty_bound(py);
+        let registry = build_cell_registry(py, canvas, outputs, source)?;
+        globals.set_item("__cell_registry__", registry)?;
+        globals.set_item("__cell_reads__", &cell_reads)?;
+        py.run_bound(CELLS_API_CODE, Some(&globals), None)?;
+
+        // Redirect sys.stdout/sys.stderr into buffers for the duration of the cell
+        let sys = py.import_bound("sys")?;
+        let string_io = py.import_bound("io")?.getattr("StringIO")?;
+        let stdout_buf = string_io.call0()?;
+        let stderr_buf = string_io.call0()?;
+        let old_stdout = sys.getattr("stdout")?;
+        let old_stderr = sys.getattr("stderr")?;
+        sys.setattr("stdout", &stdout_buf)?;
+        sys.setattr("stderr", &stderr_buf)?;
 
-        // Execute the cell code
-        py.run_bound(code, Some(&globals), None)
-            .map_err(|e| anyhow!("Python execution error: {}", e))?;
+        // Compile under a per-cell file name so traceback frames can be mapped back
+        let filename = cell_filename(cell);
+        let builtins = py.import_bound("builtins")?;
+        let run_result = builtins
+            .getattr("compile")
+            .and_then(|compile| compile.call1((code, filename.as_str(), "exec")))
+            .and_then(|compiled| builtins.getattr("exec")?.call1((compiled, &globals)));
+
+        sys.setattr("stdout", old_stdout)?;
+        sys.setattr("stderr", old_stderr)?;
+        captured.stdout = stdout_buf.call_method0("getvalue")?.extract()?;
+        captured.stderr = stderr_buf.call_method0("getvalue")?.extract()?;
+        for read in cell_reads.iter() {
+            let read: String = read.extract()?;
+            if let Ok(id) = Ulid::from_string(&read) {
+                if !captured.cell_reads.contains(&id) {
+                    captured.cell_reads.push(id);
+                }
+            }
+        }
+
+        if let Err(err) = run_result {
+            let traceback = extract_traceback(py, &err, &filename, source);
+            let location = traceback
+                .cell_line()
+                .map(|line| format!(" (line {})", line))
+                .unwrap_or_default();
+            let message = format!(
+                "Python execution error in cell {}{}: {}: {}",
+                cell.short_id, location, traceback.exception_type, traceback.message
+            );
+            captured.traceback = Some(traceback);
+            return Err(anyhow!(message));
+        }
+
+        // Every key becomes an output port that relationships can select
+        for (key, value) in output_dict.iter() {
+            captured
+                .output_ports
+                .insert(key.str()?.to_string(), python_to_celldata(&value)?);
+        }
 
         // Extract output
         let output = output_dict
@@ -432,28 +2145,393 @@ def set_output(value, key='result'):
     })
 }
 
-/// Validate Python cell (check syntax)
-fn validate_python_cell(cell: &Cell, _inputs: &[CellData]) -> Result<CellData> {
-    let code = cell
-        .content
-        .as_str()
-        .ok_or_else(|| anyhow!("Python cell has no inline content"))?;
+/// Runs one Python cell in a subprocess interpreter, mirroring `execute_python_cell`
+///
+/// Reads the cell and its inputs as JSON from stdin (values in `CellData`'s serde
+/// form), runs the cell with its console redirected and writes the outcome to stdout.
+const SUBPROCESS_RUNNER_CODE: &str = r#"
+import datetime, io, json, sys, traceback
 
-    Python::with_gil(|py| {
-        // Try to compile the code to check for syntax errors
-        py.run_bound(
-            &format!("compile({:?}, '<cell>', 'exec')", code),
-            None,
+def _decode(value):
+    if value == 'None':
+        return None
+    (kind, data), = value.items()
+    if kind == 'Binary':
+        return bytes(data)
+    if kind == 'DateTime':
+        # RFC 3339 in UTC; fractions may be finer than microseconds
+        seconds, _, fraction = data.rstrip('Z').partition('.')
+        value = datetime.datetime.fromisoformat(seconds).replace(tzinfo=datetime.timezone.utc)
+        return value.replace(microsecond=int(fraction[:6].ljust(6, '0')))
+    if kind == 'Table':
+        columns = [(c['name'], list(c['values'].values())[0]) for c in data['columns']]
+        try:
+            import pandas
+            return pandas.DataFrame(dict(columns))
+        except ImportError:
+            pass
+        rows = len(columns[0][1]) if columns else 0
+        return [{name: values[i] for name, values in columns} for i in range(rows)]
+    return data
+
+def _table_columns(value):
+    if hasattr(value, 'to_pydict'):
+        return value.to_pydict()
+    if hasattr(value, 'to_dict') and hasattr(value, 'columns'):
+        return value.astype(object).where(value.notna(), None).to_dict('list')
+    return None
+
+def _column(values):
+    present = [v for v in values if v is not None]
+    if all(isinstance(v, (int, float)) and not isinstance(v, bool) for v in present):
+        return {'Number': [None if v is None else float(v) for v in values]}
+    if all(isinstance(v, bool) for v in present):
+        return {'Boolean': values}
+    return {'Text': [
+        v if v is None or isinstance(v, str) else json.dumps(v, separators=(',', ':'), default=str)
+        for v in values
+    ]}
+
+def _encode(value):
+    if value is None:
+        return 'None'
+    if isinstance(value, bool):
+        return {'Boolean': value}
+    if isinstance(value, (int, float)):
+        return {'Number': float(value)}
+    if isinstance(value, str):
+        return {'Text': value}
+    if isinstance(value, (bytes, bytearray)):
+        return {'Binary': list(value)}
+    if isinstance(value, datetime.date):
+        if not isinstance(value, datetime.datetime):
+            value = datetime.datetime.combine(value, datetime.time())
+        if value.tzinfo is None:
+            value = value.replace(tzinfo=datetime.timezone.utc)
+        return {'DateTime': value.astimezone(datetime.timezone.utc).isoformat()}
+    columns = _table_columns(value)
+    if columns is not None:
+        return {'Table': {'columns': [
+            {'name': str(name), 'values': _column(list(values))}
+            for name, values in columns.items()
+        ]}}
+    return {'Json': value}
+
+request = json.load(sys.stdin)
+inputs = [_decode(value) for value in request['inputs']]
+cell_globals = {'inputs': inputs, '__output__': {}, '__cell_reads__': []}
+for i, value in enumerate(inputs):
+    cell_globals['input_{}'.format(i)] = value
+for name, value in request['named_inputs']:
+    cell_globals[name] = _decode(value)
+cell_globals['params'] = {name: _decode(value) for name, value in request['params'].items()}
+cell_globals['__cell_registry__'] = {
+    key: dict(data, output=_decode(data['output'])) for key, data in request['registry'].items()
+}
+for code in request['prelude']:
+    exec(code, cell_globals)
+
+stdout, stderr = io.StringIO(), io.StringIO()
+sys.stdout, sys.stderr = stdout, stderr
+error = None
+try:
+    exec(compile(request['code'], request['filename'], 'exec'), cell_globals)
+except BaseException as e:
+    in_cell = isinstance(e, SyntaxError) and e.filename == request['filename']
+    error = {
+        'exception_type': type(e).__name__,
+        'message': str(e),
+        # The first frame is this runner's own exec call
+        'frames': [
+            {'filename': f.filename, 'line': f.lineno, 'function': f.name, 'source': f.line}
+            for f in traceback.extract_tb(e.__traceback__)[1:]
+        ],
+        'syntax_line': e.lineno if in_cell else None,
+    }
+finally:
+    sys.stdout, sys.stderr = sys.__stdout__, sys.__stderr__
+
+output_ports = {}
+if error is None:
+    output_ports = {str(key): _encode(value) for key, value in cell_globals['__output__'].items()}
+json.dump({
+    'stdout': stdout.getvalue(),
+    'stderr': stderr.getvalue(),
+    'cell_reads': cell_globals['__cell_reads__'],
+    'output_ports': output_ports,
+    'error': error,
+}, sys.stdout)
+"#;
+
+/// Data a Python cell sees besides the canvas
+struct PythonRequest<'a> {
+    outputs: &'a HashMap<Ulid, CellData>,
+    inputs: &'a [CellData],
+    named_inputs: &'a [(String, CellData)],
+    params: &'a BTreeMap<String, CellData>,
+}
+
+/// Outcome of a cell run by `SUBPROCESS_RUNNER_CODE`
+#[derive(Debug, Deserialize)]
+struct PythonResponse {
+    stdout: String,
+    stderr: String,
+    cell_reads: Vec<String>,
+    output_ports: BTreeMap<String, CellData>,
+    error: Option<PythonError>,
+}
+
+/// Exception raised by a cell run in a subprocess
+#[derive(Debug, Deserialize)]
+struct PythonError {
+    exception_type: String,
+    message: String,
+    frames: Vec<RawFrame>,
+    syntax_line: Option<usize>,
+}
+
+/// Execute a Python cell in a subprocess of the given interpreter
+fn execute_python_subprocess(
+    interpreter: &Path,
+    canvas: &Canvas,
+    cell: &Cell,
+    request: PythonRequest,
+    captured: &mut CapturedOutput,
+) -> Result<CellData> {
+    let source = cell
+        .content
+        .as_str()
+        .ok_or_else(|| anyhow!("Python cell has no inline content"))?;
+    let filename = cell_filename(cell);
+
+    // Same registry as `build_cell_registry`, keyed by short ID and by name; other
+    // outputs stay in this process
+    let mut registry = serde_json::Map::new();
+    for other in cells_named_in(canvas, source) {
+        let data = serde_json::json!({
+            "id": other.id.to_string(),
+            "short_id": other.short_id,
+            "name": other.name,
+            "cell_type": format!("{:?}", other.cell_type),
+            "content": other.content.as_str(),
+            "computed_result": other.computed_result,
+            "output": request.outputs.get(&other.id).unwrap_or(&CellData::None),
+        });
+        registry.insert(other.short_id.to_uppercase(), data.clone());
+        if let Some(name) = &other.name {
+            registry.insert(name.clone(), data);
+        }
+    }
+
+    let request = serde_json::json!({
+        "code": rewrite_cell_imports(source),
+        "filename": filename,
+        "prelude": [SET_OUTPUT_CODE, CELLS_API_CODE],
+        "inputs": request.inputs,
+        "named_inputs": request.named_inputs,
+        "params": request.params,
+        "registry": registry,
+    });
+    let timeout =
+        Duration::from_secs(cell.python_timeout_secs.unwrap_or(DEFAULT_PYTHON_TIMEOUT_SECS));
+    let response = run_python(interpreter, SUBPROCESS_RUNNER_CODE, &request, timeout)
+        .map_err(|e| anyhow!("Python cell {}: {}", cell.short_id, e))?;
+    let response: PythonResponse = serde_json::from_value(response)?;
+
+    captured.stdout = response.stdout;
+    captured.stderr = response.stderr;
+    for read in response.cell_reads {
+        if let Ok(id) = Ulid::from_string(&read) {
+            if !captured.cell_reads.contains(&id) {
+                captured.cell_reads.push(id);
+            }
+        }
+    }
+
+    if let Some(error) = response.error {
+        let traceback = cell_traceback(
+            error.exception_type,
+            error.message,
+            error.frames,
+            error.syntax_line,
+            &filename,
+            source,
+        );
+        let location = traceback
+            .cell_line()
+            .map(|line| format!(" (line {})", line))
+            .unwrap_or_default();
+        let message = format!(
+            "Python execution error in cell {}{}: {}: {}",
+            cell.short_id, location, traceback.exception_type, traceback.message
+        );
+        captured.traceback = Some(traceback);
+        return Err(anyhow!(message));
+    }
+
+    captured.output_ports.extend(response.output_ports);
+    let output = ["result", "output", "value"]
+        .iter()
+        .find_map(|key| captured.output_ports.get(*key))
+        .cloned()
+        .unwrap_or(CellData::None);
+    Ok(output)
+}
+
+/// Cell name or short ID for messages about relationships
+fn relationship_label(canvas: &Canvas, cell_id: Ulid) -> String {
+    canvas
+        .get_cell(cell_id)
+        .map(|cell| cell.label())
+        .unwrap_or_else(|| cell_id.to_string())
+}
+
+/// File name a cell's code is compiled under (shows up in tracebacks)
+fn cell_filename(cell: &Cell) -> String {
+    format!("<cell {}>", cell.short_id)
+}
+
+/// Build a structured traceback from a Python error
+fn extract_traceback(py: Python, err: &PyErr, filename: &str, code: &str) -> PythonTraceback {
+    let exception_type = err
+        .get_type_bound(py)
+        .name()
+        .map(|n| n.to_string())
+        .unwrap_or_else(|_| "Exception".to_string());
+    let value = err.value_bound(py);
+    let message = value.str().map(|s| s.to_string()).unwrap_or_default();
+
+    let mut frames = Vec::new();
+    if let Some(tb) = err.traceback_bound(py) {
+        let summaries = py
+            .import_bound("traceback")
+            .and_then(|m| m.getattr("extract_tb")?.call1((tb,)))
+            .and_then(|list| list.extract::<Vec<Bound<'_, PyAny>>>())
+            .unwrap_or_default();
+
+        for summary in summaries {
+            frames.push(RawFrame {
+                filename: summary
+                    .getattr("filename")
+                    .and_then(|v| v.extract())
+                    .unwrap_or_default(),
+                line: summary
+                    .getattr("lineno")
+                    .and_then(|v| v.extract())
+                    .unwrap_or(0),
+                function: summary
+                    .getattr("name")
+                    .and_then(|v| v.extract())
+                    .unwrap_or_default(),
+                source: summary
+                    .getattr("line")
+                    .and_then(|v| v.extract::<Option<String>>())
+                    .ok()
+                    .flatten(),
+            });
+        }
+    }
+
+    // SyntaxError carries its location on the exception rather than in the stack
+    let syntax_file: Option<String> = value.getattr("filename").and_then(|v| v.extract()).ok();
+    let syntax_line = match syntax_file {
+        Some(file) if file == filename => {
+            value.getattr("lineno").and_then(|v| v.extract::<usize>()).ok()
+        }
+        _ => None,
+    };
+
+    cell_traceback(exception_type, message, frames, syntax_line, filename, code)
+}
+
+/// Traceback frame as reported by Python, before mapping it to the cell content
+#[derive(Debug, Deserialize)]
+struct RawFrame {
+    filename: String,
+    line: usize,
+    function: String,
+    source: Option<String>,
+}
+
+/// Map a Python stack onto the cell content
+///
+/// Frames executing the cell's own code get `cell_line` set, and syntax errors
+/// (which have no frames in the cell) get a synthetic frame at the offending line.
+fn cell_traceback(
+    exception_type: String,
+    message: String,
+    raw_frames: Vec<RawFrame>,
+    syntax_line: Option<usize>,
+    filename: &str,
+    code: &str,
+) -> PythonTraceback {
+    let source_line = |line: usize| {
+        code.lines()
+            .nth(line.saturating_sub(1))
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+    };
+
+    let mut frames = Vec::new();
+    for frame in raw_frames {
+        // Skip the `exec` trampoline frames that aren't part of any file
+        if frame.filename.is_empty() {
+            continue;
+        }
+
+        let in_cell = frame.filename == filename;
+        frames.push(TracebackFrame {
+            source: if in_cell {
+                source_line(frame.line)
+            } else {
+                frame.source.filter(|l| !l.is_empty())
+            },
+            cell_line: if in_cell { Some(frame.line) } else { None },
+            filename: frame.filename,
+            line: frame.line,
+            function: frame.function,
+        });
+    }
+
+    if let Some(line) = syntax_line {
+        frames.push(TracebackFrame {
+            filename: filename.to_string(),
+            line,
+            cell_line: Some(line),
+            function: "<module>".to_string(),
+            source: source_line(line),
+        });
+    }
+
+    PythonTraceback {
+        exception_type,
+        message,
+        frames,
+    }
+}
+
+/// Validate Python cell (check syntax)
+fn validate_python_cell(cell: &Cell) -> Result<()> {
+    let code = cell
+        .content
+        .as_str()
+        .ok_or_else(|| anyhow!("Python cell has no inline content"))?;
+
+    Python::with_gil(|py| {
+        // Try to compile the code to check for syntax errors, after `cell:` rewriting
+        py.run_bound(
+            &format!("compile({:?}, '<cell>', 'exec')", rewrite_cell_imports(code)),
+            None,
             None,
         )
         .map_err(|e| anyhow!("Python syntax error: {}", e))?;
 
-        Ok(CellData::Text("(syntax valid)".to_string()))
+        Ok(())
     })
 }
 
 /// Convert CellData to Python object
-fn celldata_to_python(py: Python, data: &CellData) -> PyResult<PyObject> {
+pub(crate) fn celldata_to_python(py: Python, data: &CellData) -> PyResult<PyObject> {
     match data {
         CellData::None => Ok(py.None()),
         CellData::Text(s) => Ok(s.to_object(py)),
@@ -467,11 +2545,45 @@ fn celldata_to_python(py: Python, data: &CellData) -> PyResult<PyObject> {
             py_loads.call1((json_str,))?.extract()
         }
         CellData::Binary(b) => Ok(b.to_object(py)),
+        CellData::Table(t) => table_to_python(py, t),
+        // An aware datetime in UTC
+        CellData::DateTime(d) => {
+            let text = d.to_rfc3339_opts(SecondsFormat::Micros, false);
+            let datetime = py.import_bound("datetime")?.getattr("datetime")?;
+            Ok(datetime.call_method1("fromisoformat", (text,))?.unbind())
+        }
+    }
+}
+
+/// Tables become a pandas DataFrame when pandas is installed, else a list of row dicts;
+/// both keep the column order
+fn table_to_python(py: Python, table: &Table) -> PyResult<PyObject> {
+    let columns = PyDict::new_bound(py);
+    for column in table.columns() {
+        let values = match &column.values {
+            ColumnValues::Text(values) => values.to_object(py),
+            ColumnValues::Number(values) => values.to_object(py),
+            ColumnValues::Boolean(values) => values.to_object(py),
+        };
+        columns.set_item(&column.name, values)?;
+    }
+    if let Ok(pandas) = py.import_bound("pandas") {
+        return Ok(pandas.getattr("DataFrame")?.call1((columns,))?.unbind());
+    }
+
+    let rows = PyList::empty_bound(py);
+    for row in 0..table.row_count() {
+        let dict = PyDict::new_bound(py);
+        for (name, values) in columns.iter() {
+            dict.set_item(name, values.get_item(row)?)?;
+        }
+        rows.append(dict)?;
     }
+    Ok(rows.into_any().unbind())
 }
 
 /// Convert Python object to CellData
-fn python_to_celldata(obj: &Bound<'_, PyAny>) -> Result<CellData> {
+pub(crate) fn python_to_celldata(obj: &Bound<'_, PyAny>) -> Result<CellData> {
     // Try different conversions
     if obj.is_none() {
         Ok(CellData::None)
@@ -483,6 +2595,10 @@ fn python_to_celldata(obj: &Bound<'_, PyAny>) -> Result<CellData> {
         Ok(CellData::Boolean(b))
     } else if let Ok(bytes) = obj.extract::<Vec<u8>>() {
         Ok(CellData::Binary(bytes))
+    } else if let Some(datetime) = python_datetime(obj)? {
+        Ok(CellData::DateTime(datetime))
+    } else if let Some(table) = python_table(obj)? {
+        Ok(CellData::Table(table))
     } else {
         // Try to convert to JSON
         Python::with_gil(|py| {
@@ -495,8 +2611,69 @@ fn python_to_celldata(obj: &Bound<'_, PyAny>) -> Result<CellData> {
     }
 }
 
+/// A `datetime` (naive ones taken as UTC) or a `date` (at midnight UTC) as an instant;
+/// None for any other object
+fn python_datetime(obj: &Bound<'_, PyAny>) -> Result<Option<DateTime<Utc>>> {
+    let module = obj.py().import_bound("datetime")?;
+    if !obj.is_instance(&module.getattr("date")?)? {
+        return Ok(None);
+    }
+    let datetime = module.getattr("datetime")?;
+    let utc = module.getattr("timezone")?.getattr("utc")?;
+    let value = if obj.is_instance(&datetime)? {
+        obj.clone()
+    } else {
+        datetime.call_method1("combine", (obj, module.getattr("time")?.call0()?))?
+    };
+    let value = if value.getattr("tzinfo")?.is_none() {
+        let kwargs = PyDict::new_bound(obj.py());
+        kwargs.set_item("tzinfo", &utc)?;
+        value.call_method("replace", (), Some(&kwargs))?
+    } else {
+        value.call_method1("astimezone", (&utc,))?
+    };
+    let text: String = value.call_method0("isoformat")?.extract()?;
+    let value = DateTime::parse_from_rfc3339(&text)?;
+    Ok(Some(value.with_timezone(&Utc)))
+}
+
+/// A pandas DataFrame or pyarrow Table as a table; None for any other object
+///
+/// Values go through JSON, so column types are inferred as for JSON rows.
+fn python_table(obj: &Bound<'_, PyAny>) -> Result<Option<Table>> {
+    let py = obj.py();
+    let columns = if obj.hasattr("to_pydict")? {
+        obj.call_method0("to_pydict")?
+    } else if obj.hasattr("to_dict")? && obj.hasattr("columns")? {
+        // NaN and NaT become None
+        obj.call_method1("astype", ("object",))?
+            .call_method1("where", (obj.call_method0("notna")?, py.None()))?
+            .call_method1("to_dict", ("list",))?
+    } else {
+        return Ok(None);
+    };
+    let columns = columns.downcast::<PyDict>().map_err(|e| anyhow!("{}", e))?;
+
+    let dumps = py.import_bound("json")?.getattr("dumps")?;
+    let kwargs = PyDict::new_bound(py);
+    kwargs.set_item("default", py.import_bound("builtins")?.getattr("str")?)?;
+    let mut named = Vec::new();
+    for (name, values) in columns.iter() {
+        let json: String = dumps.call((values,), Some(&kwargs))?.extract()?;
+        named.push((name.str()?.to_string(), serde_json::from_str(&json)?));
+    }
+    Table::from_json_columns(named).map(Some)
+}
+
 /// Execute a Math cell - evaluate expression with cell references
-fn execute_math_cell(canvas: &Canvas, cell: &Cell) -> Result<CellData> {
+///
+/// Tables output by referenced cells are available to its aggregate functions.
+fn execute_math_cell(
+    canvas: &Canvas,
+    cell: &Cell,
+    params: &BTreeMap<String, CellData>,
+    outputs: &HashMap<Ulid, CellData>,
+) -> Result<CellData> {
     // Get formula from cell content
     let formula = cell
         .content
@@ -509,12 +2686,37 @@ fn execute_math_cell(canvas: &Canvas, cell: &Cell) -> Result<CellData> {
     }
 
     // Evaluate the expression
-    let result = crate::math_eval::evaluate_expression(formula, canvas)
+    let result = crate::math_eval::evaluate_expression_with_data(formula, canvas, params, outputs)
         .map_err(|e| anyhow!("Math evaluation error in cell {}: {}", cell.short_id, e))?;
 
     Ok(CellData::Number(result))
 }
 
+/// Execute a Date cell: its content read in its format and time zone, or without content
+/// its first input (a date-time, or text in that format)
+fn execute_date_cell(cell: &Cell, inputs: &[CellData]) -> Result<CellData> {
+    let value = match inputs.first() {
+        _ if !cell.content.is_empty() => cell_datetime(cell),
+        Some(CellData::DateTime(value)) => Ok(*value),
+        Some(CellData::Text(text)) => parse_time_zone(&cell.time_zone)
+            .and_then(|tz| parse_datetime(text, &cell.date_format, tz)),
+        Some(other) => Err(anyhow!("No date in {:?} input", other.data_type())),
+        None => Err(anyhow!("No date")),
+    };
+    value
+        .map(CellData::DateTime)
+        .map_err(|e| anyhow!("Date cell {}: {}", cell.short_id, e))
+}
+
+/// A Chart cell's spec, parsed from its content
+fn chart_spec(cell: &Cell) -> Result<ChartSpec> {
+    ChartSpec::parse(
+        cell.content
+            .as_str()
+            .ok_or_else(|| anyhow!("Chart cell has no inline content"))?,
+    )
+}
+
 /// Execute a Number cell - parse value from content
 fn execute_number_cell(cell: &Cell) -> Result<CellData> {
     let content = cell
@@ -533,7 +2735,7 @@ fn execute_number_cell(cell: &Cell) -> Result<CellData> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{CellContent, Rectangle};
+    use crate::{CellContent, CompareOp, Guard, LoopConfig, Rectangle, Transform};
 
     #[test]
     fn test_celldata_coerce_to_string() {
@@ -543,6 +2745,23 @@ mod tests {
         assert_eq!(CellData::Boolean(true).coerce_to_string(), "true");
     }
 
+    #[test]
+    fn test_celldata_summary() {
+        assert_eq!(CellData::Text("hello".to_string()).summary(), "hello");
+        assert_eq!(CellData::Text("line 1\nline 2".to_string()).summary(), "line 1…");
+        assert_eq!(CellData::Text("x".repeat(100)).summary(), format!("{}…", "x".repeat(80)));
+        assert_eq!(CellData::Number(2.5).summary(), "2.5");
+        assert_eq!(CellData::None.summary(), "None");
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        assert_eq!(CellData::Binary(png).summary(), "<image 16 bytes>");
+        assert_eq!(CellData::Binary(vec![0; 3]).summary(), "<binary 3 bytes>");
+
+        let mut table = Table::new();
+        table.push_column("a", ColumnValues::Number(vec![Some(1.0), None])).unwrap();
+        table.push_column("b", ColumnValues::Text(vec![None, None])).unwrap();
+        assert_eq!(CellData::Table(table).summary(), "<table 2×2>");
+    }
+
     #[test]
     fn test_text_cell_execution() {
         let cell = Cell::new(
@@ -568,7 +2787,7 @@ mod tests {
         );
 
         let cell = canvas.get_cell(cell_id).unwrap();
-        let output = execute_python_cell(&canvas, cell, &[]).unwrap();
+        let output = execute_python_cell(&canvas, &HashMap::new(), cell, &[], &[], &BTreeMap::new(), &mut CapturedOutput::default()).unwrap();
 
         assert_eq!(output, CellData::Number(42.0));
     }
@@ -584,55 +2803,1277 @@ mod tests {
 
         let cell = canvas.get_cell(cell_id).unwrap();
         let inputs = vec![CellData::Number(21.0)];
-        let output = execute_python_cell(&canvas, cell, &inputs).unwrap();
+        let output = execute_python_cell(&canvas, &HashMap::new(), cell, &inputs, &[], &BTreeMap::new(), &mut CapturedOutput::default()).unwrap();
 
         assert_eq!(output, CellData::Number(42.0));
     }
 
     #[test]
-    fn test_execution_engine_simple() {
-        let mut canvas = Canvas::with_root_cell(
+    fn test_python_cell_captures_stdout_and_stderr() {
+        let mut canvas = Canvas::new();
+        let cell_id = canvas.create_cell(
             CellType::Python,
             Rectangle::new(0.0, 0.0, 100.0, 100.0),
-            CellContent::inline("set_output(100)"),
+            CellContent::inline(
+                "import sys\nprint('hello')\nprint('warn', file=sys.stderr)\nset_output(1)",
+            ),
         );
 
-        let root = canvas.root_cell().unwrap();
-        canvas.set_start_point(root).unwrap();
+        let cell = canvas.get_cell(cell_id).unwrap();
+        let mut captured = CapturedOutput::default();
+        execute_python_cell(&canvas, &HashMap::new(), cell, &[], &[], &BTreeMap::new(), &mut captured).unwrap();
+
+        assert_eq!(captured.stdout, "hello\n");
+        assert_eq!(captured.stderr, "warn\n");
+        assert!(captured.traceback.is_none());
+    }
+
+    #[test]
+    fn test_python_traceback_maps_cell_lines() {
+        let mut canvas = Canvas::new();
+        let cell_id = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("def f(x):\n    return 1 / x\n\nprint('before')\nf(0)"),
+        );
+        canvas.set_start_point(cell_id).unwrap();
 
         let mut engine = ExecutionEngine::new(ExecutionMode::Run);
-        let report = engine.execute(&canvas).unwrap();
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
 
-        assert_eq!(report.status, ExecutionStatus::Complete);
-        assert_eq!(report.total_cells_executed, 1);
-        assert_eq!(report.log[0].output, CellData::Number(100.0));
+        let entry = &engine.log()[0];
+        assert_eq!(entry.stdout, "before\n");
+        assert!(entry.error.is_some());
+
+        let traceback = entry.traceback.as_ref().unwrap();
+        assert_eq!(traceback.exception_type, "ZeroDivisionError");
+        let cell_lines: Vec<_> = traceback.frames.iter().filter_map(|f| f.cell_line).collect();
+        assert_eq!(cell_lines, vec![5, 2]);
+        assert_eq!(traceback.frames[1].function, "f");
+        assert_eq!(traceback.frames[1].source.as_deref(), Some("return 1 / x"));
     }
 
     #[test]
-    fn test_execution_engine_with_relationship() {
+    fn test_python_syntax_error_traceback() {
         let mut canvas = Canvas::new();
+        let cell_id = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("x = 1\nif x\n    pass"),
+        );
 
-        let cell1 = canvas.create_cell(
+        let cell = canvas.get_cell(cell_id).unwrap();
+        let mut captured = CapturedOutput::default();
+        assert!(execute_python_cell(&canvas, &HashMap::new(), cell, &[], &[], &BTreeMap::new(), &mut captured).is_err());
+
+        let traceback = captured.traceback.unwrap();
+        assert_eq!(traceback.exception_type, "SyntaxError");
+        assert_eq!(traceback.cell_line(), Some(2));
+    }
+
+    #[test]
+    fn test_python_subprocess_interpreter() {
+        let mut canvas = Canvas::new();
+        let load = canvas.create_cell(
             CellType::Python,
             Rectangle::new(0.0, 0.0, 100.0, 100.0),
-            CellContent::inline("set_output(10)"),
+            CellContent::inline("print('loading')\nset_output(params['n'] * 2)"),
+        );
+        canvas.rename_cell(load, Some("Load".to_string())).unwrap();
+        canvas.set_start_point(load).unwrap();
+        let total = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline(
+                "import sys\nset_output(input_0 + cell:Load, 'total')\nset_output(sys.executable)",
+            ),
         );
+        canvas.create_relationship(load, total).unwrap();
+        let fail = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(300.0, 0.0, 100.0, 100.0),
+            CellContent::inline("x = 1\nx / 0"),
+        );
+        canvas.create_relationship(total, fail).unwrap();
 
-        let cell2 = canvas.create_cell(
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_python_interpreter(Some(PathBuf::from("python3")));
+        engine.set_params(BTreeMap::from([("n".to_string(), CellData::Number(4.0))]));
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("(line 2): ZeroDivisionError"));
+
+        let log = engine.log();
+        assert_eq!(log[0].output, CellData::Number(8.0));
+        assert_eq!(log[0].stdout, "loading\n");
+        assert!(matches!(&log[1].output, CellData::Text(path) if path.contains("python")));
+        assert_eq!(log[1].output_ports["total"], CellData::Number(16.0));
+        assert_eq!(log[1].cell_reads, vec![load]);
+        assert_eq!(log[2].traceback.as_ref().unwrap().cell_line(), Some(2));
+
+        // A cell that runs too long is stopped
+        canvas.update_cell_content(fail, CellContent::inline("while True: pass")).unwrap();
+        canvas.get_cell_mut(fail).unwrap().python_timeout_secs = Some(1);
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+
+        // Only the cells the code names are sent to the subprocess (by short ID and name)
+        let code = "set_output(len(cells._registry)) if 'Load' in cells else None";
+        canvas.update_cell_content(fail, CellContent::inline(code)).unwrap();
+        let report = engine.execute(&canvas).unwrap();
+        assert_eq!(report.log[2].output, CellData::Number(2.0));
+        let code = "set_output(len(cells._registry))";
+        canvas.update_cell_content(fail, CellContent::inline(code)).unwrap();
+        let report = engine.execute(&canvas).unwrap();
+        assert_eq!(report.log[2].output, CellData::Number(0.0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_cells() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("data")).unwrap();
+        std::fs::write(dir.path().join("data/names.txt"), "b\na\n").unwrap();
+
+        let mut canvas = Canvas::new();
+        let count = canvas.create_cell(
+            CellType::NumberInt,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("3"),
+        );
+        canvas.set_start_point(count).unwrap();
+        let list = canvas.create_cell(
+            CellType::Shell,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("sort names.txt | head -n \"$INPUT_0\" | paste -sd ' ' -"),
+        );
+        canvas.get_cell_mut(list).unwrap().working_dir = Some(PathBuf::from("data"));
+        canvas.create_relationship(count, list).unwrap();
+        let json = canvas.create_cell(
+            CellType::Shell,
+            Rectangle::new(300.0, 0.0, 100.0, 100.0),
+            CellContent::inline(r#"echo "{\"names\": \"$(cat)\", \"n\": $PARAM_N}""#),
+        );
+        canvas.create_relationship(list, json).unwrap();
+        let fail = canvas.create_cell(
+            CellType::Shell,
+            Rectangle::new(450.0, 0.0, 100.0, 100.0),
+            CellContent::inline("echo 'bad input' >&2; exit 4"),
+        );
+        canvas.create_relationship(json, fail).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        engine.set_params(BTreeMap::from([("n".to_string(), CellData::Number(2.0))]));
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("exit status: 4"));
+
+        let log = engine.log();
+        assert_eq!(log[1].output, CellData::Text("a b".to_string()));
+        assert_eq!(log[2].output, CellData::Json(serde_json::json!({"names": "a b", "n": 2})));
+        assert_eq!(log[3].stderr, "bad input\n");
+
+        // Dry run checks that the commands exist
+        canvas
+            .update_cell_content(fail, CellContent::inline("no-such-tool-gce | wc -l"))
+            .unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("no-such-tool-gce"));
+
+        // Commands that run too long are killed
+        canvas.update_cell_content(fail, CellContent::inline("sleep 5")).unwrap();
+        canvas.get_cell_mut(fail).unwrap().shell_timeout_secs = Some(1);
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+
+    #[test]
+    fn test_sql_cells() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut canvas = Canvas::new();
+        let rows = canvas.create_cell(
             CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline(
+                "set_output([{'name': 'a', 'score': 3}, {'name': 'b', 'score': 9}])",
+            ),
+        );
+        canvas.set_start_point(rows).unwrap();
+        let query = canvas.create_cell(
+            CellType::Sql,
             Rectangle::new(150.0, 0.0, 100.0, 100.0),
-            CellContent::inline("set_output(input_0 * 2)"),
+            CellContent::inline(
+                "INSERT INTO log VALUES (:run);\n\
+                 SELECT name, score * 2 AS doubled FROM scores WHERE score > :min",
+            ),
+        );
+        canvas.create_relationship(rows, query).unwrap();
+        canvas
+            .set_relationship_ports(rows, query, None, Some("scores".to_string()))
+            .unwrap();
+        let python = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(300.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output([row['name'] for row in input_0])"),
         );
+        canvas.create_relationship(query, python).unwrap();
 
-        canvas.create_relationship(cell1, cell2).unwrap();
-        canvas.set_start_point(cell1).unwrap();
+        // Dry run checks the syntax; the input table doesn't exist yet
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        engine.execute(&canvas).unwrap();
+        assert!(engine.type_mismatches().is_empty());
 
+        let database = dir.path().join("project.db");
+        rusqlite::Connection::open(&database)
+            .unwrap()
+            .execute_batch("CREATE TABLE log (run TEXT)")
+            .unwrap();
         let mut engine = ExecutionEngine::new(ExecutionMode::Run);
-        let report = engine.execute(&canvas).unwrap();
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        engine.set_sql_database(Some(PathBuf::from("project.db")));
+        engine.set_params(BTreeMap::from([
+            ("min".to_string(), CellData::Number(5.0)),
+            ("run".to_string(), CellData::Text("nightly".to_string())),
+        ]));
+        engine.execute(&canvas).unwrap();
 
-        assert_eq!(report.status, ExecutionStatus::Complete);
-        assert_eq!(report.total_cells_executed, 2);
-        assert_eq!(report.log[1].output, CellData::Number(20.0));
+        let expected = serde_json::json!([{"name": "b", "doubled": 18.0}]);
+        match &engine.log()[1].output {
+            CellData::Table(table) => assert_eq!(table.to_json_rows(), expected),
+            other => panic!("expected a table, got {:?}", other),
+        }
+        assert_eq!(engine.log()[2].output, CellData::Json(serde_json::json!(["b"])));
+
+        // Writes persist in the project database; input tables are temporary
+        let connection = rusqlite::Connection::open(&database).unwrap();
+        let run: String = connection.query_row("SELECT run FROM log", [], |r| r.get(0)).unwrap();
+        assert_eq!(run, "nightly");
+        assert!(connection.prepare("SELECT * FROM scores").is_err());
+
+        canvas.update_cell_content(query, CellContent::inline("SELEC 1")).unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("syntax error"));
+    }
+
+    #[test]
+    fn test_script_cells() {
+        let mut canvas = Canvas::new();
+        let count = canvas.create_cell(
+            CellType::NumberInt,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("4"),
+        );
+        canvas.set_start_point(count).unwrap();
+        let script = canvas.create_cell(
+            CellType::Script,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("let n = input_0.to_int(); set_output(n * n, \"square\"); n + 1"),
+        );
+        canvas.create_relationship(count, script).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.execute(&canvas).unwrap();
+        assert_eq!(engine.log()[1].output, CellData::Number(5.0));
+        assert_eq!(engine.log()[1].output_ports["square"], CellData::Number(16.0));
+
+        let limits = ScriptLimits {
+            max_operations: 1_000,
+            ..ScriptLimits::default()
+        };
+        canvas
+            .update_cell_content(script, CellContent::inline("while true { }"))
+            .unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_script_limits(limits);
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("Too many operations"));
+
+        canvas
+            .update_cell_content(script, CellContent::inline("let = 1;"))
+            .unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("Script syntax error"));
+    }
+
+    #[test]
+    fn test_wasm_cells() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // Outputs its input document unchanged
+        let echo = r#"(module
+            (import "graph_cell" "set_output" (func $set_output (param i32 i32)))
+            (memory (export "memory") 1)
+            (func (export "alloc") (param i32) (result i32) (i32.const 16))
+            (func (export "run") (param i32 i32) (call $set_output (local.get 0) (local.get 1))))"#;
+        std::fs::write(dir.path().join("echo.wat"), echo).unwrap();
+
+        let mut canvas = Canvas::new();
+        let number = canvas.create_cell(
+            CellType::NumberFloat,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("2.5"),
+        );
+        canvas.set_start_point(number).unwrap();
+        let wasm = canvas.create_cell(
+            CellType::Wasm,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::external(PathBuf::from("echo.wat"), "echo module", false),
+        );
+        canvas.create_relationship(number, wasm).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        engine.execute(&canvas).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        engine.set_params(BTreeMap::from([("n".to_string(), CellData::Boolean(true))]));
+        engine.execute(&canvas).unwrap();
+        let expected =
+            serde_json::json!({"inputs": [2.5], "named_inputs": {}, "params": {"n": true}});
+        assert_eq!(engine.log()[1].output, CellData::Json(expected));
+
+        canvas.update_cell_content(wasm, CellContent::inline("(module)")).unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("doesn't export 'memory'"));
+    }
+
+    #[test]
+    fn test_json_cells() {
+        let mut canvas = Canvas::new();
+        let script = canvas.create_cell(
+            CellType::Script,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("#{ id: 7 }"),
+        );
+        canvas.set_start_point(script).unwrap();
+        let json = canvas.create_cell(
+            CellType::Json,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline(r#"{"id": 1, "tags": ["a"]}"#),
+        );
+        canvas.create_relationship(script, json).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.execute(&canvas).unwrap();
+        let expected = serde_json::json!({"id": 1, "tags": ["a"]});
+        assert_eq!(engine.log()[1].output, CellData::Json(expected));
+
+        canvas.get_cell_mut(json).unwrap().json_schema = Some(serde_json::json!({
+            "type": "object",
+            "required": ["id", "tags"]
+        }));
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let err = engine.execute(&canvas).unwrap_err().to_string();
+        assert!(err.contains("doesn't match the schema: (root): \"tags\" is a required property"));
+
+        canvas.update_cell_content(json, CellContent::inline("[1,")).unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("Invalid JSON at line 1"));
+    }
+
+    #[test]
+    fn test_csv_cells() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("scores.csv"), "zeta,alpha\n1,x\n,y\n").unwrap();
+
+        let mut canvas = Canvas::new();
+        let csv = canvas.create_cell(
+            CellType::Csv,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::external(PathBuf::from("scores.csv"), "scores", true),
+        );
+        canvas.set_start_point(csv).unwrap();
+        let python = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline(
+                "rows = input_0 if isinstance(input_0, list) else input_0.to_dict('records')\n\
+                 set_output([list(rows[0]), rows[1]['zeta']])",
+            ),
+        );
+        canvas.create_relationship(csv, python).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        engine.execute(&canvas).unwrap();
+        assert!(engine.type_mismatches().is_empty());
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        engine.execute(&canvas).unwrap();
+        match &engine.log()[0].output {
+            CellData::Table(table) => assert_eq!(table.row_count(), 2),
+            other => panic!("expected a table, got {:?}", other),
+        }
+        // Python sees the file's column order, and nulls as None
+        let expected = serde_json::json!([["zeta", "alpha"], null]);
+        assert_eq!(engine.log()[1].output, CellData::Json(expected));
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("scores.csv"));
+    }
+
+    #[test]
+    fn test_table_cells() {
+        let mut canvas = Canvas::new();
+        let csv = canvas.create_cell(
+            CellType::Csv,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("item,price\nbolt,0.5\nnut,\nscrew,1.5\n"),
+        );
+        canvas.set_start_point(csv).unwrap();
+        let short_id = canvas.get_cell(csv).unwrap().short_id.clone();
+        let total = canvas.create_cell(
+            CellType::Math,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline(format!("sum([[{0}]].price) * count([[{0}]].item)", short_id)),
+        );
+        canvas.create_relationship(csv, total).unwrap();
+        // Stands in for a pyarrow Table, converted through `to_pydict`
+        let columns = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 150.0, 100.0, 100.0),
+            CellContent::inline(
+                "class Columns:\n\
+                 \x20   def to_pydict(self):\n\
+                 \x20       return {'ok': [True, None], 'n': [1, 2.5], 'tag': ['a', {'b': 1}]}\n\
+                 set_output(Columns())",
+            ),
+        );
+        canvas.create_relationship(csv, columns).unwrap();
+
+        let expected = Table::from_json_columns([
+            ("ok".to_string(), vec![serde_json::json!(true), serde_json::Value::Null]),
+            ("n".to_string(), vec![serde_json::json!(1), serde_json::json!(2.5)]),
+            ("tag".to_string(), vec![serde_json::json!("a"), serde_json::json!({"b": 1})]),
+        ])
+        .unwrap();
+        for interpreter in [None, Some(PathBuf::from("python3"))] {
+            let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+            engine.set_python_interpreter(interpreter);
+            engine.execute(&canvas).unwrap();
+            let output = |cell| &engine.log().iter().find(|e| e.cell_id == cell).unwrap().output;
+            assert_eq!(output(total), &CellData::Number(6.0));
+            assert_eq!(output(columns), &CellData::Table(expected.clone()));
+        }
+    }
+
+    #[test]
+    fn test_image_cells() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"4\" height=\"4\"/>";
+        std::fs::write(dir.path().join("logo.svg"), svg).unwrap();
+
+        let mut canvas = Canvas::new();
+        let logo = canvas.create_cell(
+            CellType::Image,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::external(PathBuf::from("logo.svg"), "logo", false),
+        );
+        canvas.set_start_point(logo).unwrap();
+        // Stands in for a matplotlib figure saved to a BytesIO
+        let figure = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(b'\\x89PNG\\r\\n\\x1a\\n' + bytes(8))"),
+        );
+        canvas.create_relationship(logo, figure).unwrap();
+        let chart = canvas.create_cell(
+            CellType::Image,
+            Rectangle::new(300.0, 0.0, 100.0, 100.0),
+            CellContent::inline(""),
+        );
+        canvas.create_relationship(figure, chart).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        engine.execute(&canvas).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        let report = engine.execute(&canvas).unwrap();
+        assert_eq!(engine.log()[0].output, CellData::Binary(svg.as_bytes().to_vec()));
+        let png = &engine.log()[2].output;
+        assert!(matches!(png, CellData::Binary(bytes) if bytes.starts_with(b"\x89PNG")));
+        assert_eq!(png.coerce_to_string(), "[Binary data: 16 bytes]");
+        // The Markdown report embeds the image
+        let label = canvas.get_cell(chart).unwrap().label();
+        let section = format!("## {} (step 3)\n\n![{}](data:image/png;base64,", label, label);
+        assert!(report.to_markdown(&canvas).contains(&section));
+
+        canvas.update_cell_content(figure, CellContent::inline("set_output(b'GIF89a')")).unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_project_dir(Some(dir.path().to_path_buf()));
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("Not a PNG, JPEG or SVG image"));
+    }
+
+    #[test]
+    fn test_chart_cells() {
+        let mut canvas = Canvas::new();
+        let series = canvas.create_cell(
+            CellType::Json,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("[1, 4, 9]"),
+        );
+        canvas.set_start_point(series).unwrap();
+        let price = canvas.create_cell(
+            CellType::NumberFloat,
+            Rectangle::new(0.0, 150.0, 100.0, 100.0),
+            CellContent::inline("2.5"),
+        );
+        let price_id = canvas.get_cell(price).unwrap().short_id.clone();
+        let spec = format!(r#"{{"kind": "scatter", "cells": ["[[{}]]"]}}"#, price_id);
+        let chart = canvas.create_cell(
+            CellType::Chart,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline(&spec),
+        );
+        canvas.create_relationship(series, chart).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        engine.execute(&canvas).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.execute(&canvas).unwrap();
+        let CellData::Json(output) = &engine.log()[1].output else {
+            panic!("expected a chart");
+        };
+        let output: Chart = serde_json::from_value(output.clone()).unwrap();
+        assert_eq!(output.kind, crate::ChartKind::Scatter);
+        assert_eq!(
+            output.series[0].points,
+            vec![[0.0, 1.0], [1.0, 4.0], [2.0, 9.0]]
+        );
+        assert_eq!(output.series[1].points, vec![[0.0, 2.5]]);
+        assert_eq!(output.categories, vec![price_id]);
+
+        canvas
+            .update_cell_content(chart, CellContent::inline(r#"{"kind": "area"}"#))
+            .unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let err = engine.execute(&canvas).unwrap_err().to_string();
+        assert!(err.contains("Invalid chart spec"), "{}", err);
+    }
+
+    #[test]
+    fn test_date_cells() {
+        let mut canvas = Canvas::new();
+        let invoice = canvas.create_cell(
+            CellType::Date,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("01/03/2024 09:30"),
+        );
+        let cell = canvas.get_cell_mut(invoice).unwrap();
+        cell.date_format = "%d/%m/%Y %H:%M".to_string();
+        cell.time_zone = "Europe/London".to_string();
+        canvas.set_start_point(invoice).unwrap();
+        let due = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline(
+                "import datetime\n\
+                 assert input_0.tzinfo is not None\n\
+                 set_output(input_0 + datetime.timedelta(days=30, microseconds=5))",
+            ),
+        );
+        canvas.create_relationship(invoice, due).unwrap();
+        let shown = canvas.create_cell(
+            CellType::Date,
+            Rectangle::new(300.0, 0.0, 100.0, 100.0),
+            CellContent::inline(""),
+        );
+        canvas.create_relationship(due, shown).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        engine.execute(&canvas).unwrap();
+
+        let expected = parse_datetime("2024-03-31T09:30:00.000005Z", "", chrono_tz::Tz::UTC);
+        let expected = CellData::DateTime(expected.unwrap());
+        for interpreter in [None, Some(PathBuf::from("python3"))] {
+            let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+            engine.set_python_interpreter(interpreter);
+            engine.execute(&canvas).unwrap();
+            let log = engine.log();
+            assert_eq!(log[0].output.coerce_to_string(), "2024-03-01T09:30:00Z");
+            assert_eq!(log[1].output, expected);
+            assert_eq!(log[2].output, log[1].output);
+        }
+
+        canvas
+            .update_cell_content(invoice, CellContent::inline("31/02/2024 09:30"))
+            .unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let err = engine.execute(&canvas).unwrap_err().to_string();
+        assert!(err.contains("Cannot read '31/02/2024 09:30'"), "{}", err);
+    }
+
+    #[test]
+    fn test_rewrite_cell_imports() {
+        assert_eq!(
+            rewrite_cell_imports("import cell:Data as d"),
+            "d = cells[\"Data\"]"
+        );
+        assert_eq!(
+            rewrite_cell_imports("    from cell:Data import a, b as c"),
+            "    a, c = cells.import_from(\"Data\", \"a\", \"b\")"
+        );
+        assert_eq!(
+            rewrite_cell_imports("x = cell:Calculate * 2\nprint('cell:X')"),
+            "x = cells[\"Calculate\"].output * 2\nprint('cell:X')"
+        );
+    }
+
+    #[test]
+    fn test_python_reads_other_cells() {
+        let mut canvas = Canvas::new();
+        let source = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output({'a': 1, 'b': 2})"),
+        );
+        canvas.rename_cell(source, Some("Data".to_string())).unwrap();
+        let number = canvas.create_cell(
+            CellType::NumberInt,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("5"),
+        );
+        let number_short = canvas.get_cell(number).unwrap().short_id.clone();
+        let reader = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(300.0, 0.0, 100.0, 100.0),
+            CellContent::inline(format!(
+                "from cell:Data import b\nn = int(cells['{}'].content)\nset_output(cell:Data['a'] + b + n)",
+                number_short.to_lowercase()
+            )),
+        );
+        canvas.create_relationship(source, reader).unwrap();
+        canvas.set_start_point(source).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let report = engine.execute(&canvas).unwrap();
+
+        assert_eq!(report.log[1].output, CellData::Number(8.0));
+        assert_eq!(report.log[1].cell_reads, vec![source, number]);
+        assert_eq!(
+            engine.implicit_dependencies(),
+            vec![(reader, source), (reader, number)]
+        );
+
+        // The `cell:` syntax is not a syntax error in a dry run
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let report = engine.execute(&canvas).unwrap();
+        assert_eq!(report.status, ExecutionStatus::DryRunComplete);
+        assert!(report.log.iter().all(|entry| entry.error.is_none()));
+    }
+
+    #[test]
+    fn test_python_cells_api_is_read_only() {
+        let mut canvas = Canvas::new();
+        let source = canvas.create_cell(
+            CellType::NumberInt,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("5"),
+        );
+        canvas.rename_cell(source, Some("Five".to_string())).unwrap();
+        let writer = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("cells.Five.content = '6'"),
+        );
+
+        let cell = canvas.get_cell(writer).unwrap();
+        let mut captured = CapturedOutput::default();
+        let result = execute_python_cell(&canvas, &HashMap::new(), cell, &[], &[], &BTreeMap::new(), &mut captured);
+
+        assert!(result.is_err());
+        assert_eq!(captured.traceback.unwrap().exception_type, "AttributeError");
+        assert_eq!(canvas.get_cell(source).unwrap().content.as_str(), Some("5"));
+
+        // Only the cells the code names are in the registry, by short ID and by name
+        let outputs = HashMap::new();
+        let count = |code: &str| {
+            let reader = canvas.get_cell(writer).unwrap().clone();
+            let reader = Cell { content: CellContent::inline(code), ..reader };
+            let params = BTreeMap::new();
+            let mut captured = CapturedOutput::default();
+            execute_python_cell(&canvas, &outputs, &reader, &[], &[], &params, &mut captured)
+                .unwrap()
+        };
+        assert_eq!(count("set_output(len(cells._registry)) # Five"), CellData::Number(2.0));
+        assert_eq!(count("set_output(len(cells._registry))"), CellData::Number(0.0));
+    }
+
+    #[test]
+    fn test_execution_engine_simple() {
+        let mut canvas = Canvas::with_root_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(100)"),
+        );
+
+        let root = canvas.root_cell().unwrap();
+        canvas.set_start_point(root).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let report = engine.execute(&canvas).unwrap();
+
+        assert_eq!(report.status, ExecutionStatus::Complete);
+        assert_eq!(report.total_cells_executed, 1);
+        assert_eq!(report.log[0].output, CellData::Number(100.0));
+    }
+
+    #[test]
+    fn test_execution_engine_with_relationship() {
+        let mut canvas = Canvas::new();
+
+        let cell1 = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(10)"),
+        );
+
+        let cell2 = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(input_0 * 2)"),
+        );
+
+        canvas.create_relationship(cell1, cell2).unwrap();
+        canvas.set_start_point(cell1).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let report = engine.execute(&canvas).unwrap();
+
+        assert_eq!(report.status, ExecutionStatus::Complete);
+        assert_eq!(report.total_cells_executed, 2);
+        assert_eq!(report.log[1].output, CellData::Number(20.0));
+    }
+
+    #[test]
+    fn test_named_ports() {
+        let mut canvas = Canvas::new();
+
+        let split = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output([1, 2, 3], key='train')\nset_output([0.5], key='test')"),
+        );
+        let train = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(len(train_data) + len(input_0))"),
+        );
+
+        canvas.create_relationship(split, train).unwrap();
+        canvas
+            .set_relationship_ports(split, train, Some("train".to_string()), Some("train_data".to_string()))
+            .unwrap();
+        canvas.set_start_point(split).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let report = engine.execute(&canvas).unwrap();
+
+        assert_eq!(report.status, ExecutionStatus::Complete);
+        assert_eq!(report.log[0].output_ports.len(), 2);
+        assert_eq!(
+            engine.port_output(split, "test"),
+            Some(&CellData::Json(serde_json::json!([0.5])))
+        );
+        assert_eq!(report.log[1].output, CellData::Number(6.0));
+
+        // A port the source never set fails the consumer instead of dropping its input
+        canvas
+            .set_relationship_ports(split, train, Some("tarin".to_string()), Some("train_data".to_string()))
+            .unwrap();
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("set no output port 'tarin'"));
+    }
+
+    #[test]
+    fn test_relationship_transforms() {
+        let mut canvas = Canvas::new();
+
+        let source = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output({'rate': 0.25})"),
+        );
+        let target = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(input_0 + 1)"),
+        );
+        canvas.create_relationship(source, target).unwrap();
+        canvas.set_start_point(source).unwrap();
+
+        // A JSON path picks the field, then the target adds one
+        canvas
+            .set_relationship_transform(source, target, Some(Transform::JsonPath("$.rate".to_string())))
+            .unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let report = engine.execute(&canvas).unwrap();
+        assert_eq!(report.log[1].output, CellData::Number(1.25));
+
+        // Dry run rejects a malformed transform without running anything
+        canvas
+            .set_relationship_transform(source, target, Some(Transform::Math("x * ".to_string())))
+            .unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(err.to_string().contains("Transform on relationship"));
+    }
+
+    #[test]
+    fn test_conditional_branches() {
+        let mut canvas = Canvas::new();
+
+        let check = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(len([r for r in [1, None, 3] if r is None]))"),
+        );
+        let report = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(f'{int(input_0)} bad rows')"),
+        );
+        let proceed = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 150.0, 100.0, 100.0),
+            CellContent::inline("set_output('ok')"),
+        );
+        canvas.create_relationship(check, report).unwrap();
+        canvas.create_relationship(check, proceed).unwrap();
+        canvas.set_relationship_guard(check, report, Some(Guard::Truthy)).unwrap();
+        canvas.set_relationship_guard(check, proceed, Some(Guard::Falsy)).unwrap();
+        canvas.set_start_point(check).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let result = engine.execute(&canvas).unwrap();
+
+        assert_eq!(result.total_cells_executed, 2);
+        assert_eq!(result.log[0].branches_taken, vec![report]);
+        assert_eq!(result.log[0].branches_skipped, vec![proceed]);
+        assert_eq!(result.log[1].output, CellData::Text("1 bad rows".to_string()));
+    }
+
+    #[test]
+    fn test_dry_run_lists_unreachable_branches() {
+        let mut canvas = Canvas::new();
+
+        let limit = canvas.create_cell(
+            CellType::NumberInt,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("5"),
+        );
+        let big = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(input_0)"),
+        );
+        let small = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 150.0, 100.0, 100.0),
+            CellContent::inline("set_output(input_0)"),
+        );
+        canvas.create_relationship(limit, big).unwrap();
+        canvas.create_relationship(limit, small).unwrap();
+        let over = |value: &str| Guard::Compare {
+            op: CompareOp::Gt,
+            value: value.to_string(),
+        };
+        canvas.set_relationship_guard(limit, big, Some(over("10"))).unwrap();
+        canvas.set_relationship_guard(limit, small, Some(over("1"))).unwrap();
+        canvas.set_start_point(limit).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let report = engine.execute(&canvas).unwrap();
+
+        assert_eq!(report.unreachable_branches, vec![(limit, big)]);
+        assert_eq!(report.total_cells_executed, 2);
+    }
+
+    #[test]
+    fn test_partial_runs_reuse_cached_outputs() {
+        let mut canvas = Canvas::new();
+        let code = [
+            "set_output(10)",
+            "set_output(input_0 * 2)",
+            "set_output(input_0 + 1)",
+            "set_output(input_0 * 10)",
+        ];
+        let ids: Vec<Ulid> = code
+            .iter()
+            .enumerate()
+            .map(|(i, code)| {
+                canvas.create_cell(
+                    CellType::Python,
+                    Rectangle::new(150.0 * i as f32, 0.0, 100.0, 100.0),
+                    CellContent::inline(*code),
+                )
+            })
+            .collect();
+        for pair in ids.windows(2) {
+            canvas.create_relationship(pair[0], pair[1]).unwrap();
+        }
+        canvas.set_start_point(ids[0]).unwrap();
+
+        // Upstream outputs must be cached before running from the middle
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        assert!(engine.execute_from(&canvas, ids[1]).is_err());
+        engine.execute(&canvas).unwrap();
+
+        canvas
+            .update_cell_content(ids[1], CellContent::inline("set_output(input_0 * 3)"))
+            .unwrap();
+        let report = engine.execute_from(&canvas, ids[1]).unwrap();
+        assert_eq!(report.status, ExecutionStatus::Complete);
+        let ran: Vec<Ulid> = report.log.iter().map(|e| e.cell_id).collect();
+        assert_eq!(ran, vec![ids[1], ids[2], ids[3]]);
+        assert_eq!(report.log[2].output, CellData::Number(310.0));
+
+        // The subgraph between two cells stops before the cells below it
+        canvas
+            .update_cell_content(ids[2], CellContent::inline("set_output(input_0 + 2)"))
+            .unwrap();
+        let report = engine.execute_subgraph(&canvas, ids[1], ids[2]).unwrap();
+        let ran: Vec<Ulid> = report.log.iter().map(|e| e.cell_id).collect();
+        assert_eq!(ran, vec![ids[1], ids[2]]);
+        assert_eq!(report.log[1].output, CellData::Number(32.0));
+
+        assert!(engine.execute_subgraph(&canvas, ids[2], ids[0]).is_err());
+    }
+
+    #[test]
+    fn test_named_entry_points() {
+        let mut canvas = Canvas::new();
+        let train = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(1)"),
+        );
+        let evaluate = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(2)"),
+        );
+        let report = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(300.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(input_0 * 10)"),
+        );
+        canvas.create_relationship(evaluate, report).unwrap();
+        canvas.set_entry_point(train, Some("train".to_string())).unwrap();
+        canvas.set_entry_point(evaluate, Some("evaluate".to_string())).unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        assert!(engine.execute(&canvas).is_err());
+        assert!(engine.execute_entry_point(&canvas, "deploy").is_err());
+
+        let result = engine.execute_entry_point(&canvas, "evaluate").unwrap();
+        let ran: Vec<Ulid> = result.log.iter().map(|e| e.cell_id).collect();
+        assert_eq!(ran, vec![evaluate, report]);
+        assert_eq!(result.log[1].output, CellData::Number(20.0));
+
+        let result = engine.execute_entry_point(&canvas, "train").unwrap();
+        assert_eq!(result.total_cells_executed, 1);
+    }
+
+    #[test]
+    fn test_run_parameters() {
+        let mut canvas = Canvas::new();
+        let python = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(f\"{params['start']}:{params['limit']}\")"),
+        );
+        canvas.set_start_point(python).unwrap();
+        let math = canvas.create_cell(
+            CellType::Math,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("limit * 2"),
+        );
+
+        let mut params = crate::Parameters::new();
+        params.set("start", serde_json::json!("2024-01-01")).unwrap();
+        params.set("limit", serde_json::json!(5)).unwrap();
+        params.apply_override("limit=7").unwrap();
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_params(params.resolve().unwrap());
+        let result = engine.execute(&canvas).unwrap();
+        assert_eq!(result.log[0].output, CellData::Text("2024-01-01:7.0".to_string()));
+
+        let cell = canvas.get_cell(math).unwrap();
+        let output = execute_math_cell(&canvas, cell, &engine.params, &HashMap::new()).unwrap();
+        assert_eq!(output, CellData::Number(14.0));
+        assert!(execute_math_cell(&canvas, cell, &BTreeMap::new(), &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_secrets_are_masked() {
+        let mut canvas = Canvas::new();
+        let python = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("print('key', params['token'])\nset_output(len(params['token']))"),
+        );
+        let shell = canvas.create_cell(
+            CellType::Shell,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("echo \"${PARAM_TOKEN:-unset}\""),
+        );
+        let failing = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(300.0, 0.0, 100.0, 100.0),
+            CellContent::inline("raise ValueError('bad token ' + params['token'])"),
+        );
+        canvas.create_relationship(python, shell).unwrap();
+        canvas.create_relationship(shell, failing).unwrap();
+        canvas.set_start_point(python).unwrap();
+
+        let mut params = crate::Parameters::new();
+        params.set_secret("token", "API_TOKEN").unwrap();
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_params(params.resolve_with(|_| Some("s3cret".to_string())).unwrap());
+        engine.set_secrets(params.secrets.keys().cloned(), false);
+
+        // The cell still gets the value; the log and the error don't show it
+        let err = engine.execute(&canvas).unwrap_err();
+        assert!(!format!("{:#}", err).contains("s3cret"));
+        let log = engine.log();
+        assert_eq!(log[0].output, CellData::Number(6.0));
+        assert_eq!(log[0].stdout, "key [redacted]\n");
+        assert_eq!(log[1].output, CellData::Text("unset".to_string()));
+        assert!(log[2].error.as_ref().unwrap().contains("bad token [redacted]"));
+        assert_eq!(log[2].traceback.as_ref().unwrap().message, "bad token [redacted]");
+
+        // Shell cells get secrets only when the project opts in
+        engine.set_secrets(params.secrets.keys().cloned(), true);
+        engine.execute(&canvas).unwrap_err();
+        assert_eq!(engine.log()[1].output, CellData::Text("[redacted]".to_string()));
+    }
+
+    #[test]
+    fn test_error_policies() {
+        let mut canvas = Canvas::new();
+        let code = [
+            "set_output(1)",
+            "raise ValueError('bad data')",
+            "set_output(repr(inputs))",
+            "set_output(2)",
+        ];
+        let ids: Vec<Ulid> = code
+            .iter()
+            .enumerate()
+            .map(|(i, code)| {
+                canvas.create_cell(
+                    CellType::Python,
+                    Rectangle::new(150.0 * i as f32, 0.0, 100.0, 100.0),
+                    CellContent::inline(*code),
+                )
+            })
+            .collect();
+        let (source, bad, below_bad, sibling) = (ids[0], ids[1], ids[2], ids[3]);
+        canvas.create_relationship(source, bad).unwrap();
+        canvas.create_relationship(bad, below_bad).unwrap();
+        canvas.create_relationship(source, sibling).unwrap();
+        canvas.set_start_point(source).unwrap();
+
+        // Fail-fast aborts the run
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        assert!(engine.execute(&canvas).is_err());
+        assert!(engine.log().last().unwrap().error.is_some());
+
+        // Continue runs everything; the failed cell's input slot holds None
+        engine.set_error_policy(ErrorPolicy::Continue);
+        let report = engine.execute(&canvas).unwrap();
+        assert_eq!(report.status, ExecutionStatus::Complete);
+        assert_eq!(report.failed(), vec![bad]);
+        assert!(report.skipped().is_empty());
+        assert_eq!(report.succeeded().len(), 3);
+        assert_eq!(engine.log().last().unwrap().output, CellData::Text("[None]".into()));
+        assert!(matches!(
+            &report.outcomes[&bad],
+            CellOutcome::Failed(message) if message.contains("bad data")
+        ));
+
+        // Skip-downstream skips only the failed cell's descendants
+        engine.set_error_policy(ErrorPolicy::SkipDownstream);
+        let report = engine.execute(&canvas).unwrap();
+        assert_eq!(report.failed(), vec![bad]);
+        assert_eq!(report.skipped(), vec![below_bad]);
+        let mut succeeded = vec![source, sibling];
+        succeeded.sort();
+        assert_eq!(report.succeeded(), succeeded);
+
+        // A cell's own policy overrides the run's
+        engine.set_error_policy(ErrorPolicy::FailFast);
+        canvas.get_cell_mut(bad).unwrap().error_policy = Some(ErrorPolicy::SkipDownstream);
+        let report = engine.execute(&canvas).unwrap();
+        assert_eq!(report.skipped(), vec![below_bad]);
+    }
+
+    #[test]
+    fn test_dry_run_type_inference() {
+        let mut canvas = Canvas::new();
+        let count = canvas.create_cell(
+            CellType::NumberInt,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("42"),
+        );
+        let note = canvas.create_cell(
+            CellType::Text,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("Passes its input through"),
+        );
+        let report = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(300.0, 0.0, 100.0, 100.0),
+            CellContent::inline("input_0: float\nresult: str\nset_output(f'{input_0:.1f}')"),
+        );
+        let shout = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(450.0, 0.0, 100.0, 100.0),
+            CellContent::inline("input_0: str\nset_output(input_0.upper())"),
+        );
+        canvas.create_relationship(count, note).unwrap();
+        canvas.create_relationship(note, report).unwrap();
+        canvas.create_relationship(report, shout).unwrap();
+        canvas.set_start_point(count).unwrap();
+
+        // Number → Text (pass-through) → float input → str output → str input
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let result = engine.execute(&canvas).unwrap();
+        assert!(result.type_mismatches.is_empty());
+        assert_eq!(result.log[2].output, CellData::Text("(dry-run: Text)".to_string()));
+
+        // The report cell now gets text it can't use
+        let to_text = Transform::Coerce(crate::CoerceType::Text);
+        canvas.set_relationship_transform(note, report, Some(to_text)).unwrap();
+        let result = engine.execute(&canvas).unwrap();
+        assert_eq!(result.type_mismatches.len(), 1);
+        let issue = &result.type_mismatches[0];
+        assert_eq!(issue.issue_type, crate::validation::ValidationIssueType::TypeMismatch);
+        assert_eq!(issue.affected_cells, vec![report, note]);
+        assert!(issue.message.contains("expects input_0: Number"));
+
+        // A math transform can't take JSON
+        let to_json = Transform::Coerce(crate::CoerceType::Json);
+        canvas.set_relationship_transform(count, note, Some(to_json)).unwrap();
+        canvas
+            .set_relationship_transform(note, report, Some(Transform::Math("x * 2".to_string())))
+            .unwrap();
+        let result = engine.execute(&canvas).unwrap();
+        assert_eq!(result.type_mismatches.len(), 1);
+        assert!(result.type_mismatches[0].message.contains("can't take Json"));
+    }
+
+    /// Counter -> Echo with Echo -> Counter marked as a loop
+    fn counter_loop(loop_config: LoopConfig) -> (Canvas, Ulid, Ulid) {
+        let mut canvas = Canvas::new();
+        let counter = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output((input_0 if inputs else 0) + 1)"),
+        );
+        let echo = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(input_0)"),
+        );
+        canvas.create_relationship(counter, echo).unwrap();
+        canvas.create_relationship(echo, counter).unwrap();
+        canvas.set_relationship_loop(echo, counter, Some(loop_config)).unwrap();
+        canvas.set_start_point(counter).unwrap();
+        (canvas, counter, echo)
+    }
+
+    #[test]
+    fn test_bounded_loop_exits() {
+        let exit = Guard::Compare {
+            op: CompareOp::Ge,
+            value: "3".to_string(),
+        };
+        let (canvas, counter, echo) = counter_loop(LoopConfig::with_exit(10, exit));
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let report = engine.execute(&canvas).unwrap();
+
+        assert_eq!(report.status, ExecutionStatus::Complete);
+        let iterations: Vec<usize> = report.log.iter().map(|e| e.iteration).collect();
+        assert_eq!(iterations, vec![0, 0, 1, 1, 2, 2]);
+        assert_eq!(report.log[4].output, CellData::Number(3.0));
+        assert_eq!(report.log[5].branches_skipped, vec![counter]);
+        assert_eq!(report.log[1].branches_taken, vec![counter]);
+        assert_eq!(report.log[5].cell_id, echo);
+    }
+
+    #[test]
+    fn test_loop_iteration_limit() {
+        let (canvas, counter, echo) = counter_loop(LoopConfig::new(2));
+
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        let report = engine.execute(&canvas).unwrap();
+
+        assert_eq!(
+            report.status,
+            ExecutionStatus::IterationLimitReached {
+                from: echo,
+                to: counter,
+                max_iterations: 2,
+            }
+        );
+        assert_eq!(report.total_cells_executed, 4);
+
+        // Dry run checks the loop body once without following the back edge
+        let mut engine = ExecutionEngine::new(ExecutionMode::DryRun);
+        let report = engine.execute(&canvas).unwrap();
+        assert_eq!(report.status, ExecutionStatus::DryRunComplete);
+        assert_eq!(report.total_cells_executed, 2);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let (canvas, counter, echo) = counter_loop(LoopConfig::new(5));
+
+        // Pause before Echo on the pass where it receives 2
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_breakpoint(echo, Breakpoint::conditional("input_0 == 2"));
+        let report = engine.execute(&canvas).unwrap();
+
+        assert_eq!(report.status, ExecutionStatus::Paused);
+        assert_eq!(report.paused_at, Some(echo));
+        assert_eq!(report.total_cells_executed, 3);
+        assert_eq!(
+            engine.paused_cell(),
+            Some((echo, &[CellData::Number(2.0)][..]))
+        );
+
+        // Continuing runs the paused cell and stops at the loop limit
+        engine.remove_breakpoint(echo);
+        engine.set_breakpoint(counter, Breakpoint::conditional("x > 100"));
+        let report = engine.continue_execution(&canvas).unwrap();
+        assert!(matches!(report.status, ExecutionStatus::IterationLimitReached { .. }));
+        assert_eq!(report.total_cells_executed, 10);
+        assert_eq!(report.paused_at, None);
+
+        // An unconditional breakpoint pauses on every pass
+        let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+        engine.set_breakpoint(echo, Breakpoint::new());
+        engine.execute(&canvas).unwrap();
+        let report = engine.continue_execution(&canvas).unwrap();
+        assert_eq!(report.paused_at, Some(echo));
+        assert_eq!(report.total_cells_executed, 3);
     }
 
     #[test]
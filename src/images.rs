@@ -0,0 +1,140 @@
+//! Image cells: PNG, JPEG and SVG images, from base64, files or upstream bytes
+
+use crate::CellContent;
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::path::Path;
+
+/// Formats Image cells display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Svg,
+}
+
+impl ImageFormat {
+    /// MIME type, as used in `data:` URIs
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Svg => "image/svg+xml",
+        }
+    }
+
+    /// File extension, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// Detect an image's format from its bytes
+pub fn detect_format(bytes: &[u8]) -> Option<ImageFormat> {
+    match image::guess_format(bytes) {
+        Ok(image::ImageFormat::Png) => Some(ImageFormat::Png),
+        Ok(image::ImageFormat::Jpeg) => Some(ImageFormat::Jpeg),
+        _ => is_svg(bytes).then_some(ImageFormat::Svg),
+    }
+}
+
+/// SVG is markup with an `<svg` element near the start, after any XML declaration,
+/// doctype or comments
+fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    head.starts_with('<') && head.contains("<svg")
+}
+
+/// Check that bytes are an image a cell can display
+pub fn ensure_image(bytes: &[u8]) -> Result<ImageFormat> {
+    detect_format(bytes).ok_or_else(|| anyhow!("Not a PNG, JPEG or SVG image"))
+}
+
+/// Decode inline image text: base64, a base64 `data:` URI, or SVG markup
+pub fn decode_inline(text: &str) -> Result<Vec<u8>> {
+    let text = text.trim();
+    if text.starts_with('<') {
+        return Ok(text.as_bytes().to_vec());
+    }
+    let encoded = match text.strip_prefix("data:") {
+        Some(uri) => uri
+            .split_once(";base64,")
+            .map(|(_, data)| data)
+            .ok_or_else(|| anyhow!("Only base64 data URIs are supported"))?,
+        None => text,
+    };
+    let encoded: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+    STANDARD
+        .decode(encoded)
+        .map_err(|e| anyhow!("Invalid base64 image: {}", e))
+}
+
+/// An Image cell's own image: inline text, or an external file relative to `project_dir`
+pub fn read_image_content(content: &CellContent, project_dir: Option<&Path>) -> Result<Vec<u8>> {
+    match content {
+        CellContent::Inline(text) => decode_inline(text),
+        CellContent::External { path, .. } => {
+            let path = match project_dir {
+                Some(project_dir) => project_dir.join(path),
+                None => path.clone(),
+            };
+            std::fs::read(&path).with_context(|| format!("Failed to read image {}", path.display()))
+        }
+    }
+}
+
+/// `data:` URI embedding an image, for text and documents that inline their images
+pub fn data_uri(bytes: &[u8]) -> Result<String> {
+    let format = ensure_image(bytes)?;
+    Ok(format!("data:{};base64,{}", format.mime_type(), STANDARD.encode(bytes)))
+}
+
+/// Markdown image with the image embedded, so the document needs no side files
+pub fn markdown_image(alt: &str, bytes: &[u8]) -> Result<String> {
+    let alt = alt.replace(['[', ']'], "");
+    Ok(format!("![{}]({})", alt, data_uri(bytes)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(PNG_HEADER), Some(ImageFormat::Png));
+        assert_eq!(detect_format(b"\xff\xd8\xff\xe0\0\x10JFIF"), Some(ImageFormat::Jpeg));
+        let svg = "\u{feff}<?xml version=\"1.0\"?>\n<!-- chart -->\n<svg width=\"10\"></svg>";
+        assert_eq!(detect_format(svg.as_bytes()), Some(ImageFormat::Svg));
+        assert_eq!(detect_format(b"<html></html>"), None);
+        assert_eq!(detect_format(b"GIF89a"), None);
+    }
+
+    #[test]
+    fn test_inline_images() {
+        let encoded = STANDARD.encode(PNG_HEADER);
+        assert_eq!(decode_inline(&encoded).unwrap(), PNG_HEADER);
+
+        let uri = data_uri(PNG_HEADER).unwrap();
+        assert_eq!(uri, format!("data:image/png;base64,{}", encoded));
+        assert_eq!(decode_inline(&uri).unwrap(), PNG_HEADER);
+
+        assert_eq!(decode_inline(" <svg/> ").unwrap(), b"<svg/>");
+        assert!(decode_inline("data:image/svg+xml,<svg/>").is_err());
+        assert!(decode_inline("not base64!").is_err());
+        assert!(data_uri(b"plain").is_err());
+
+        assert_eq!(
+            markdown_image("Sales [Q1]", PNG_HEADER).unwrap(),
+            format!("![Sales Q1](data:image/png;base64,{})", encoded)
+        );
+        assert!(markdown_image("notes", b"plain").is_err());
+    }
+}
@@ -2,23 +2,46 @@
 
 pub mod canvas;
 pub mod cell;
+pub mod chart;
+pub mod dates;
 pub mod event;
 pub mod execution;
 pub mod id_generator;
+pub mod images;
 pub mod markdown_links;
 pub mod math_eval;
+pub mod params;
+pub mod python_env;
 pub mod relationship;
+pub mod schema;
+pub mod script;
 pub mod serialization;
+pub mod shell;
+pub mod sql;
+pub mod table;
+pub mod transform;
 pub mod ui;
 pub mod validation;
+pub mod wasm;
 
 // Re-export main types for convenience
 pub use canvas::{Canvas, SnapGuide};
 pub use cell::{Cell, CellContent, CellType, MarkdownPreviewMode, Rectangle};
+pub use chart::{Chart, ChartKind, ChartSpec};
 pub use event::{EventType, GraphEvent, SplitDirection};
-pub use execution::{CellData, ExecutionEngine, ExecutionMode, ExecutionReport, ExecutionStatus};
+pub use execution::{
+    Breakpoint, CellData, CellOutcome, DataType, ErrorPolicy, ExecutionEngine, ExecutionLogEntry,
+    ExecutionMode, ExecutionReport, ExecutionStatus, PythonTraceback,
+};
 pub use id_generator::IdGenerator;
-pub use relationship::Relationship;
+pub use params::Parameters;
+pub use python_env::{EnvironmentReport, PythonEnvironment};
+pub use relationship::{LoopConfig, Relationship};
+pub use schema::SchemaViolation;
+pub use script::ScriptLimits;
 pub use serialization::{ExternalFileHandle, Manifest, Project};
+pub use table::{Column, ColumnType, ColumnValues, Table};
+pub use transform::{CoerceType, CompareOp, Guard, Transform};
 pub use ui::GraphCellEditorApp;
 pub use validation::{ValidatedCanvas, ValidationIssue, ValidationResult, ValidationSeverity};
+pub use wasm::WasmSandbox;
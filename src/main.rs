@@ -1,6 +1,214 @@
-use graph_cell_editor::{Canvas, CellContent, CellType, Project, Rectangle, SplitDirection};
+use graph_cell_editor::{
+    chart::{render_svg, DEFAULT_SVG_SIZE},
+    validation::Validator,
+    Canvas, CellContent, CellData, CellType, Chart, ErrorPolicy, ExecutionEngine,
+    ExecutionLogEntry, ExecutionMode, Project, Rectangle, SplitDirection,
+};
+use std::path::Path;
+
+const USAGE: &str = "Usage:
+  graph_cell_editor                        Run the demo
+  graph_cell_editor entries <project-dir>  List entry points
+  graph_cell_editor run <project-dir> [--entry NAME] [--param KEY=VALUE]... [--svg DIR]
+                                   [--markdown FILE]
+                                   [--error-policy fail-fast|continue|skip-downstream]
+                                           Run from the start point or an entry point,
+                                           overriding project parameters; --svg writes
+                                           each chart to DIR/<cell>.svg; --markdown
+                                           writes the run's outputs to FILE";
+
+/// Options accepted by `run`
+#[derive(Default)]
+struct RunOptions<'a> {
+    entry: Option<&'a str>,
+    params: Vec<&'a str>,
+    svg_dir: Option<&'a str>,
+    markdown: Option<&'a str>,
+    error_policy: Option<ErrorPolicy>,
+}
+
+impl<'a> RunOptions<'a> {
+    /// Parse the arguments after the project directory
+    fn parse(args: &[&'a str]) -> Option<Self> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match *arg {
+                "--entry" if options.entry.is_none() => options.entry = Some(args.next()?),
+                "--param" => options.params.push(args.next()?),
+                "--svg" if options.svg_dir.is_none() => options.svg_dir = Some(args.next()?),
+                "--markdown" if options.markdown.is_none() => {
+                    options.markdown = Some(args.next()?)
+                }
+                "--error-policy" if options.error_policy.is_none() => {
+                    options.error_policy = Some(parse_error_policy(args.next()?)?)
+                }
+                _ => return None,
+            }
+        }
+        Some(options)
+    }
+}
+
+/// Read an error policy written as its label in kebab case ("skip-downstream")
+fn parse_error_policy(name: &str) -> Option<ErrorPolicy> {
+    ErrorPolicy::ALL
+        .into_iter()
+        .find(|policy| policy.label().to_lowercase().replace(' ', "-") == name)
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [] => {
+            run_demo();
+            Ok(())
+        }
+        ["entries", project_dir] => list_entry_points(Path::new(project_dir)),
+        ["run", project_dir, options @ ..] => match RunOptions::parse(options) {
+            Some(options) => run_project(Path::new(project_dir), options),
+            None => usage(),
+        },
+        _ => usage(),
+    };
+
+    if let Err(e) = result {
+        eprintln!("❌ {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Print usage and exit
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+/// Print the entry points of a project
+fn list_entry_points(project_dir: &Path) -> anyhow::Result<()> {
+    let (_manifest, canvas) = Project::open(project_dir)?.load()?;
+
+    if let Some(start) = canvas.get_start_point() {
+        println!("(start point) → {}", start.label());
+    }
+    for (name, cell_id) in canvas.entry_points() {
+        let label = canvas.get_cell(cell_id).map(|c| c.label()).unwrap_or_default();
+        println!("{} → {}", name, label);
+    }
+
+    Ok(())
+}
+
+/// Execute a project from its start point or a named entry point
+fn run_project(project_dir: &Path, options: RunOptions) -> anyhow::Result<()> {
+    let (manifest, canvas) = Project::open(project_dir)?.load()?;
+
+    let mut engine = ExecutionEngine::new(ExecutionMode::Run);
+    engine.set_project_dir(Some(project_dir.to_path_buf()));
+    engine.set_sql_database(manifest.sql_database.clone());
+    engine.set_wasm_sandbox(manifest.wasm);
+    if let Some(environment) = &manifest.python {
+        let issues = Validator::check_python_environment(&canvas, environment, project_dir);
+        if !issues.is_empty() {
+            let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+            anyhow::bail!("Python environment check failed:\n  {}", messages.join("\n  "));
+        }
+        engine.set_python_interpreter(Some(environment.executable(project_dir)));
+    }
+
+    let mut params = manifest.params;
+    for assignment in options.params {
+        params.apply_override(assignment)?;
+    }
+    engine.set_params(params.resolve()?);
+    engine.set_secrets(params.secrets.keys().cloned(), params.shell_secrets);
+    if let Some(policy) = options.error_policy {
+        engine.set_error_policy(policy);
+    }
+    let result = match options.entry {
+        Some(name) => engine.execute_entry_point(&canvas, name),
+        None => engine.execute(&canvas),
+    };
+    // Under fail-fast the run stops with an error; still show what ran before it
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            print_log(&canvas, engine.log());
+            return Err(e);
+        }
+    };
+
+    print_log(&canvas, &report.log);
+    let label = |id| canvas.get_cell(id).map(|c| c.label()).unwrap_or_default();
+    for cell_id in report.skipped() {
+        println!("- {} (skipped: an upstream cell failed)", label(cell_id));
+    }
+    println!(
+        "{:?}: {} succeeded, {} failed, {} skipped in {} steps",
+        report.status,
+        report.succeeded().len(),
+        report.failed().len(),
+        report.skipped().len(),
+        report.step
+    );
+
+    if let Some(dir) = options.svg_dir {
+        export_charts(&canvas, &report.log, Path::new(dir))?;
+    }
+    if let Some(path) = options.markdown {
+        std::fs::write(path, report.to_markdown(&canvas))?;
+        println!("📝 {}", path);
+    }
+
+    Ok(())
+}
+
+/// Print each executed cell with its output, or its error if it failed, followed by
+/// what it printed and its Python traceback
+fn print_log(canvas: &Canvas, log: &[ExecutionLogEntry]) {
+    for entry in log {
+        let label = canvas.get_cell(entry.cell_id).map(|c| c.label()).unwrap_or_default();
+        match &entry.error {
+            Some(error) => println!("✗ {} (step {}): {}", label, entry.step, error),
+            None => println!("✓ {} (step {}): {}", label, entry.step, entry.output.summary()),
+        }
+        for line in entry.stdout.lines() {
+            println!("  │ {}", line);
+        }
+        for line in entry.stderr.lines() {
+            println!("  ! {}", line);
+        }
+        if let Some(traceback) = &entry.traceback {
+            for line in traceback.to_string().lines() {
+                println!("  ‖ {}", line);
+            }
+        }
+    }
+}
+
+/// Write each executed Chart cell's latest chart to `<dir>/<short id>.svg`
+fn export_charts(canvas: &Canvas, log: &[ExecutionLogEntry], dir: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let mut charts = std::collections::BTreeMap::new();
+    for entry in log {
+        let Some(cell) = canvas.get_cell(entry.cell_id) else {
+            continue;
+        };
+        if let (CellType::Chart, CellData::Json(value)) = (cell.cell_type, &entry.output) {
+            charts.insert(cell.short_id.clone(), value);
+        }
+    }
+    for (short_id, value) in charts {
+        let chart: Chart = serde_json::from_value(value.clone())?;
+        let path = dir.join(format!("{}.svg", short_id));
+        std::fs::write(&path, render_svg(&chart, DEFAULT_SVG_SIZE))?;
+        println!("📈 {}", path.display());
+    }
+    Ok(())
+}
+
+/// Walk through the core data model and serialization
+fn run_demo() {
     println!("Graph Cell Editor - Phase 1 & 2 Demo");
     println!("=====================================\n");
 
@@ -1,6 +1,8 @@
-use crate::{Canvas, Cell, CellType};
+use crate::dates::{date_to_day_number, day_number_to_date};
+use crate::{Canvas, Cell, CellData, CellType, ColumnValues};
+use chrono::{Datelike, NaiveDate};
 use evalexpr::*;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use ulid::Ulid;
 
 /// Parse a formula to extract cell references in [[cell_id]] format
@@ -28,6 +30,13 @@ pub fn resolve_cell_value(cell: &Cell, _canvas: &Canvas) -> Result<f64, String>
                 Err(format!("Cell {} has no inline content", cell.short_id))
             }
         }
+        // Dates take part as day numbers
+        CellType::Date => crate::dates::cell_datetime(cell)
+            .and_then(|value| {
+                let tz = crate::dates::parse_time_zone(&cell.time_zone)?;
+                Ok(crate::dates::to_day_number(value, tz))
+            })
+            .map_err(|e| format!("Date cell {}: {}", cell.short_id, e)),
         CellType::Math => {
             // Math cells should have computed_result set by execution
             Err(format!(
@@ -43,8 +52,17 @@ pub fn resolve_cell_value(cell: &Cell, _canvas: &Canvas) -> Result<f64, String>
 }
 
 /// Build an evalexpr context with cell references resolved to their values
-pub fn build_eval_context(formula: &str, canvas: &Canvas) -> Result<HashMapContext, String> {
+///
+/// A referenced cell whose output in `outputs` is a table contributes one tuple per column
+/// instead, named `cell_<id>.<column>`, holding the column's non-null values.
+pub fn build_eval_context(
+    formula: &str,
+    canvas: &Canvas,
+    outputs: &HashMap<Ulid, CellData>,
+) -> Result<HashMapContext, String> {
     let mut context = HashMapContext::new();
+    set_aggregate_functions(&mut context)?;
+    set_date_functions(&mut context)?;
 
     // Extract cell references
     let references = parse_formula_references(formula);
@@ -60,8 +78,34 @@ pub fn build_eval_context(formula: &str, canvas: &Canvas) -> Result<HashMapConte
             .get_cell(cell_id)
             .ok_or_else(|| format!("Cell {} not found", ref_id))?;
 
-        // Resolve the cell's value
-        let value = resolve_cell_value(cell, canvas)?;
+        if let Some(CellData::Table(table)) = outputs.get(&cell_id) {
+            for column in table.columns() {
+                let values: TupleType = match &column.values {
+                    ColumnValues::Text(values) => {
+                        values.iter().flatten().map(|s| Value::String(s.clone())).collect()
+                    }
+                    ColumnValues::Number(values) => {
+                        values.iter().flatten().map(|n| Value::Float(*n)).collect()
+                    }
+                    ColumnValues::Boolean(values) => {
+                        values.iter().flatten().map(|b| Value::Boolean(*b)).collect()
+                    }
+                };
+                let var_name = format!("cell_{}.{}", ref_id, column.name);
+                context
+                    .set_value(var_name.clone(), Value::Tuple(values))
+                    .map_err(|e| format!("Failed to set variable {}: {}", var_name, e))?;
+            }
+            continue;
+        }
+
+        // Resolve the cell's value; a date-time output is a day number in the cell's zone
+        let value = match outputs.get(&cell_id) {
+            Some(CellData::DateTime(value)) => crate::dates::parse_time_zone(&cell.time_zone)
+                .map(|tz| crate::dates::to_day_number(*value, tz))
+                .map_err(|e| e.to_string())?,
+            _ => resolve_cell_value(cell, canvas)?,
+        };
 
         // Add to context - use "cell_" prefix to avoid numeric literal interpretation
         let var_name = format!("cell_{}", ref_id);
@@ -73,6 +117,87 @@ pub fn build_eval_context(formula: &str, canvas: &Canvas) -> Result<HashMapConte
     Ok(context)
 }
 
+/// Add `sum`, `avg` and `count` over a tuple (a table column) or a single value;
+/// evalexpr's own `min` and `max` accept a column too
+fn set_aggregate_functions(context: &mut HashMapContext) -> Result<(), String> {
+    fn numbers(argument: &Value) -> Result<Vec<f64>, EvalexprError> {
+        match argument {
+            Value::Tuple(values) => values.iter().map(Value::as_number).collect(),
+            value => Ok(vec![value.as_number()?]),
+        }
+    }
+
+    let sum = Function::new(|argument| Ok(Value::Float(numbers(argument)?.iter().sum())));
+    let avg = Function::new(|argument| {
+        let values = numbers(argument)?;
+        if values.is_empty() {
+            return Err(EvalexprError::CustomMessage("avg of no values".to_string()));
+        }
+        Ok(Value::Float(values.iter().sum::<f64>() / values.len() as f64))
+    });
+    let count = Function::new(|argument| {
+        Ok(Value::Int(match argument {
+            Value::Tuple(values) => values.len() as IntType,
+            Value::Empty => 0,
+            _ => 1,
+        }))
+    });
+    for (name, function) in [("sum", sum), ("avg", avg), ("count", count)] {
+        context
+            .set_function(name.to_string(), function)
+            .map_err(|e| format!("Failed to set function {}: {}", name, e))?;
+    }
+    Ok(())
+}
+
+/// Add `days_between(a, b)`, `date(year, month, day)`, and `year`, `month` and `day` of a
+/// date; dates are day numbers (days since 1970-01-01)
+fn set_date_functions(context: &mut HashMapContext) -> Result<(), String> {
+    fn date_of(argument: &Value) -> Result<NaiveDate, EvalexprError> {
+        let days = argument.as_number()?;
+        day_number_to_date(days)
+            .ok_or_else(|| EvalexprError::CustomMessage(format!("{} is not a date", days)))
+    }
+
+    let days_between = Function::new(|argument| {
+        let dates = argument.as_fixed_len_tuple(2)?;
+        Ok(Value::Float(dates[1].as_number()? - dates[0].as_number()?))
+    });
+    // Year, month and day must be whole and not negative, rather than truncated
+    fn date_part(part: &Value) -> Result<u32, EvalexprError> {
+        let number = part.as_number()?;
+        if number.fract() != 0.0 || !(0.0..=u32::MAX as f64).contains(&number) {
+            return Err(EvalexprError::CustomMessage(format!(
+                "{} is not a whole, non-negative date part",
+                number
+            )));
+        }
+        Ok(number as u32)
+    }
+    let date = Function::new(|argument| {
+        let parts = argument.as_fixed_len_tuple(3)?;
+        let [year, month, day] = [&parts[0], &parts[1], &parts[2]].map(date_part);
+        NaiveDate::from_ymd_opt(i32::try_from(year?).unwrap_or(i32::MAX), month?, day?)
+            .map(|date| Value::Float(date_to_day_number(date)))
+            .ok_or_else(|| EvalexprError::CustomMessage("No such date".to_string()))
+    });
+    let year = Function::new(|argument| Ok(Value::Int(date_of(argument)?.year() as IntType)));
+    let month = Function::new(|argument| Ok(Value::Int(date_of(argument)?.month() as IntType)));
+    let day = Function::new(|argument| Ok(Value::Int(date_of(argument)?.day() as IntType)));
+    for (name, function) in [
+        ("days_between", days_between),
+        ("date", date),
+        ("year", year),
+        ("month", month),
+        ("day", day),
+    ] {
+        context
+            .set_function(name.to_string(), function)
+            .map_err(|e| format!("Failed to set function {}: {}", name, e))?;
+    }
+    Ok(())
+}
+
 /// Replace [[cell_id]] references in formula with safe variable names for evalexpr
 pub fn prepare_formula(formula: &str) -> String {
     // Replace [[cell_id]] with cell_cell_id to avoid numeric literal interpretation
@@ -105,8 +230,41 @@ pub fn prepare_formula(formula: &str) -> String {
 
 /// Evaluate a mathematical expression with cell references
 pub fn evaluate_expression(formula: &str, canvas: &Canvas) -> Result<f64, String> {
+    evaluate_expression_with_params(formula, canvas, &BTreeMap::new())
+}
+
+/// Evaluate a mathematical expression with cell references and named run parameters
+pub fn evaluate_expression_with_params(
+    formula: &str,
+    canvas: &Canvas,
+    params: &BTreeMap<String, CellData>,
+) -> Result<f64, String> {
+    evaluate_expression_with_data(formula, canvas, params, &HashMap::new())
+}
+
+/// Evaluate a mathematical expression with cell references, run parameters and the
+/// outputs of executed cells (tables referenced as `[[id]].column`)
+pub fn evaluate_expression_with_data(
+    formula: &str,
+    canvas: &Canvas,
+    params: &BTreeMap<String, CellData>,
+    outputs: &HashMap<Ulid, CellData>,
+) -> Result<f64, String> {
     // Build context with cell values
-    let context = build_eval_context(formula, canvas)?;
+    let mut context = build_eval_context(formula, canvas, outputs)?;
+
+    // Parameters are plain variables; only scalar values can take part in a formula
+    for (name, value) in params {
+        let value = match value {
+            CellData::Number(n) => Value::Float(*n),
+            CellData::Boolean(b) => Value::Boolean(*b),
+            CellData::Text(s) => Value::String(s.clone()),
+            _ => continue,
+        };
+        context
+            .set_value(name.clone(), value)
+            .map_err(|e| format!("Failed to set parameter {}: {}", name, e))?;
+    }
 
     // Prepare formula by removing [[ ]] markers
     let prepared_formula = prepare_formula(formula);
@@ -643,4 +801,80 @@ mod tests {
         let result = evaluate_expression(&formula, &canvas).unwrap();
         assert_eq!(result, 25.0); // 10 * 2 + 5 = 25
     }
+
+    #[test]
+    fn test_table_aggregates() {
+        let mut canvas = Canvas::new();
+        let cell = canvas.create_cell(
+            CellType::Text,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("scores"),
+        );
+        let short_id = canvas.get_cell(cell).unwrap().short_id.clone();
+        let table = crate::Table::from_json_rows(&serde_json::json!([
+            {"name": "a", "score": 4},
+            {"name": "b", "score": null},
+            {"name": "c", "score": 8},
+        ]))
+        .unwrap();
+        let outputs = HashMap::from([(cell, CellData::Table(table))]);
+        let eval = |formula: &str| {
+            let formula = formula.replace("T", &format!("[[{}]]", short_id));
+            evaluate_expression_with_data(&formula, &canvas, &BTreeMap::new(), &outputs)
+        };
+
+        assert_eq!(eval("sum(T.score)").unwrap(), 12.0);
+        assert_eq!(eval("avg(T.score)").unwrap(), 6.0);
+        assert_eq!(eval("count(T.score) + count(T.name)").unwrap(), 5.0);
+        assert_eq!(eval("max(T.score) - min(T.score)").unwrap(), 4.0);
+        assert!(eval("sum(T.name)").is_err());
+        assert!(eval("sum(T.missing)").is_err());
+    }
+
+    #[test]
+    fn test_date_arithmetic() {
+        let mut canvas = Canvas::new();
+        let start = canvas.create_cell(
+            CellType::Date,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("15/01/2024 18:00"),
+        );
+        let end = canvas.create_cell(
+            CellType::Date,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("2024-03-01"),
+        );
+        let cell = canvas.get_cell_mut(start).unwrap();
+        cell.date_format = "%d/%m/%Y %H:%M".to_string();
+        cell.time_zone = "America/New_York".to_string();
+        let ids = [start, end].map(|id| canvas.get_cell(id).unwrap().short_id.clone());
+        let eval = |formula: &str| {
+            let formula = formula.replace("S", &format!("[[{}]]", ids[0]));
+            let formula = formula.replace("E", &format!("[[{}]]", ids[1]));
+            evaluate_expression(&formula, &canvas)
+        };
+
+        // Each date is read in its own zone; 18:00 is three quarters of the day
+        assert_eq!(eval("E - S").unwrap(), 45.25);
+        assert_eq!(eval("days_between(S, E)").unwrap(), 45.25);
+        assert_eq!(eval("E - date(2024, 1, 1)").unwrap(), 60.0);
+        assert_eq!(eval("month(E + 30) * 100 + day(E + 30)").unwrap(), 331.0);
+        assert_eq!(eval("year(S)").unwrap(), 2024.0);
+        assert!(eval("date(2024, 2, 30)").is_err());
+        assert!(eval("date(2024, 1.5, 1)").is_err());
+        assert!(eval("date(2024, 1, -1)").is_err());
+        assert!(eval("date(-2024, 1, 1)").is_err());
+
+        // A date-time output counts instead of the content
+        let output = crate::dates::parse_datetime(
+            "2024-03-02",
+            crate::dates::DEFAULT_DATE_FORMAT,
+            chrono_tz::Tz::UTC,
+        )
+        .unwrap();
+        let outputs = HashMap::from([(end, CellData::DateTime(output))]);
+        let formula = format!("[[{}]] - date(2024, 1, 1)", ids[1]);
+        let days = evaluate_expression_with_data(&formula, &canvas, &BTreeMap::new(), &outputs);
+        assert_eq!(days.unwrap(), 61.0);
+    }
 }
@@ -0,0 +1,219 @@
+//! Project parameters and secrets for parameterized runs
+
+use crate::CellData;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What secret values are replaced with in logs and reports
+pub const REDACTED: &str = "[redacted]";
+
+/// Named values a run can be parameterized with
+///
+/// Parameters are stored in the manifest. Secrets only record the environment
+/// variable their value is read from, so the value never ends up in the project.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Parameters {
+    /// Parameter name → default value
+    #[serde(default)]
+    pub parameters: BTreeMap<String, serde_json::Value>,
+    /// Secret name → environment variable holding its value
+    #[serde(default)]
+    pub secrets: BTreeMap<String, String>,
+    /// Also pass secrets to Shell cells as `PARAM_<NAME>` variables; off by default, since
+    /// child processes can leak their environment
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shell_secrets: bool,
+}
+
+impl Parameters {
+    /// Create an empty parameter set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a parameter's value
+    pub fn set(&mut self, name: &str, value: serde_json::Value) -> Result<()> {
+        check_name(name)?;
+        if self.secrets.contains_key(name) {
+            return Err(anyhow!("'{}' is already a secret", name));
+        }
+        self.parameters.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Declare a secret whose value is read from an environment variable
+    pub fn set_secret(&mut self, name: &str, env_var: &str) -> Result<()> {
+        check_name(name)?;
+        if self.parameters.contains_key(name) {
+            return Err(anyhow!("'{}' is already a parameter", name));
+        }
+        self.secrets.insert(name.to_string(), env_var.to_string());
+        Ok(())
+    }
+
+    /// Remove a parameter or secret
+    pub fn remove(&mut self, name: &str) {
+        self.parameters.remove(name);
+        self.secrets.remove(name);
+    }
+
+    /// Apply a `key=value` override, e.g. from `--param`
+    ///
+    /// The value is parsed as JSON when possible (`10`, `true`, `[1, 2]`) and
+    /// taken as text otherwise, so `--param start=2024-01-01` needs no quoting.
+    pub fn apply_override(&mut self, assignment: &str) -> Result<()> {
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected key=value, got '{}'", assignment))?;
+        let name = name.trim();
+        if let Some(env_var) = self.secrets.get(name) {
+            return Err(anyhow!(
+                "'{}' is a secret; set the environment variable {} instead",
+                name,
+                env_var
+            ));
+        }
+        let value = serde_json::from_str(value)
+            .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+        self.set(name, value)
+    }
+
+    /// Resolve every parameter and secret to the value cells see, reading secrets from
+    /// the process environment
+    pub fn resolve(&self) -> Result<BTreeMap<String, CellData>> {
+        self.resolve_with(|env_var| std::env::var(env_var).ok())
+    }
+
+    /// Resolve every parameter and secret, reading secrets with `lookup`
+    pub fn resolve_with(
+        &self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<BTreeMap<String, CellData>> {
+        let mut values = BTreeMap::new();
+        for (name, value) in &self.parameters {
+            check_name(name)?;
+            values.insert(name.clone(), crate::transform::json_to_celldata(value.clone()));
+        }
+        for (name, env_var) in &self.secrets {
+            check_name(name)?;
+            let value = lookup(env_var).with_context(|| {
+                format!("Secret '{}' needs environment variable {}", name, env_var)
+            })?;
+            values.insert(name.clone(), CellData::Text(value));
+        }
+        Ok(values)
+    }
+}
+
+/// Replace every occurrence of a secret value in text
+pub fn redact(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED))
+}
+
+/// Replace secret values in the text of a value (Text, strings inside JSON and text
+/// columns of tables)
+pub fn redact_data(data: &CellData, secrets: &[String]) -> CellData {
+    fn redact_json(value: &serde_json::Value, secrets: &[String]) -> serde_json::Value {
+        match value {
+            serde_json::Value::String(s) => serde_json::Value::String(redact(s, secrets)),
+            serde_json::Value::Array(items) => {
+                items.iter().map(|item| redact_json(item, secrets)).collect()
+            }
+            serde_json::Value::Object(map) => map
+                .iter()
+                .map(|(key, item)| (key.clone(), redact_json(item, secrets)))
+                .collect(),
+            other => other.clone(),
+        }
+    }
+
+    match data {
+        CellData::Text(text) => CellData::Text(redact(text, secrets)),
+        CellData::Json(value) => CellData::Json(redact_json(value, secrets)),
+        CellData::Table(table) => CellData::Table(table.map_text(|text| redact(text, secrets))),
+        other => other.clone(),
+    }
+}
+
+/// Parameter names become Python keys and formula variables, so they must be identifiers
+fn check_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid parameter name '{}'", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::Table;
+
+    #[test]
+    fn test_overrides() {
+        let mut params = Parameters::new();
+        params.set("limit", json!(10)).unwrap();
+
+        params.apply_override("limit=25").unwrap();
+        params.apply_override("start=2024-01-01").unwrap();
+        params.apply_override("verbose=true").unwrap();
+
+        let values = params.resolve().unwrap();
+        assert_eq!(values["limit"], CellData::Number(25.0));
+        assert_eq!(values["start"], CellData::Text("2024-01-01".to_string()));
+        assert_eq!(values["verbose"], CellData::Boolean(true));
+
+        assert!(params.apply_override("limit").is_err());
+        assert!(params.apply_override("2x=1").is_err());
+        assert!(params.apply_override("cell-1=1").is_err());
+    }
+
+    #[test]
+    fn test_secrets_come_from_environment() {
+        let mut params = Parameters::new();
+        params.set_secret("token", "GCE_TEST_PARAMS_TOKEN").unwrap();
+
+        // Secrets can't be overridden on the command line or shadowed by parameters
+        assert!(params.apply_override("token=abc").is_err());
+        assert!(params.set("token", json!("abc")).is_err());
+
+        assert!(params.resolve_with(|_| None).is_err());
+
+        let values = params
+            .resolve_with(|env_var| (env_var == "GCE_TEST_PARAMS_TOKEN").then(|| "s3cret".into()))
+            .unwrap();
+        assert_eq!(values["token"], CellData::Text("s3cret".to_string()));
+
+        // Only the variable name is serialized
+        let json = serde_json::to_string(&params).unwrap();
+        assert!(json.contains("GCE_TEST_PARAMS_TOKEN"));
+        assert!(!json.contains("s3cret"));
+        assert!(!json.contains("shell_secrets"));
+    }
+
+    #[test]
+    fn test_redaction() {
+        let secrets = vec!["s3cret".to_string(), String::new()];
+        assert_eq!(redact("token=s3cret;", &secrets), "token=[redacted];");
+        assert_eq!(
+            redact_data(&CellData::Json(json!({"auth": ["s3cret"], "n": 1})), &secrets),
+            CellData::Json(json!({"auth": ["[redacted]"], "n": 1}))
+        );
+        assert_eq!(redact_data(&CellData::Number(1.0), &secrets), CellData::Number(1.0));
+
+        let rows = json!([{"user": "ann", "token": "s3cret"}, {"user": "bob", "token": null}]);
+        let table = Table::from_json_rows(&rows).unwrap();
+        let CellData::Table(redacted) = redact_data(&CellData::Table(table), &secrets) else {
+            panic!("expected a table");
+        };
+        assert_eq!(redacted.to_csv(), "token,user\n[redacted],ann\n,bob");
+    }
+}
@@ -0,0 +1,199 @@
+//! Per-project Python interpreter, run as a subprocess
+
+use crate::shell::{read_in_background, wait_with_timeout};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Seconds a Python cell may run in a subprocess interpreter when it doesn't set its own
+pub const DEFAULT_PYTHON_TIMEOUT_SECS: u64 = 300;
+
+/// Reports which requirements and modules an interpreter lacks
+///
+/// Reads `{"requirements": [[spec, name], ...], "modules": [...]}` from stdin.
+const CHECK_CODE: &str = r#"
+import importlib.metadata, importlib.util, json, sys
+
+def installed(name):
+    try:
+        importlib.metadata.distribution(name)
+        return True
+    except importlib.metadata.PackageNotFoundError:
+        return False
+
+def importable(name):
+    try:
+        return importlib.util.find_spec(name) is not None
+    except (ImportError, ValueError):
+        return False
+
+request = json.load(sys.stdin)
+json.dump({
+    'missing_requirements': [spec for spec, name in request['requirements'] if not installed(name)],
+    'missing_modules': [name for name in request['modules'] if not importable(name)],
+}, sys.stdout)
+"#;
+
+/// Python interpreter or virtual environment a project's cells run in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PythonEnvironment {
+    /// Interpreter executable or virtual environment directory; relative paths are
+    /// resolved against the project directory, bare names are looked up on PATH
+    pub interpreter: PathBuf,
+    /// Packages the project needs, in pip requirement syntax (e.g. "pandas>=2.0")
+    #[serde(default)]
+    pub requirements: Vec<String>,
+}
+
+/// Requirements and modules an environment lacks
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct EnvironmentReport {
+    pub missing_requirements: Vec<String>,
+    pub missing_modules: Vec<String>,
+}
+
+impl PythonEnvironment {
+    /// Use the given interpreter or virtual environment
+    pub fn new(interpreter: impl Into<PathBuf>) -> Self {
+        Self {
+            interpreter: interpreter.into(),
+            requirements: Vec::new(),
+        }
+    }
+
+    /// Interpreter executable for a project rooted at `project_root`
+    pub fn executable(&self, project_root: &Path) -> PathBuf {
+        let is_bare_name = self.interpreter.components().count() == 1
+            && !self.interpreter.is_absolute()
+            && !project_root.join(&self.interpreter).exists();
+        if is_bare_name {
+            return self.interpreter.clone();
+        }
+
+        let path = project_root.join(&self.interpreter);
+        if path.is_dir() {
+            if cfg!(windows) {
+                path.join("Scripts").join("python.exe")
+            } else {
+                path.join("bin").join("python")
+            }
+        } else {
+            path
+        }
+    }
+
+    /// Check that the interpreter runs and has the requirements and modules installed
+    ///
+    /// Errors if the interpreter can't be started at all.
+    pub fn check(&self, project_root: &Path, modules: &[String]) -> Result<EnvironmentReport> {
+        let requirements: Vec<(&str, &str)> = self
+            .requirements
+            .iter()
+            .filter_map(|spec| Some((spec.as_str(), requirement_name(spec)?)))
+            .collect();
+        let request = serde_json::json!({ "requirements": requirements, "modules": modules });
+
+        let timeout = Duration::from_secs(DEFAULT_PYTHON_TIMEOUT_SECS);
+        let response = run_python(&self.executable(project_root), CHECK_CODE, &request, timeout)?;
+        serde_json::from_value(response).context("Unexpected response from Python environment check")
+    }
+}
+
+/// Distribution name of a pip requirement ("pandas[excel]>=2.0" → "pandas")
+fn requirement_name(spec: &str) -> Option<&str> {
+    let spec = spec.trim();
+    let end = spec
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')))
+        .unwrap_or(spec.len());
+    Some(&spec[..end]).filter(|name| !name.is_empty() && !spec.starts_with('#'))
+}
+
+/// Run a Python script in a subprocess, sending it JSON on stdin and reading JSON back;
+/// the interpreter is killed if it runs past `timeout`
+pub(crate) fn run_python(
+    executable: &Path,
+    script: &str,
+    request: &serde_json::Value,
+    timeout: Duration,
+) -> Result<serde_json::Value> {
+    let mut child = Command::new(executable)
+        .arg("-c")
+        .arg(script)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start Python interpreter {}", executable.display()))?;
+
+    // The scripts read all of stdin before writing anything, so this can't deadlock
+    let stdout = read_in_background(child.stdout.take().expect("stdout is piped"));
+    let stderr = read_in_background(child.stderr.take().expect("stderr is piped"));
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    serde_json::to_writer(&mut stdin, request)?;
+    stdin.flush()?;
+    drop(stdin);
+
+    let status = wait_with_timeout(&mut child, timeout)
+        .map_err(|e| anyhow!("Python interpreter {}: {}", executable.display(), e))?;
+    let stdout = stdout.join().unwrap_or_default();
+    if !status.success() {
+        return Err(anyhow!(
+            "Python interpreter {} failed: {}",
+            executable.display(),
+            stderr.join().unwrap_or_default().trim()
+        ));
+    }
+    serde_json::from_str(&stdout).with_context(|| {
+        format!("Unexpected output from Python interpreter {}", executable.display())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_executable_resolution() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir(root.join(".venv")).unwrap();
+
+        let venv = PythonEnvironment::new(".venv");
+        let expected = if cfg!(windows) { "Scripts/python.exe" } else { "bin/python" };
+        assert_eq!(venv.executable(root), root.join(".venv").join(expected));
+
+        let on_path = PythonEnvironment::new("python3");
+        assert_eq!(on_path.executable(root), PathBuf::from("python3"));
+
+        let absolute = PythonEnvironment::new("/opt/python/bin/python3");
+        assert_eq!(absolute.executable(root), PathBuf::from("/opt/python/bin/python3"));
+    }
+
+    #[test]
+    fn test_requirement_names() {
+        assert_eq!(requirement_name("pandas"), Some("pandas"));
+        assert_eq!(requirement_name("  scikit-learn>=1.3 "), Some("scikit-learn"));
+        assert_eq!(requirement_name("pandas[excel]==2.0"), Some("pandas"));
+        assert_eq!(requirement_name("# comment"), None);
+        assert_eq!(requirement_name(""), None);
+    }
+
+    #[test]
+    fn test_check_environment() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut env = PythonEnvironment::new("python3");
+        env.requirements = vec!["pip".to_string(), "no-such-package-gce>=1.0".to_string()];
+
+        let modules = vec!["json".to_string(), "no_such_module_gce".to_string()];
+        let report = env.check(temp_dir.path(), &modules).unwrap();
+        assert_eq!(report.missing_requirements, vec!["no-such-package-gce>=1.0"]);
+        assert_eq!(report.missing_modules, vec!["no_such_module_gce"]);
+
+        let missing = PythonEnvironment::new("missing-venv/bin/python");
+        assert!(missing.check(temp_dir.path(), &modules).is_err());
+    }
+}
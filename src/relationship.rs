@@ -1,3 +1,4 @@
+use crate::transform::{Guard, Transform};
 use serde::{Deserialize, Serialize};
 use ulid::Ulid;
 
@@ -10,13 +11,92 @@ pub struct Relationship {
     /// Destination cell (data flows TO this cell)
     pub to: Ulid,
 
-    // Future: transformation functions, filters, etc.
+    /// Output port on the source cell (a `set_output(..., key=...)` key); None = main output
+    #[serde(default)]
+    pub source_key: Option<String>,
+
+    /// Input name on the destination cell (exposed as a variable); None = positional only
+    #[serde(default)]
+    pub target_input: Option<String>,
+
+    /// Position among the destination's incoming relationships (its `input_N` index)
+    #[serde(default)]
+    pub input_order: usize,
+
+    /// Transform applied to the data before the destination receives it
+    #[serde(default)]
+    pub transform: Option<Transform>,
+
+    /// Condition on the source's output; the relationship is only followed when it holds
+    #[serde(default)]
+    pub guard: Option<Guard>,
+
+    /// Marks this relationship as the back edge of a bounded loop
+    #[serde(default, rename = "loop")]
+    pub loop_config: Option<LoopConfig>,
+}
+
+/// Bounds for a loop closed by a back-edge relationship
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct LoopConfig {
+    /// Maximum passes through the loop; execution stops if the loop wants more
+    pub max_iterations: usize,
+
+    /// Leave the loop (don't follow the back edge) once this holds for the source's output
+    #[serde(default)]
+    pub exit_when: Option<Guard>,
+}
+
+impl LoopConfig {
+    /// Create a loop bound without an exit condition
+    pub fn new(max_iterations: usize) -> Self {
+        Self {
+            max_iterations,
+            exit_when: None,
+        }
+    }
+
+    /// Create a loop bound that exits when a condition holds
+    pub fn with_exit(max_iterations: usize, exit_when: Guard) -> Self {
+        Self {
+            max_iterations,
+            exit_when: Some(exit_when),
+        }
+    }
 }
 
 impl Relationship {
     /// Create a new relationship
     pub fn new(from: Ulid, to: Ulid) -> Self {
-        Self { from, to }
+        Self {
+            from,
+            to,
+            source_key: None,
+            target_input: None,
+            input_order: 0,
+            transform: None,
+            guard: None,
+            loop_config: None,
+        }
+    }
+
+    /// Create a relationship between named ports
+    pub fn with_ports(
+        from: Ulid,
+        to: Ulid,
+        source_key: Option<String>,
+        target_input: Option<String>,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            source_key,
+            target_input,
+            input_order: 0,
+            transform: None,
+            guard: None,
+            loop_config: None,
+        }
     }
 
     /// Check if this relationship involves a given cell
@@ -34,12 +114,30 @@ impl Relationship {
         self.to == cell_id
     }
 
-    /// Reverse the direction of this relationship
+    /// Reverse the direction of this relationship (ports don't carry over)
     pub fn reversed(&self) -> Self {
-        Self {
-            from: self.to,
-            to: self.from,
-        }
+        Self::new(self.to, self.from)
+    }
+
+    /// Names every Python cell already binds; an input port with one of them would hide it
+    pub const RESERVED_PORT_NAMES: [&'static str; 4] = ["set_output", "cells", "inputs", "params"];
+
+    /// Shell cells get the number of inputs as `INPUT_COUNT`, which a port named `count`
+    /// (in any case) would overwrite. Positional `INPUT_<n>` can't clash, since port names
+    /// don't start with a digit.
+    pub const SHELL_RESERVED_PORT_NAME: &'static str = "count";
+
+    /// Check if an input port name is taken by the Python or Shell cell runtime
+    pub fn is_reserved_port_name(name: &str) -> bool {
+        Self::RESERVED_PORT_NAMES.contains(&name)
+            || name.eq_ignore_ascii_case(Self::SHELL_RESERVED_PORT_NAME)
+    }
+
+    /// Check if a name can be used as a Python variable for an input port
+    pub fn is_valid_port_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
     }
 }
 
@@ -92,4 +190,34 @@ mod tests {
         assert_eq!(reversed.from, to_id);
         assert_eq!(reversed.to, from_id);
     }
+
+    #[test]
+    fn test_relationship_ports() {
+        let rel = Relationship::with_ports(
+            Ulid::new(),
+            Ulid::new(),
+            Some("train".to_string()),
+            Some("train_data".to_string()),
+        );
+        assert_eq!(rel.source_key.as_deref(), Some("train"));
+        assert_eq!(rel.target_input.as_deref(), Some("train_data"));
+        assert!(rel.reversed().source_key.is_none());
+        assert!(Relationship::is_reserved_port_name("params"));
+        assert!(!Relationship::is_reserved_port_name("train_data"));
+        assert!(Relationship::is_reserved_port_name("Count"));
+        assert!(!Relationship::is_valid_port_name("0"));
+
+        // Relationships saved before ports existed still load
+        let old: Relationship = serde_json::from_str(&format!(
+            r#"{{"from":"{}","to":"{}"}}"#,
+            Ulid::new(),
+            Ulid::new()
+        ))
+        .unwrap();
+        assert!(old.source_key.is_none() && old.target_input.is_none());
+
+        assert!(Relationship::is_valid_port_name("train_2"));
+        assert!(!Relationship::is_valid_port_name("2train"));
+        assert!(!Relationship::is_valid_port_name("a-b"));
+    }
 }
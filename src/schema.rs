@@ -0,0 +1,111 @@
+//! JSON cells: parsing their content and checking values against a JSON Schema
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::fmt;
+
+/// Violations listed in an execution error before the rest are summarized
+const MAX_REPORTED_VIOLATIONS: usize = 5;
+
+/// A place where a value doesn't match its schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value ("" for the whole document)
+    pub pointer: String,
+    /// What is wrong there
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() { "(root)" } else { &self.pointer };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// Parse a JSON cell's content
+pub fn parse_json_content(content: &str) -> Result<Value> {
+    serde_json::from_str(content).map_err(|e| {
+        anyhow!("Invalid JSON at line {}, column {}: {}", e.line(), e.column(), e)
+    })
+}
+
+/// Check that a schema is itself a valid JSON Schema
+pub fn check_schema(schema: &Value) -> Result<()> {
+    jsonschema::validator_for(schema)
+        .map(|_| ())
+        .map_err(|e| anyhow!("Invalid JSON Schema: {}", e))
+}
+
+/// Every place `instance` breaks `schema`; errors if the schema itself is invalid
+pub fn schema_violations(schema: &Value, instance: &Value) -> Result<Vec<SchemaViolation>> {
+    let validator =
+        jsonschema::validator_for(schema).map_err(|e| anyhow!("Invalid JSON Schema: {}", e))?;
+    Ok(validator
+        .iter_errors(instance)
+        .map(|error| SchemaViolation {
+            pointer: error.instance_path.to_string(),
+            message: error.to_string(),
+        })
+        .collect())
+}
+
+/// Fail with the violations found, if any (what is checked is named by `what`)
+pub(crate) fn ensure_matches(schema: &Value, instance: &Value, what: &str) -> Result<()> {
+    let violations = schema_violations(schema, instance)?;
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    let mut listed: Vec<String> = violations
+        .iter()
+        .take(MAX_REPORTED_VIOLATIONS)
+        .map(ToString::to_string)
+        .collect();
+    if violations.len() > MAX_REPORTED_VIOLATIONS {
+        listed.push(format!("and {} more", violations.len() - MAX_REPORTED_VIOLATIONS));
+    }
+    Err(anyhow!("{} doesn't match the schema: {}", what, listed.join("; ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_violations() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": {"type": "string"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        });
+
+        let valid = json!({"name": "a", "tags": ["x"]});
+        assert!(schema_violations(&schema, &valid).unwrap().is_empty());
+
+        let invalid = json!({"tags": ["x", 2]});
+        let violations = schema_violations(&schema, &invalid).unwrap();
+        let pointers: Vec<&str> = violations.iter().map(|v| v.pointer.as_str()).collect();
+        assert_eq!(pointers.len(), 2);
+        assert!(pointers.contains(&""));
+        assert!(pointers.contains(&"/tags/1"));
+
+        let error = ensure_matches(&schema, &invalid, "Input").unwrap_err().to_string();
+        assert!(error.starts_with("Input doesn't match the schema: "));
+        assert!(error.contains("/tags/1: 2 is not of type \"string\""));
+
+        assert!(check_schema(&json!({"type": "no-such-type"})).is_err());
+        assert!(check_schema(&schema).is_ok());
+    }
+
+    #[test]
+    fn test_parse_json_content() {
+        assert_eq!(parse_json_content("[1, 2]").unwrap(), json!([1, 2]));
+        let error = parse_json_content("{\n  \"a\": }").unwrap_err();
+        assert!(error.to_string().starts_with("Invalid JSON at line 2, column"));
+    }
+}
@@ -0,0 +1,225 @@
+//! Script cells: Rhai scripts run inside the process, without Python
+
+use crate::execution::CapturedOutput;
+use crate::CellData;
+use anyhow::{anyhow, Result};
+use rhai::{Dynamic, Engine, Scope};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+/// Resource limits a script runs under
+///
+/// Rhai has no allocator hook, so memory is bounded by capping the size of the values a
+/// script can build rather than by counting bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptLimits {
+    /// Operations a script may perform before it is stopped
+    pub max_operations: u64,
+    /// Longest string a script may build, in bytes
+    pub max_string_size: usize,
+    /// Most elements an array, blob or object map may hold
+    pub max_collection_size: usize,
+    /// Deepest nesting of function calls
+    pub max_call_levels: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 1_000_000,
+            max_string_size: 1 << 20,
+            max_collection_size: 100_000,
+            max_call_levels: 64,
+        }
+    }
+}
+
+/// Engine with the limits applied; scripts have no access to time, files or the network
+fn script_engine(limits: &ScriptLimits) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(limits.max_operations)
+        .set_max_string_size(limits.max_string_size)
+        .set_max_array_size(limits.max_collection_size)
+        .set_max_map_size(limits.max_collection_size)
+        .set_max_call_levels(limits.max_call_levels);
+    engine
+}
+
+/// Compile a script to check its syntax (dry run)
+pub(crate) fn check_script(script: &str) -> Result<()> {
+    script_engine(&ScriptLimits::default())
+        .compile(script)
+        .map(|_| ())
+        .map_err(|e| anyhow!("Script syntax error: {}", e))
+}
+
+/// Run a script cell
+///
+/// Inputs are bound as `input_0`, `input_1`, ..., the `inputs` array and one variable
+/// per named input; run parameters are in the `params` map. `set_output(value)` or
+/// `set_output(value, key)` sets the output and its ports, mirroring Python cells; a
+/// script that doesn't set the output outputs the value of its last expression.
+/// `print` goes to captured stdout and `debug` to captured stderr.
+pub(crate) fn run_script(
+    script: &str,
+    inputs: &[CellData],
+    named_inputs: &[(String, CellData)],
+    params: &BTreeMap<String, CellData>,
+    limits: &ScriptLimits,
+    captured: &mut CapturedOutput,
+) -> Result<CellData> {
+    let mut engine = script_engine(limits);
+
+    let stdout = Rc::new(RefCell::new(String::new()));
+    let stderr = Rc::new(RefCell::new(String::new()));
+    let outputs: Rc<RefCell<BTreeMap<String, Dynamic>>> = Rc::default();
+    {
+        let stdout = stdout.clone();
+        engine.on_print(move |text| {
+            let mut stdout = stdout.borrow_mut();
+            stdout.push_str(text);
+            stdout.push('\n');
+        });
+        let stderr = stderr.clone();
+        engine.on_debug(move |text, _, _| {
+            let mut stderr = stderr.borrow_mut();
+            stderr.push_str(text);
+            stderr.push('\n');
+        });
+        let result = outputs.clone();
+        engine.register_fn("set_output", move |value: Dynamic| {
+            result.borrow_mut().insert("result".to_string(), value);
+        });
+        let ports = outputs.clone();
+        engine.register_fn("set_output", move |value: Dynamic, key: &str| {
+            ports.borrow_mut().insert(key.to_string(), value);
+        });
+    }
+
+    let mut scope = Scope::new();
+    let mut all_inputs = rhai::Array::new();
+    for (i, input) in inputs.iter().enumerate() {
+        let value = celldata_to_dynamic(input)?;
+        scope.push_dynamic(format!("input_{}", i), value.clone());
+        all_inputs.push(value);
+    }
+    scope.push("inputs", all_inputs);
+    for (name, input) in named_inputs {
+        scope.push_dynamic(name.clone(), celldata_to_dynamic(input)?);
+    }
+    let mut param_map = rhai::Map::new();
+    for (name, value) in params {
+        param_map.insert(name.into(), celldata_to_dynamic(value)?);
+    }
+    scope.push("params", param_map);
+
+    let result = engine.eval_with_scope::<Dynamic>(&mut scope, script);
+    captured.stdout = stdout.take();
+    captured.stderr = stderr.take();
+    let last_value = result.map_err(script_error)?;
+
+    let outputs = outputs.take();
+    for (key, value) in &outputs {
+        captured
+            .output_ports
+            .insert(key.clone(), dynamic_to_celldata(value)?);
+    }
+    match captured.output_ports.get("result") {
+        Some(output) => Ok(output.clone()),
+        None => dynamic_to_celldata(&last_value),
+    }
+}
+
+/// Numbers arrive as floats, tables as arrays of object maps, date-times as RFC 3339 text
+fn celldata_to_dynamic(data: &CellData) -> Result<Dynamic> {
+    let value = match data {
+        CellData::None => Dynamic::UNIT,
+        CellData::Text(s) => s.clone().into(),
+        CellData::Number(n) => (*n).into(),
+        CellData::Boolean(b) => (*b).into(),
+        CellData::Json(v) => rhai::serde::to_dynamic(v).map_err(script_error)?,
+        CellData::Binary(b) => Dynamic::from_blob(b.clone()),
+        CellData::Table(t) => rhai::serde::to_dynamic(t.to_json_rows()).map_err(script_error)?,
+        CellData::DateTime(_) => data.coerce_to_string().into(),
+    };
+    Ok(value)
+}
+
+fn dynamic_to_celldata(value: &Dynamic) -> Result<CellData> {
+    Ok(if value.is_unit() {
+        CellData::None
+    } else if let Ok(b) = value.as_bool() {
+        CellData::Boolean(b)
+    } else if let Ok(n) = value.as_int() {
+        CellData::Number(n as f64)
+    } else if let Ok(n) = value.as_float() {
+        CellData::Number(n)
+    } else if value.is_string() || value.is_char() {
+        CellData::Text(value.to_string())
+    } else if value.is_blob() {
+        CellData::Binary(value.clone().into_blob().map_err(|e| anyhow!(e))?)
+    } else {
+        CellData::Json(rhai::serde::from_dynamic(value).map_err(script_error)?)
+    })
+}
+
+/// Rhai errors aren't `Send`, so they are converted by message
+fn script_error(error: Box<rhai::EvalAltResult>) -> anyhow::Error {
+    anyhow!("{}", error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(script: &str, inputs: &[CellData]) -> (Result<CellData>, CapturedOutput) {
+        let mut captured = CapturedOutput::default();
+        let named = vec![("rows".to_string(), CellData::Json(json!([{"n": 1}, {"n": 2}])))];
+        let params = BTreeMap::from([("factor".to_string(), CellData::Number(3.0))]);
+        let limits = ScriptLimits::default();
+        let result = run_script(script, inputs, &named, &params, &limits, &mut captured);
+        (result, captured)
+    }
+
+    #[test]
+    fn test_run_script() {
+        let inputs = [CellData::Number(2.5), CellData::Text("x".to_string())];
+        let (output, captured) = run("print(`got ${input_1}`); input_0 * params.factor", &inputs);
+        assert_eq!(output.unwrap(), CellData::Number(7.5));
+        assert_eq!(captured.stdout, "got x\n");
+
+        let script = r#"
+            let total = 0;
+            for row in rows { total += row.n; }
+            set_output(#{ total: total, count: rows.len() });
+            set_output(inputs.len(), "inputs");
+            debug("done");
+        "#;
+        let (output, captured) = run(script, &inputs);
+        assert_eq!(output.unwrap(), CellData::Json(json!({"total": 3, "count": 2})));
+        assert_eq!(captured.output_ports["inputs"], CellData::Number(2.0));
+        assert!(captured.stderr.contains("done"));
+    }
+
+    #[test]
+    fn test_script_limits() {
+        let (output, _) = run("loop { }", &[]);
+        assert!(output.unwrap_err().to_string().contains("Too many operations"));
+
+        let (output, _) = run("let s = \"x\"; loop { s += s; }", &[]);
+        assert!(output.unwrap_err().to_string().contains("Length of string too large"));
+
+        let (output, _) = run("fn f(n) { f(n + 1) } f(0)", &[]);
+        assert!(output.is_err());
+    }
+
+    #[test]
+    fn test_check_script() {
+        assert!(check_script("let x = input_0 + 1; x * 2").is_ok());
+        let error = check_script("let x = ;").unwrap_err();
+        assert!(error.to_string().contains("line 1"));
+    }
+}
@@ -1,10 +1,13 @@
-use crate::{Canvas, Cell, GraphEvent, Relationship};
+use crate::{
+    Canvas, Cell, GraphEvent, Parameters, PythonEnvironment, Relationship, WasmSandbox,
+};
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use memmap2::Mmap;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 use ulid::Ulid;
 
@@ -15,6 +18,22 @@ pub struct Manifest {
     pub created: DateTime<Utc>,
     pub modified: DateTime<Utc>,
     pub start_cell: Option<Ulid>,
+    /// Named entry points (name → cell)
+    #[serde(default)]
+    pub entry_points: BTreeMap<String, Ulid>,
+    /// Run parameters and the environment variables secrets are read from
+    #[serde(default, flatten)]
+    pub params: Parameters,
+    /// Interpreter or virtual environment Python cells run in (None = the linked one)
+    #[serde(default)]
+    pub python: Option<PythonEnvironment>,
+    /// SQLite database file SQL cells query, relative to the project directory
+    /// (None = a fresh in-memory database per cell)
+    #[serde(default)]
+    pub sql_database: Option<PathBuf>,
+    /// Fuel, memory and filesystem access for WASM cells
+    #[serde(default)]
+    pub wasm: WasmSandbox,
 }
 
 impl Manifest {
@@ -26,6 +45,11 @@ impl Manifest {
             created: now,
             modified: now,
             start_cell,
+            entry_points: BTreeMap::new(),
+            params: Parameters::new(),
+            python: None,
+            sql_database: None,
+            wasm: WasmSandbox::default(),
         }
     }
 
@@ -313,6 +337,7 @@ impl Project {
         let mut manifest = self.load_manifest().unwrap_or_else(|_| Manifest::new(None));
         manifest.touch();
         manifest.start_cell = canvas.get_start_point().map(|c| c.id);
+        manifest.entry_points = canvas.entry_points().into_iter().collect();
         self.save_manifest(&manifest)?;
 
         // Save canvas
@@ -324,6 +349,14 @@ impl Project {
         Ok(())
     }
 
+    /// Save run parameters and secret declarations to the manifest
+    pub fn save_params(&self, params: &Parameters) -> Result<()> {
+        let mut manifest = self.load_manifest()?;
+        manifest.touch();
+        manifest.params = params.clone();
+        self.save_manifest(&manifest)
+    }
+
     /// Load complete project state
     pub fn load(&self) -> Result<(Manifest, Canvas)> {
         let manifest = self.load_manifest()?;
@@ -380,6 +413,18 @@ impl ExternalFileHandle {
         }
     }
 
+    /// Stream the file's content; memory-mapped files are read in place, without a copy
+    pub fn reader(&self) -> Result<Box<dyn Read + '_>> {
+        match &self.mmap {
+            Some(mmap) => Ok(Box::new(&mmap[..])),
+            None => {
+                let file = File::open(&self.path)
+                    .with_context(|| format!("Failed to open file: {}", self.path.display()))?;
+                Ok(Box::new(file))
+            }
+        }
+    }
+
     /// Read a range of bytes from the file
     pub fn read_range(&self, start: usize, length: usize) -> Result<&[u8]> {
         if let Some(mmap) = &self.mmap {
@@ -428,13 +473,22 @@ mod tests {
         let manifest_path = temp_dir.path().join("manifest.json");
 
         let cell_id = Ulid::new();
-        let manifest = Manifest::new(Some(cell_id));
+        let mut manifest = Manifest::new(Some(cell_id));
+        manifest.params.set("limit", serde_json::json!(10)).unwrap();
+        manifest.params.set_secret("api_key", "API_KEY").unwrap();
 
         manifest.save(&manifest_path).unwrap();
         let loaded = Manifest::load(&manifest_path).unwrap();
 
         assert_eq!(loaded.version, "0.1.0");
         assert_eq!(loaded.start_cell, Some(cell_id));
+        assert_eq!(loaded.params, manifest.params);
+
+        // Parameters sit at the top level of the manifest
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        assert_eq!(json["parameters"]["limit"], 10);
+        assert_eq!(json["secrets"]["api_key"], "API_KEY");
     }
 
     #[test]
@@ -535,6 +589,9 @@ mod tests {
             .unwrap();
         canvas.create_relationship(child1, child2).unwrap();
         canvas.set_start_point(child1).unwrap();
+        canvas
+            .set_entry_point(child2, Some("report".to_string()))
+            .unwrap();
 
         // Save
         project.save(&canvas).unwrap();
@@ -549,6 +606,8 @@ mod tests {
             canvas.relationship_count()
         );
         assert_eq!(manifest.start_cell, Some(child1));
+        assert_eq!(manifest.entry_points.get("report"), Some(&child2));
+        assert_eq!(loaded_canvas.get_entry_point("report").unwrap().id, child2);
 
         // Verify cells
         assert_eq!(
@@ -0,0 +1,274 @@
+//! Shell cells: running a command line and checking that its commands exist
+
+use anyhow::{anyhow, Context, Result};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long a shell command may run when its cell doesn't set a timeout
+pub const DEFAULT_SHELL_TIMEOUT_SECS: u64 = 60;
+
+/// Shell builtins and keywords that never need to be on PATH
+const BUILTINS: &[&str] = &[
+    ":", ".", "[", "[[", "alias", "break", "cd", "command", "continue", "echo", "eval", "exit",
+    "export", "false", "getopts", "hash", "let", "local", "printf", "pwd", "read", "readonly",
+    "return", "set", "shift", "source", "test", "trap", "true", "type", "ulimit", "umask",
+    "unset", "wait",
+];
+
+/// Keywords that can precede the command a simple command runs
+const PREFIX_KEYWORDS: &[&str] =
+    &["!", "{", "do", "elif", "else", "exec", "if", "then", "time", "until", "while"];
+
+/// Keywords that start or end a compound command rather than running one
+const COMPOUND_KEYWORDS: &[&str] =
+    &["}", "case", "done", "esac", "fi", "for", "function", "select"];
+
+/// Output of a finished shell command
+#[derive(Debug)]
+pub(crate) struct ShellOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Run a command line through the platform shell
+///
+/// `stdin` is written to the command's standard input; the command is killed if it
+/// runs longer than `timeout`.
+pub(crate) fn run_shell_command(
+    command_line: &str,
+    working_dir: &Path,
+    stdin: Vec<u8>,
+    env: &[(String, String)],
+    timeout: Duration,
+) -> Result<ShellOutput> {
+    let mut child = shell(command_line)
+        .current_dir(working_dir)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start shell in {}", working_dir.display()))?;
+
+    // Feed stdin and drain stdout/stderr on threads so a full pipe can't block the command
+    let mut child_stdin = child.stdin.take().expect("stdin is piped");
+    let writer = thread::spawn(move || {
+        // Commands that don't read their input close the pipe early; that's not an error
+        let _ = child_stdin.write_all(&stdin);
+    });
+    let stdout = read_in_background(child.stdout.take().expect("stdout is piped"));
+    let stderr = read_in_background(child.stderr.take().expect("stderr is piped"));
+
+    let status = wait_with_timeout(&mut child, timeout)?;
+    let _ = writer.join();
+    Ok(ShellOutput {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+#[cfg(unix)]
+fn shell(command_line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(command_line);
+    command
+}
+
+#[cfg(windows)]
+fn shell(command_line: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(command_line);
+    command
+}
+
+pub(crate) fn read_in_background(mut pipe: impl Read + Send + 'static) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        String::from_utf8_lossy(&buffer).into_owned()
+    })
+}
+
+pub(crate) fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<ExitStatus> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!("Command timed out after {:?}", timeout));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Commands in a command line that are neither shell builtins nor found on PATH
+///
+/// Paths (`./build.sh`) are checked relative to `working_dir`. Commands named by a
+/// variable (`$TOOL`) can't be checked and are skipped.
+pub fn missing_commands(command_line: &str, working_dir: &Path) -> Vec<String> {
+    let mut missing = Vec::new();
+    for name in command_names(command_line) {
+        let unchecked = BUILTINS.contains(&name.as_str()) || name.starts_with('$');
+        if unchecked || missing.contains(&name) {
+            continue;
+        }
+        let found = if name.contains('/') || name.contains(std::path::MAIN_SEPARATOR) {
+            working_dir.join(&name).exists()
+        } else {
+            find_on_path(&name).is_some()
+        };
+        if !found {
+            missing.push(name);
+        }
+    }
+    missing
+}
+
+/// Locate an executable in the directories on PATH
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let extensions: &[&str] = if cfg!(windows) { &["", ".exe", ".bat", ".cmd"] } else { &[""] };
+    std::env::split_paths(&std::env::var_os("PATH")?).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|ext| dir.join(format!("{}{}", name, ext)))
+            .find(|path| path.is_file())
+    })
+}
+
+/// Name of the command each simple command in a command line runs
+///
+/// `a x | b && FOO=1 c; if d; then e; fi` → a, b, c, d, e. Quotes are honoured, but
+/// this is not a full shell parser: subshells are read as separate commands and
+/// function definitions or `case` patterns may be mistaken for commands.
+fn command_names(command_line: &str) -> Vec<String> {
+    let assignment = regex::Regex::new(r"^[A-Za-z_]\w*=").unwrap();
+    let redirection = regex::Regex::new(r"^\d*[<>]").unwrap();
+
+    split_simple_commands(command_line)
+        .into_iter()
+        .filter_map(|words| {
+            let name = words.into_iter().find(|word| {
+                !assignment.is_match(word)
+                    && !redirection.is_match(word)
+                    && !PREFIX_KEYWORDS.contains(&word.as_str())
+            })?;
+            Some(name).filter(|name| !COMPOUND_KEYWORDS.contains(&name.as_str()))
+        })
+        .collect()
+}
+
+/// Split a command line into the words of each simple command
+///
+/// Commands are separated by `|`, `&`, `;`, newlines, parentheses and backticks outside
+/// quotes. Quotes are removed from words; comments are dropped.
+fn split_simple_commands(command_line: &str) -> Vec<Vec<String>> {
+    let mut commands: Vec<Vec<String>> = vec![Vec::new()];
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = command_line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                if let Some(escaped) = chars.next() {
+                    word.get_or_insert_with(String::new).push(escaped);
+                }
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, '\\') => {
+                if let Some(escaped) = chars.next() {
+                    word.get_or_insert_with(String::new).push(escaped);
+                }
+            }
+            (None, '#') if word.is_none() => {
+                chars.by_ref().find(|&c| c == '\n');
+                commands.push(Vec::new());
+            }
+            // `2>&1` and `<&3` are redirections, not background operators
+            (None, '&') if word.as_deref().is_some_and(|w| w.ends_with(['<', '>'])) => {
+                word.get_or_insert_with(String::new).push(c);
+            }
+            (None, c) if c.is_whitespace() || "|&;()`".contains(c) => {
+                if let Some(finished) = word.take() {
+                    commands.last_mut().unwrap().push(finished);
+                }
+                if !c.is_whitespace() || c == '\n' {
+                    commands.push(Vec::new());
+                }
+            }
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(finished) = word {
+        commands.last_mut().unwrap().push(finished);
+    }
+
+    commands.retain(|words| !words.is_empty());
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_names() {
+        assert_eq!(
+            command_names(r#"grep -c 'a | b' f | sort && LANG=C wc -l; echo "x;\"y|z\"""#),
+            vec!["grep", "sort", "wc", "echo"]
+        );
+        assert_eq!(
+            command_names("if test -f x; then cat x 2>&1; fi  # cat y\nls"),
+            vec!["test", "cat", "ls"]
+        );
+        assert_eq!(command_names("for f in *.csv; do head -1 $f; done"), vec!["head"]);
+        assert_eq!(command_names("echo $(date +%F)"), vec!["echo", "date"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_missing_commands() {
+        let dir = std::env::temp_dir();
+        assert!(missing_commands("ls | sort -r && cd /tmp", &dir).is_empty());
+        assert_eq!(
+            missing_commands("no-such-tool-gce --help | ./no-such-script.sh", &dir),
+            vec!["no-such-tool-gce", "./no-such-script.sh"]
+        );
+        assert!(missing_commands("$TOOL run", &dir).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_shell_command() {
+        let dir = std::env::temp_dir();
+        let env = vec![("GREETING".to_string(), "hello".to_string())];
+        let output = run_shell_command(
+            "echo \"$GREETING $(cat)\"; echo oops >&2; exit 3",
+            &dir,
+            b"world".to_vec(),
+            &env,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(output.stdout, "hello world\n");
+        assert_eq!(output.stderr, "oops\n");
+        assert_eq!(output.status.code(), Some(3));
+
+        let timed_out =
+            run_shell_command("sleep 5", &dir, Vec::new(), &[], Duration::from_millis(100));
+        assert!(timed_out.unwrap_err().to_string().contains("timed out"));
+    }
+}
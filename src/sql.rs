@@ -0,0 +1,281 @@
+//! SQL cells: queries against an embedded SQLite database
+
+use crate::{CellData, ColumnValues, Table};
+use anyhow::{anyhow, Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{Batch, Connection, OpenFlags, Statement};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Open the database SQL cells query: a file, or a fresh in-memory database for None
+///
+/// Read-only connections (used by dry run) never create the file; a missing file is
+/// checked against an empty in-memory database instead.
+pub(crate) fn open_database(path: Option<&Path>, read_only: bool) -> Result<Connection> {
+    let connection = match path {
+        Some(path) if read_only && !path.exists() => Connection::open_in_memory(),
+        Some(path) if read_only => {
+            Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        }
+        Some(path) => Connection::open(path),
+        None => Connection::open_in_memory(),
+    };
+    connection.with_context(|| match path {
+        Some(path) => format!("Failed to open SQLite database {}", path.display()),
+        None => "Failed to open in-memory SQLite database".to_string(),
+    })
+}
+
+/// Load a table as a TEMP table, visible only to this connection
+///
+/// Booleans are stored as 0/1 integers, since SQLite has no boolean type.
+pub(crate) fn load_table(connection: &Connection, name: &str, table: &Table) -> Result<()> {
+    if table.columns().is_empty() {
+        return Err(anyhow!("Input table '{}' has no columns", name));
+    }
+    let columns: Vec<String> = table
+        .columns()
+        .iter()
+        .map(|column| {
+            let affinity = match column.values {
+                ColumnValues::Text(_) => "TEXT",
+                ColumnValues::Number(_) => "REAL",
+                ColumnValues::Boolean(_) => "INTEGER",
+            };
+            format!("{} {}", quote_identifier(&column.name), affinity)
+        })
+        .collect();
+    connection.execute_batch(&format!(
+        "DROP TABLE IF EXISTS temp.{name}; CREATE TEMP TABLE {name} ({columns})",
+        name = quote_identifier(name),
+        columns = columns.join(", ")
+    ))?;
+
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let mut insert = connection.prepare(&format!(
+        "INSERT INTO temp.{} VALUES ({})",
+        quote_identifier(name),
+        placeholders
+    ))?;
+    for row in 0..table.row_count() {
+        let values = table.columns().iter().map(|column| match &column.values {
+            ColumnValues::Text(values) => values[row].clone().map_or(Value::Null, Value::Text),
+            ColumnValues::Number(values) => values[row].map_or(Value::Null, Value::Real),
+            ColumnValues::Boolean(values) => {
+                values[row].map_or(Value::Null, |b| Value::Integer(b.into()))
+            }
+        });
+        insert.execute(rusqlite::params_from_iter(values))?;
+    }
+    Ok(())
+}
+
+/// Run every statement of a query, returning the rows of the last one that has columns
+///
+/// Named parameters (`:name`, `@name` or `$name`) are bound from `values`. A query with
+/// no `SELECT`-like statement returns `CellData::None`.
+pub(crate) fn run_query(
+    connection: &Connection,
+    sql: &str,
+    values: &BTreeMap<String, CellData>,
+) -> Result<CellData> {
+    let mut result = CellData::None;
+    let mut batch = Batch::new(connection, sql);
+    while let Some(mut statement) = batch.next()? {
+        bind_parameters(&mut statement, values)?;
+        if statement.column_count() == 0 {
+            statement.raw_execute()?;
+        } else {
+            result = CellData::Table(query_table(&mut statement)?);
+        }
+    }
+    Ok(result)
+}
+
+/// Check a query's syntax without running it
+///
+/// Statements are only prepared, so tables and columns the query creates itself or
+/// reads from inputs may not exist yet; errors about those are not reported, and the
+/// statements after them are still checked.
+pub(crate) fn check_query(connection: &Connection, sql: &str) -> Result<()> {
+    let mut statement = String::new();
+    for piece in split_statements(sql) {
+        statement.push_str(piece);
+        let Err(e) = connection.prepare(&statement) else {
+            statement.clear();
+            continue;
+        };
+        let message = e.to_string();
+        // A `;` inside a trigger body ends a piece but not the statement
+        if message == "incomplete input" {
+            continue;
+        }
+        if !(message.starts_with("no such table") || message.starts_with("no such column")) {
+            return Err(anyhow!("SQL error: {}", message));
+        }
+        statement.clear();
+    }
+    if statement.trim().is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("SQL error: incomplete input"))
+    }
+}
+
+/// Split SQL at each `;` outside quotes and comments, keeping the `;`
+fn split_statements(sql: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = sql.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let closing = match c {
+            '\'' | '"' | '`' => Some(c),
+            '[' => Some(']'),
+            '-' if chars.peek().is_some_and(|&(_, next)| next == '-') => Some('\n'),
+            '/' if chars.peek().is_some_and(|&(_, next)| next == '*') => {
+                chars.next();
+                let mut previous = ' ';
+                for (_, c) in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                None
+            }
+            ';' => {
+                pieces.push(&sql[start..=i]);
+                start = i + 1;
+                None
+            }
+            _ => None,
+        };
+        if let Some(closing) = closing {
+            // A doubled quote inside a string is an escaped quote; the scan just resumes
+            chars.by_ref().find(|&(_, c)| c == closing);
+        }
+    }
+    if !sql[start..].trim().is_empty() {
+        pieces.push(&sql[start..]);
+    }
+    pieces
+}
+
+fn bind_parameters(statement: &mut Statement, values: &BTreeMap<String, CellData>) -> Result<()> {
+    for index in 1..=statement.parameter_count() {
+        let name = statement
+            .parameter_name(index)
+            .ok_or_else(|| anyhow!("SQL parameters must be named (:name), found '?'"))?;
+        let value = values
+            .get(&name[1..])
+            .ok_or_else(|| anyhow!("No input or parameter for SQL parameter {}", name))?;
+        let value = match value {
+            CellData::None => Value::Null,
+            CellData::Text(s) => Value::Text(s.clone()),
+            CellData::Number(n) => Value::Real(*n),
+            CellData::Boolean(b) => Value::Integer((*b).into()),
+            CellData::Json(v) => Value::Text(v.to_string()),
+            CellData::Binary(b) => Value::Blob(b.clone()),
+            CellData::Table(_) => {
+                return Err(anyhow!("SQL parameter {} can't be bound to a table", name))
+            }
+            // SQLite's date functions read ISO 8601 text
+            CellData::DateTime(_) => Value::Text(value.coerce_to_string()),
+        };
+        statement.raw_bind_parameter(index, value)?;
+    }
+    Ok(())
+}
+
+/// Collect a statement's rows; column types are inferred from the values
+fn query_table(statement: &mut Statement) -> Result<Table> {
+    let names: Vec<String> = statement.column_names().into_iter().map(String::from).collect();
+    let mut columns: Vec<Vec<serde_json::Value>> = vec![Vec::new(); names.len()];
+    let mut rows = statement.raw_query();
+    while let Some(row) = rows.next()? {
+        for (i, column) in columns.iter_mut().enumerate() {
+            column.push(match row.get::<_, Value>(i)? {
+                Value::Null => serde_json::Value::Null,
+                Value::Integer(n) => serde_json::json!(n),
+                Value::Real(n) => serde_json::json!(n),
+                Value::Text(s) => serde_json::Value::String(s),
+                Value::Blob(b) => serde_json::Value::String(String::from_utf8_lossy(&b).into()),
+            });
+        }
+    }
+
+    let mut table = Table::new();
+    for (name, values) in names.into_iter().zip(columns) {
+        table.push_column(name, ColumnValues::from_json(values))?;
+    }
+    Ok(table)
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_query_input_tables() {
+        let connection = open_database(None, false).unwrap();
+        let sales = Table::from_json_rows(&json!([
+            {"region": "north", "amount": 10, "paid": true},
+            {"region": "south", "amount": 5, "paid": false},
+            {"region": "north", "amount": 7.5, "paid": null},
+        ]))
+        .unwrap();
+        load_table(&connection, "sales data", &sales).unwrap();
+
+        let values = BTreeMap::from([("min".to_string(), CellData::Number(6.0))]);
+        let sql = "SELECT region, SUM(amount) AS total, COUNT(paid) AS known FROM \"sales data\" \
+                   WHERE amount > :min GROUP BY region ORDER BY region";
+        let CellData::Table(result) = run_query(&connection, sql, &values).unwrap() else {
+            panic!("expected a table");
+        };
+        let expected = json!([{"region": "north", "total": 17.5, "known": 1.0}]);
+        assert_eq!(result.to_json_rows(), expected);
+
+        let script = "CREATE TABLE t (x); INSERT INTO t VALUES (1), ('a'); SELECT x FROM t";
+        let CellData::Table(result) = run_query(&connection, script, &values).unwrap() else {
+            panic!("expected a table");
+        };
+        assert_eq!(
+            result.column("x").unwrap().values,
+            ColumnValues::Text(vec![Some("1".to_string()), Some("a".to_string())])
+        );
+
+        assert_eq!(run_query(&connection, "DELETE FROM t", &values).unwrap(), CellData::None);
+        let missing = run_query(&connection, "SELECT :nope", &values).unwrap_err();
+        assert!(missing.to_string().contains(":nope"));
+    }
+
+    #[test]
+    fn test_check_query() {
+        let connection = open_database(None, true).unwrap();
+        assert!(check_query(&connection, "SELECT name FROM inputs WHERE id = :id").is_ok());
+        assert!(check_query(&connection, "CREATE TABLE t (x); SELECT x FROM t").is_ok());
+        let error = check_query(&connection, "SELEC 1").unwrap_err();
+        assert!(error.to_string().contains("syntax error"));
+
+        // A missing table doesn't stop the statements after it from being checked
+        let error = check_query(&connection, "SELECT * FROM missing; SELEC 1").unwrap_err();
+        assert!(error.to_string().contains("syntax error"));
+        assert!(check_query(&connection, "SELECT ';' -- ;\n, x FROM t; /* ; */ SELECT 1").is_ok());
+
+        // A trigger body's statements belong to the trigger
+        connection.execute_batch("CREATE TABLE log (x)").unwrap();
+        let trigger = "CREATE TRIGGER tr AFTER INSERT ON log BEGIN \
+                       DELETE FROM log; SELEC 1; END; SELECT 2";
+        let error = check_query(&connection, trigger).unwrap_err();
+        assert!(error.to_string().contains("syntax error"));
+        let trigger = "CREATE TRIGGER tr AFTER INSERT ON log BEGIN DELETE FROM log; END; SELECT 2";
+        assert!(check_query(&connection, trigger).is_ok());
+        let error = check_query(&connection, "CREATE TRIGGER tr AFTER INSERT ON log BEGIN");
+        assert!(error.unwrap_err().to_string().contains("incomplete input"));
+    }
+}
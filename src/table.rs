@@ -0,0 +1,399 @@
+//! Tabular data passed between cells
+
+use crate::{CellContent, ExternalFileHandle};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::Path;
+
+/// Table stored column by column; every column has the same length
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Table {
+    columns: Vec<Column>,
+}
+
+/// Named, typed column of a table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Column {
+    pub name: String,
+    pub values: ColumnValues,
+}
+
+/// Type of a column's values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnType {
+    Text,
+    Number,
+    Boolean,
+}
+
+/// Values of a column, `None` for nulls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ColumnValues {
+    Text(Vec<Option<String>>),
+    Number(Vec<Option<f64>>),
+    Boolean(Vec<Option<bool>>),
+}
+
+impl ColumnValues {
+    /// Number of values, nulls included
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnValues::Text(values) => values.len(),
+            ColumnValues::Number(values) => values.len(),
+            ColumnValues::Boolean(values) => values.len(),
+        }
+    }
+
+    /// Check if the column has no values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Type of the values
+    pub fn column_type(&self) -> ColumnType {
+        match self {
+            ColumnValues::Text(_) => ColumnType::Text,
+            ColumnValues::Number(_) => ColumnType::Number,
+            ColumnValues::Boolean(_) => ColumnType::Boolean,
+        }
+    }
+
+    /// Number of nulls
+    pub fn null_count(&self) -> usize {
+        match self {
+            ColumnValues::Text(values) => values.iter().filter(|v| v.is_none()).count(),
+            ColumnValues::Number(values) => values.iter().filter(|v| v.is_none()).count(),
+            ColumnValues::Boolean(values) => values.iter().filter(|v| v.is_none()).count(),
+        }
+    }
+
+    /// Value at a row as JSON (null for nulls)
+    pub fn json_value(&self, row: usize) -> serde_json::Value {
+        match self {
+            ColumnValues::Text(values) => serde_json::json!(values[row]),
+            ColumnValues::Number(values) => serde_json::json!(values[row]),
+            ColumnValues::Boolean(values) => serde_json::json!(values[row]),
+        }
+    }
+
+    /// Infer a column type from JSON values
+    ///
+    /// Numbers and booleans keep their type when every non-null value has it; anything
+    /// else becomes text (nested values as JSON).
+    pub fn from_json(values: Vec<serde_json::Value>) -> Self {
+        use serde_json::Value;
+        if values.iter().all(|v| v.is_null() || v.is_number()) {
+            ColumnValues::Number(values.iter().map(Value::as_f64).collect())
+        } else if values.iter().all(|v| v.is_null() || v.is_boolean()) {
+            ColumnValues::Boolean(values.iter().map(Value::as_bool).collect())
+        } else {
+            ColumnValues::Text(
+                values
+                    .into_iter()
+                    .map(|v| match v {
+                        Value::Null => None,
+                        Value::String(s) => Some(s),
+                        other => Some(other.to_string()),
+                    })
+                    .collect(),
+            )
+        }
+    }
+
+    /// Infer a column type from text fields, as read from CSV
+    ///
+    /// Numbers and booleans (`true`/`false` in any case) keep their type when every
+    /// non-null field parses as one; anything else stays text.
+    pub fn from_text(values: Vec<Option<String>>) -> Self {
+        fn parse_all<T>(
+            values: &[Option<String>],
+            parse: impl Fn(&str) -> Option<T>,
+        ) -> Option<Vec<Option<T>>> {
+            values
+                .iter()
+                .map(|value| match value {
+                    None => Some(None),
+                    Some(text) => parse(text.trim()).map(Some),
+                })
+                .collect()
+        }
+
+        if let Some(numbers) = parse_all(&values, |text| text.parse().ok()) {
+            ColumnValues::Number(numbers)
+        } else if let Some(booleans) =
+            parse_all(&values, |text| text.to_ascii_lowercase().parse().ok())
+        {
+            ColumnValues::Boolean(booleans)
+        } else {
+            ColumnValues::Text(values)
+        }
+    }
+}
+
+impl Table {
+    /// Create a table with no columns
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a column; it must be as long as the existing ones
+    pub fn push_column(&mut self, name: impl Into<String>, values: ColumnValues) -> Result<()> {
+        let name = name.into();
+        if self.columns.iter().any(|c| c.name == name) {
+            return Err(anyhow!("Duplicate column '{}'", name));
+        }
+        if !self.columns.is_empty() && values.len() != self.row_count() {
+            return Err(anyhow!(
+                "Column '{}' has {} rows, the table has {}",
+                name,
+                values.len(),
+                self.row_count()
+            ));
+        }
+        self.columns.push(Column { name, values });
+        Ok(())
+    }
+
+    /// Columns in order
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Look up a column by name
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// Number of rows
+    pub fn row_count(&self) -> usize {
+        self.columns.first().map_or(0, |c| c.values.len())
+    }
+
+    /// Column names and types, in order
+    pub fn schema(&self) -> Vec<(&str, ColumnType)> {
+        self.columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.values.column_type()))
+            .collect()
+    }
+
+    /// Build a table from named columns of JSON values, typed as by `ColumnValues::from_json`
+    pub fn from_json_columns(
+        columns: impl IntoIterator<Item = (String, Vec<serde_json::Value>)>,
+    ) -> Result<Self> {
+        let mut table = Table::new();
+        for (name, values) in columns {
+            table.push_column(name, ColumnValues::from_json(values))?;
+        }
+        Ok(table)
+    }
+
+    /// Build a table from a JSON array of objects
+    ///
+    /// Columns are sorted by name, since JSON objects don't keep their key order; a row
+    /// missing a key has a null there.
+    pub fn from_json_rows(rows: &serde_json::Value) -> Result<Self> {
+        let rows = rows
+            .as_array()
+            .ok_or_else(|| anyhow!("Expected an array of objects"))?;
+        let mut names = BTreeSet::new();
+        for row in rows {
+            let object = row
+                .as_object()
+                .ok_or_else(|| anyhow!("Expected an array of objects, found {}", row))?;
+            names.extend(object.keys());
+        }
+
+        Table::from_json_columns(names.into_iter().map(|name| {
+            let values = rows
+                .iter()
+                .map(|row| row.get(name).cloned().unwrap_or(serde_json::Value::Null))
+                .collect();
+            (name.clone(), values)
+        }))
+    }
+
+    /// Read CSV with a header row; empty fields are nulls and column types are inferred
+    pub fn from_csv(reader: impl Read) -> Result<Self> {
+        let mut reader = csv::Reader::from_reader(reader);
+        let names: Vec<String> = reader
+            .headers()
+            .map_err(|e| anyhow!("Invalid CSV: {}", e))?
+            .iter()
+            .map(String::from)
+            .collect();
+        let mut columns: Vec<Vec<Option<String>>> = vec![Vec::new(); names.len()];
+        for record in reader.records() {
+            let record = record.map_err(|e| anyhow!("Invalid CSV: {}", e))?;
+            for (column, field) in columns.iter_mut().zip(record.iter()) {
+                column.push((!field.is_empty()).then(|| field.to_string()));
+            }
+        }
+
+        let mut table = Table::new();
+        for (name, values) in names.into_iter().zip(columns) {
+            table.push_column(name, ColumnValues::from_text(values))?;
+        }
+        Ok(table)
+    }
+
+    /// Copy of the table with every text value passed through `f`
+    pub fn map_text(&self, f: impl Fn(&str) -> String) -> Table {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| {
+                let values = match &c.values {
+                    ColumnValues::Text(values) => ColumnValues::Text(
+                        values.iter().map(|v| v.as_deref().map(&f)).collect(),
+                    ),
+                    other => other.clone(),
+                };
+                Column { name: c.name.clone(), values }
+            })
+            .collect();
+        Table { columns }
+    }
+
+    /// Rows as a JSON array of objects
+    pub fn to_json_rows(&self) -> serde_json::Value {
+        let rows = (0..self.row_count())
+            .map(|row| {
+                let object = self
+                    .columns
+                    .iter()
+                    .map(|c| (c.name.clone(), c.values.json_value(row)))
+                    .collect();
+                serde_json::Value::Object(object)
+            })
+            .collect();
+        serde_json::Value::Array(rows)
+    }
+
+    /// Render as CSV with a header row (nulls are empty fields)
+    pub fn to_csv(&self) -> String {
+        fn field(value: &str) -> String {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        }
+
+        let mut lines = vec![self
+            .columns
+            .iter()
+            .map(|c| field(&c.name))
+            .collect::<Vec<_>>()
+            .join(",")];
+        for row in 0..self.row_count() {
+            let fields: Vec<String> = self
+                .columns
+                .iter()
+                .map(|c| match c.values.json_value(row) {
+                    serde_json::Value::Null => String::new(),
+                    serde_json::Value::String(s) => field(&s),
+                    other => other.to_string(),
+                })
+                .collect();
+            lines.push(fields.join(","));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Read a CSV cell's table: inline text, or an external file relative to `project_dir`
+///
+/// Large external files are memory-mapped, so the file is parsed without first being
+/// copied into a buffer; the parsed table itself is always held in memory.
+pub fn read_csv_content(content: &CellContent, project_dir: Option<&Path>) -> Result<Table> {
+    match content {
+        CellContent::Inline(text) => Table::from_csv(text.as_bytes()),
+        CellContent::External { path, .. } => {
+            let path = match project_dir {
+                Some(project_dir) => project_dir.join(path),
+                None => path.clone(),
+            };
+            Table::from_csv(ExternalFileHandle::open(path)?.reader()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_rows_round_trip() {
+        let rows = json!([
+            {"city": "Oslo", "temp": 3.5, "sunny": false},
+            {"city": "Rome, IT", "temp": null, "sunny": true, "note": {"a": 1}},
+        ]);
+        let table = Table::from_json_rows(&rows).unwrap();
+
+        assert_eq!(table.row_count(), 2);
+        let names: Vec<&str> = table.columns().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["city", "note", "sunny", "temp"]);
+        assert_eq!(
+            table.column("temp").unwrap().values,
+            ColumnValues::Number(vec![Some(3.5), None])
+        );
+        assert_eq!(
+            table.column("note").unwrap().values,
+            ColumnValues::Text(vec![None, Some("{\"a\":1}".to_string())])
+        );
+
+        assert_eq!(
+            table.to_json_rows()[0],
+            json!({"city": "Oslo", "temp": 3.5, "sunny": false, "note": null})
+        );
+        assert_eq!(
+            table.to_csv(),
+            "city,note,sunny,temp\nOslo,,false,3.5\n\"Rome, IT\",\"{\"\"a\"\":1}\",true,"
+        );
+
+        assert_eq!(
+            table.schema(),
+            vec![
+                ("city", ColumnType::Text),
+                ("note", ColumnType::Text),
+                ("sunny", ColumnType::Boolean),
+                ("temp", ColumnType::Number),
+            ]
+        );
+        assert_eq!(table.column("note").unwrap().values.null_count(), 1);
+
+        assert!(Table::from_json_rows(&json!([1, 2])).is_err());
+        let mut table = Table::new();
+        table.push_column("a", ColumnValues::Number(vec![Some(1.0)])).unwrap();
+        assert!(table.push_column("b", ColumnValues::Number(vec![])).is_err());
+        assert!(table.push_column("a", ColumnValues::Number(vec![Some(2.0)])).is_err());
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let csv = "name,score,passed,note\nAda,91.5,TRUE,\"first, best\"\nBo,,false,\n";
+        let table = Table::from_csv(csv.as_bytes()).unwrap();
+
+        let names: Vec<&str> = table.columns().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["name", "score", "passed", "note"]);
+        assert_eq!(
+            table.column("score").unwrap().values,
+            ColumnValues::Number(vec![Some(91.5), None])
+        );
+        assert_eq!(
+            table.column("passed").unwrap().values,
+            ColumnValues::Boolean(vec![Some(true), Some(false)])
+        );
+        assert_eq!(
+            table.column("note").unwrap().values,
+            ColumnValues::Text(vec![Some("first, best".to_string()), None])
+        );
+
+        let error = Table::from_csv("a,b\n1,2\n3\n".as_bytes()).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid CSV: "));
+    }
+}
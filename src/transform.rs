@@ -0,0 +1,527 @@
+//! Transformation functions and guards applied to data flowing along a relationship
+//! The upstream output is transformed before the downstream cell receives it;
+//! a guard decides whether the relationship is followed at all
+
+use crate::execution::{celldata_to_python, python_to_celldata, CellData, DataType};
+use crate::Table;
+use anyhow::{anyhow, Result};
+use evalexpr::{ContextWithMutableVariables, HashMapContext, Value};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Transform attached to a relationship
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Transform {
+    /// Math expression over `x`, the upstream value as a number (e.g. "x * 100")
+    Math(String),
+    /// JSON path into the upstream value (e.g. "$.rows[0].name")
+    JsonPath(String),
+    /// Coerce the upstream value to another type
+    Coerce(CoerceType),
+    /// Python lambda called with the upstream value (e.g. "lambda x: x.upper()")
+    Python(String),
+}
+
+/// Target type of a coercion transform
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum CoerceType {
+    Text,
+    Number,
+    Boolean,
+    Json,
+    /// A table, from JSON rows or CSV text
+    Table,
+}
+
+/// Condition on a relationship, evaluated against the source cell's output
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Guard {
+    /// Follow the relationship when the value is truthy
+    Truthy,
+    /// Follow the relationship when the value is falsy
+    Falsy,
+    /// Compare the value against a literal (numerically if both sides are numbers)
+    Compare { op: CompareOp, value: String },
+    /// Math expression over `x` that must evaluate to a boolean (e.g. "x > 0 && x < 10")
+    Math(String),
+}
+
+/// Comparison operator of a guard
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    /// All operators, in display order
+    pub const ALL: [CompareOp; 6] = [
+        CompareOp::Eq,
+        CompareOp::Ne,
+        CompareOp::Lt,
+        CompareOp::Le,
+        CompareOp::Gt,
+        CompareOp::Ge,
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+
+    fn holds(&self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            CompareOp::Eq => ordering == Equal,
+            CompareOp::Ne => ordering != Equal,
+            CompareOp::Lt => ordering == Less,
+            CompareOp::Le => ordering != Greater,
+            CompareOp::Gt => ordering == Greater,
+            CompareOp::Ge => ordering != Less,
+        }
+    }
+}
+
+impl Guard {
+    /// Evaluate the guard against the source's output
+    pub fn evaluate(&self, data: &CellData) -> Result<bool> {
+        match self {
+            Guard::Truthy => Ok(data.is_truthy()),
+            Guard::Falsy => Ok(!data.is_truthy()),
+            Guard::Compare { op, value } => {
+                let ordering = match (as_number(data), value.trim().parse::<f64>()) {
+                    (Ok(lhs), Ok(rhs)) => lhs
+                        .partial_cmp(&rhs)
+                        .ok_or_else(|| anyhow!("Cannot compare {} with {}", lhs, rhs))?,
+                    _ => data.coerce_to_string().as_str().cmp(value.as_str()),
+                };
+                Ok(op.holds(ordering))
+            }
+            Guard::Math(expr) => evaluate_condition(expr, &[("x".to_string(), data.clone())]),
+        }
+    }
+
+    /// Check the guard without real data (used by dry run)
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Guard::Math(_) => self.evaluate(&CellData::Number(1.0)).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Short name of the guard kind (for UI)
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Guard::Truthy => "Truthy",
+            Guard::Falsy => "Falsy",
+            Guard::Compare { .. } => "Compare",
+            Guard::Math(_) => "Math",
+        }
+    }
+}
+
+impl std::fmt::Display for Guard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Guard::Truthy => write!(f, "if x"),
+            Guard::Falsy => write!(f, "if not x"),
+            Guard::Compare { op, value } => write!(f, "if x {} {}", op.symbol(), value),
+            Guard::Math(expr) => write!(f, "if {}", expr),
+        }
+    }
+}
+
+/// Evaluate a boolean Math expression over named values (guards, breakpoint conditions)
+///
+/// Whole numbers are bound as integers so `x == 3` compares as expected.
+pub fn evaluate_condition(expr: &str, variables: &[(String, CellData)]) -> Result<bool> {
+    let mut context = HashMapContext::new();
+    for (name, data) in variables {
+        let value = match data {
+            CellData::Number(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => {
+                Value::Int(*n as i64)
+            }
+            CellData::Number(n) => Value::Float(*n),
+            CellData::Boolean(b) => Value::Boolean(*b),
+            CellData::Text(s) => Value::String(s.clone()),
+            _ => continue,
+        };
+        context
+            .set_value(name.clone(), value)
+            .map_err(|e| anyhow!("Condition '{}': {}", expr, e))?;
+    }
+
+    match evalexpr::eval_with_context(expr, &context) {
+        Ok(Value::Boolean(b)) => Ok(b),
+        Ok(other) => Err(anyhow!(
+            "Condition '{}' must be true or false, got {}",
+            expr,
+            other
+        )),
+        Err(e) => Err(anyhow!("Condition '{}': {}", expr, e)),
+    }
+}
+
+/// One step of a JSON path
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl Transform {
+    /// Apply the transform to an upstream value
+    pub fn apply(&self, data: &CellData) -> Result<CellData> {
+        match self {
+            Transform::Math(expr) => {
+                let mut context = HashMapContext::new();
+                context
+                    .set_value("x".into(), Value::Float(as_number(data)?))
+                    .map_err(|e| anyhow!("Math transform error: {}", e))?;
+                let value = evalexpr::eval_with_context(expr, &context)
+                    .map_err(|e| anyhow!("Math transform error: {}", e))?;
+                match value {
+                    Value::Float(n) => Ok(CellData::Number(n)),
+                    Value::Int(n) => Ok(CellData::Number(n as f64)),
+                    Value::Boolean(b) => Ok(CellData::Boolean(b)),
+                    Value::String(s) => Ok(CellData::Text(s)),
+                    other => Err(anyhow!("Math transform produced unsupported value: {}", other)),
+                }
+            }
+            Transform::JsonPath(path) => {
+                let segments = parse_json_path(path)?;
+                let mut current = as_json(data)?;
+                for segment in &segments {
+                    current = match (segment, current) {
+                        (PathSegment::Key(key), serde_json::Value::Object(mut map)) => map
+                            .remove(key)
+                            .ok_or_else(|| anyhow!("JSON path {}: no key '{}'", path, key))?,
+                        (PathSegment::Index(i), serde_json::Value::Array(mut items))
+                            if *i < items.len() =>
+                        {
+                            items.swap_remove(*i)
+                        }
+                        (segment, _) => {
+                            return Err(anyhow!("JSON path {}: cannot select {:?}", path, segment))
+                        }
+                    };
+                }
+                Ok(json_to_celldata(current))
+            }
+            Transform::Coerce(target) => coerce(data, *target),
+            Transform::Python(source) => Python::with_gil(|py| {
+                let function = py
+                    .eval_bound(source, None, None)
+                    .map_err(|e| anyhow!("Python transform error: {}", e))?;
+                let result = function
+                    .call1((celldata_to_python(py, data)?,))
+                    .map_err(|e| anyhow!("Python transform error: {}", e))?;
+                python_to_celldata(&result)
+            }),
+        }
+    }
+
+    /// Check the transform without running it (used by dry run)
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            // evalexpr only reports some malformed expressions when evaluating, so try a sample
+            Transform::Math(expr) => self
+                .apply(&CellData::Number(1.0))
+                .map(|_| ())
+                .map_err(|e| anyhow!("Invalid math transform '{}': {}", expr, e)),
+            Transform::JsonPath(path) => parse_json_path(path).map(|_| ()),
+            Transform::Coerce(_) => Ok(()),
+            Transform::Python(source) => {
+                if !source.trim_start().starts_with("lambda") {
+                    return Err(anyhow!("Python transform must be a lambda: {}", source));
+                }
+                Python::with_gil(|py| {
+                    py.import_bound("builtins")?
+                        .getattr("compile")?
+                        .call1((source.as_str(), "<transform>", "eval"))
+                        .map_err(|e| anyhow!("Invalid Python transform '{}': {}", source, e))?;
+                    Ok(())
+                })
+            }
+        }
+    }
+
+    /// Check if the transform can take upstream data of a type (used by dry run)
+    pub fn accepts(&self, input: DataType) -> bool {
+        match self {
+            // Math converts its input to a number first
+            Transform::Math(_) => matches!(
+                input,
+                DataType::Any | DataType::Number | DataType::Boolean | DataType::Text
+            ),
+            Transform::JsonPath(_) => input != DataType::Binary,
+            Transform::Coerce(_) | Transform::Python(_) => true,
+        }
+    }
+
+    /// Type the transform produces, when known statically
+    pub fn output_type(&self) -> DataType {
+        match self {
+            Transform::Coerce(CoerceType::Text) => DataType::Text,
+            Transform::Coerce(CoerceType::Number) => DataType::Number,
+            Transform::Coerce(CoerceType::Boolean) => DataType::Boolean,
+            Transform::Coerce(CoerceType::Json) => DataType::Json,
+            Transform::Coerce(CoerceType::Table) => DataType::Table,
+            // Math may produce a number, boolean or string depending on the expression
+            Transform::Math(_) | Transform::JsonPath(_) | Transform::Python(_) => DataType::Any,
+        }
+    }
+
+    /// Short name of the transform kind (for UI)
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Transform::Math(_) => "Math",
+            Transform::JsonPath(_) => "JSON path",
+            Transform::Coerce(_) => "Coerce",
+            Transform::Python(_) => "Python",
+        }
+    }
+}
+
+impl std::fmt::Display for Transform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Transform::Math(expr) => write!(f, "ƒ {}", expr),
+            Transform::JsonPath(path) => write!(f, "{}", path),
+            Transform::Coerce(target) => write!(f, "as {:?}", target),
+            Transform::Python(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+/// Parse a JSON path like `$.rows[0].name` or `rows.0.name`
+fn parse_json_path(path: &str) -> Result<Vec<PathSegment>> {
+    let trimmed = path.trim();
+    let rest = trimmed.strip_prefix('$').unwrap_or(trimmed);
+    let mut segments = Vec::new();
+    let mut chars = rest.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {}
+            '[' => {
+                let inner: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let inner = inner.trim();
+                if let Some(key) = inner
+                    .strip_prefix('"')
+                    .and_then(|s| s.strip_suffix('"'))
+                    .or_else(|| inner.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
+                {
+                    segments.push(PathSegment::Key(key.to_string()));
+                } else {
+                    let index = inner
+                        .parse()
+                        .map_err(|_| anyhow!("Invalid JSON path {}: bad index [{}]", path, inner))?;
+                    segments.push(PathSegment::Index(index));
+                }
+            }
+            _ => {
+                let mut name = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next == '.' || next == '[' {
+                        break;
+                    }
+                    name.push(next);
+                    chars.next();
+                }
+                segments.push(match name.parse() {
+                    Ok(index) => PathSegment::Index(index),
+                    Err(_) => PathSegment::Key(name),
+                });
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+fn as_number(data: &CellData) -> Result<f64> {
+    match data {
+        CellData::Number(n) => Ok(*n),
+        CellData::Boolean(b) => Ok(if *b { 1.0 } else { 0.0 }),
+        CellData::Text(s) => s
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("Cannot convert '{}' to a number", s)),
+        CellData::Json(serde_json::Value::Number(n)) => {
+            n.as_f64().ok_or_else(|| anyhow!("Cannot convert {} to a number", n))
+        }
+        other => Err(anyhow!("Cannot convert {:?} to a number", other)),
+    }
+}
+
+pub(crate) fn as_json(data: &CellData) -> Result<serde_json::Value> {
+    match data {
+        CellData::None => Ok(serde_json::Value::Null),
+        CellData::Json(v) => Ok(v.clone()),
+        CellData::Text(s) => {
+            Ok(serde_json::from_str(s).unwrap_or_else(|_| serde_json::Value::String(s.clone())))
+        }
+        CellData::Number(n) => Ok(serde_json::json!(n)),
+        CellData::Boolean(b) => Ok(serde_json::Value::Bool(*b)),
+        CellData::Binary(b) => Ok(serde_json::json!(b)),
+        CellData::Table(t) => Ok(t.to_json_rows()),
+        CellData::DateTime(_) => Ok(serde_json::Value::String(data.coerce_to_string())),
+    }
+}
+
+pub(crate) fn json_to_celldata(value: serde_json::Value) -> CellData {
+    match value {
+        serde_json::Value::Null => CellData::None,
+        serde_json::Value::Bool(b) => CellData::Boolean(b),
+        serde_json::Value::Number(n) => match n.as_f64() {
+            Some(f) => CellData::Number(f),
+            None => CellData::Json(serde_json::Value::Number(n)),
+        },
+        serde_json::Value::String(s) => CellData::Text(s),
+        other => CellData::Json(other),
+    }
+}
+
+fn coerce(data: &CellData, target: CoerceType) -> Result<CellData> {
+    match target {
+        CoerceType::Text => Ok(CellData::Text(data.coerce_to_string())),
+        CoerceType::Number => as_number(data).map(CellData::Number),
+        CoerceType::Boolean => Ok(CellData::Boolean(match data {
+            CellData::None => false,
+            CellData::Boolean(b) => *b,
+            CellData::Number(n) => *n != 0.0,
+            CellData::Text(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "" | "false" | "0" | "no" => false,
+                "true" | "1" | "yes" => true,
+                _ => return Err(anyhow!("Cannot convert '{}' to a boolean", s)),
+            },
+            CellData::Json(v) => !(v.is_null() || v == &serde_json::Value::Bool(false)),
+            CellData::Binary(b) => !b.is_empty(),
+            CellData::Table(t) => t.row_count() > 0,
+            CellData::DateTime(_) => true,
+        })),
+        CoerceType::Json => as_json(data).map(CellData::Json),
+        CoerceType::Table => Ok(CellData::Table(match data {
+            CellData::Table(t) => t.clone(),
+            CellData::Json(v) => Table::from_json_rows(v)?,
+            CellData::Text(s) => Table::from_csv(s.as_bytes())?,
+            other => return Err(anyhow!("Cannot convert {:?} to a table", other.data_type())),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_math_transform() {
+        let transform = Transform::Math("x * 100".to_string());
+        assert!(transform.validate().is_ok());
+        assert_eq!(
+            transform.apply(&CellData::Number(0.25)).unwrap(),
+            CellData::Number(25.0)
+        );
+        assert!(Transform::Math("x *".to_string()).validate().is_err());
+    }
+
+    #[test]
+    fn test_json_path_transform() {
+        let data = CellData::Json(serde_json::json!({"rows": [{"name": "a"}, {"name": "b"}]}));
+        let transform = Transform::JsonPath("$.rows[1].name".to_string());
+        assert_eq!(transform.apply(&data).unwrap(), CellData::Text("b".to_string()));
+        assert_eq!(
+            Transform::JsonPath("rows.0".to_string()).apply(&data).unwrap(),
+            CellData::Json(serde_json::json!({"name": "a"}))
+        );
+        assert!(Transform::JsonPath("$.missing".to_string()).apply(&data).is_err());
+        assert!(Transform::JsonPath("$.rows[x]".to_string()).validate().is_err());
+    }
+
+    #[test]
+    fn test_coerce_transform() {
+        let text = CellData::Text(" 42 ".to_string());
+        assert_eq!(
+            Transform::Coerce(CoerceType::Number).apply(&text).unwrap(),
+            CellData::Number(42.0)
+        );
+        assert_eq!(
+            Transform::Coerce(CoerceType::Boolean)
+                .apply(&CellData::Number(0.0))
+                .unwrap(),
+            CellData::Boolean(false)
+        );
+        assert!(Transform::Coerce(CoerceType::Number)
+            .apply(&CellData::Text("abc".to_string()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_python_transform() {
+        let transform = Transform::Python("lambda x: x.upper()".to_string());
+        assert!(transform.validate().is_ok());
+        assert_eq!(
+            transform.apply(&CellData::Text("abc".to_string())).unwrap(),
+            CellData::Text("ABC".to_string())
+        );
+        assert!(Transform::Python("x +".to_string()).validate().is_err());
+        assert!(Transform::Python("len".to_string()).validate().is_err());
+    }
+
+    #[test]
+    fn test_guards() {
+        let low = CellData::Number(3.0);
+        assert!(Guard::Truthy.evaluate(&low).unwrap());
+        assert!(!Guard::Falsy.evaluate(&low).unwrap());
+        assert!(Guard::Falsy.evaluate(&CellData::Text(String::new())).unwrap());
+
+        let over_ten = Guard::Compare {
+            op: CompareOp::Gt,
+            value: "10".to_string(),
+        };
+        assert!(!over_ten.evaluate(&low).unwrap());
+        assert!(over_ten.evaluate(&CellData::Text("12".to_string())).unwrap());
+
+        let is_ok = Guard::Compare {
+            op: CompareOp::Eq,
+            value: "ok".to_string(),
+        };
+        assert!(is_ok.evaluate(&CellData::Text("ok".to_string())).unwrap());
+
+        assert!(Guard::Math("x > 1 && x < 5".to_string()).evaluate(&low).unwrap());
+        assert!(Guard::Math("x == 3".to_string()).evaluate(&low).unwrap());
+        assert!(Guard::Math("x + 1".to_string()).validate().is_err());
+    }
+
+    #[test]
+    fn test_coerce_table() {
+        let to_table = Transform::Coerce(CoerceType::Table);
+        let rows = CellData::Json(serde_json::json!([{"n": 1}, {"n": null}]));
+        let CellData::Table(table) = to_table.apply(&rows).unwrap() else {
+            panic!("expected a table");
+        };
+        assert_eq!(table.schema(), vec![("n", crate::ColumnType::Number)]);
+
+        let csv = CellData::Text("n\n1\n\"\"\n".to_string());
+        assert_eq!(to_table.apply(&csv).unwrap(), CellData::Table(table));
+        assert!(to_table.apply(&CellData::Number(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_transform_serialization() {
+        let transform = Transform::Coerce(CoerceType::Json);
+        let json = serde_json::to_string(&transform).unwrap();
+        assert_eq!(serde_json::from_str::<Transform>(&json).unwrap(), transform);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
-    validation::{ValidatedCanvas, ValidationSeverity},
-    Canvas, CellContent, CellType, ExecutionEngine, ExecutionMode, Project, Rectangle,
-    SplitDirection,
+    validation::{ValidatedCanvas, ValidationSeverity, Validator},
+    Canvas, CellContent, CellType, ExecutionEngine, ExecutionMode, Parameters, Project,
+    PythonEnvironment, Rectangle, SplitDirection, WasmSandbox,
 };
 use anyhow::Result;
 use egui::{
@@ -9,7 +9,9 @@ use egui::{
     Vec2,
 };
 use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::PathBuf;
+use std::sync::Arc;
 use ulid::Ulid;
 
 /// Main application state
@@ -29,6 +31,18 @@ pub struct GraphCellEditorApp {
     /// Project path (if loaded)
     project_path: Option<PathBuf>,
 
+    /// Run parameters and secret declarations from the manifest
+    params: Parameters,
+
+    /// Python interpreter or virtual environment from the manifest
+    python_environment: Option<PythonEnvironment>,
+
+    /// SQLite database SQL cells query, from the manifest
+    sql_database: Option<PathBuf>,
+
+    /// Limits and capabilities of WASM cells, from the manifest
+    wasm_sandbox: WasmSandbox,
+
     /// UI state
     ui_state: UiState,
 
@@ -56,12 +70,27 @@ struct UiState {
     /// First cell selected for relationship creation
     relationship_source: Option<Ulid>,
 
+    /// Last cell of the subgraph run from the selected cell
+    subgraph_end: Option<Ulid>,
+
+    /// Entry point the toolbar runs from (None = start point)
+    entry_point: Option<String>,
+
+    /// Error policy for runs started from the UI
+    error_policy: crate::ErrorPolicy,
+
+    /// `key=value` being typed into the parameters panel
+    new_param: String,
+
     /// Show grid
     show_grid: bool,
 
     /// Show cell IDs
     show_cell_ids: bool,
 
+    /// Show captured stdout/stderr panels under executed cells
+    show_cell_output: bool,
+
     /// Cell being resized
     resizing_cell: Option<Ulid>,
 
@@ -89,10 +118,23 @@ struct UiState {
     /// Temporary content buffer for editing
     edit_buffer: String,
 
+    /// Schema text being edited per JSON cell, kept while it isn't valid JSON
+    schema_drafts: HashMap<Ulid, String>,
+
+    /// Parsed tables of CSV cells, with the content each was parsed from
+    csv_tables: HashMap<Ulid, (CellContent, Result<crate::Table, String>)>,
+
+    /// Images of Image cells (texture URI and bytes), with the content and a hash of the
+    /// output each was loaded from
+    images: HashMap<Ulid, CachedImage>,
+
     /// Default markdown preview mode for all cells
     default_preview_mode: crate::MarkdownPreviewMode,
 }
 
+/// Image cell source (content, output hash) and the image loaded from it
+type CachedImage = (CellContent, Option<u64>, Result<(String, Arc<[u8]>), String>);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ResizeHandle {
     TopLeft,
@@ -134,9 +176,14 @@ impl GraphCellEditorApp {
             canvas_offset: Vec2::ZERO,
             zoom: 1.0,
             project_path: None,
+            params: Parameters::new(),
+            python_environment: None,
+            sql_database: None,
+            wasm_sandbox: WasmSandbox::default(),
             ui_state: UiState {
                 show_grid: true,
                 show_cell_ids: false,
+                show_cell_output: true,
                 ..Default::default()
             },
             status_message: "Welcome to Graph Cell Editor!".to_string(),
@@ -157,9 +204,14 @@ impl GraphCellEditorApp {
             canvas_offset: Vec2::ZERO,
             zoom: 1.0,
             project_path: Some(project.root_dir().to_path_buf()),
+            params: manifest.params,
+            python_environment: manifest.python,
+            sql_database: manifest.sql_database,
+            wasm_sandbox: manifest.wasm,
             ui_state: UiState {
                 show_grid: true,
                 show_cell_ids: false,
+                show_cell_output: true,
                 ..Default::default()
             },
             status_message: format!("Loaded project from {}", project.root_dir().display()),
@@ -175,7 +227,10 @@ impl GraphCellEditorApp {
         if let Some(path) = &self.project_path {
             match Project::open(path) {
                 Ok(project) => {
-                    if let Err(e) = project.save(&self.canvas) {
+                    let saved = project
+                        .save(&self.canvas)
+                        .and_then(|_| project.save_params(&self.params));
+                    if let Err(e) = saved {
                         self.status_message = format!("❌ Save failed: {}", e);
                     } else {
                         self.status_message = "✓ Project saved".to_string();
@@ -192,7 +247,18 @@ impl GraphCellEditorApp {
 
     /// Validate the canvas and update validation state
     fn validate_canvas(&mut self) {
-        let result = self.canvas.validate();
+        let mut result = self
+            .canvas
+            .validate_with_dependencies(&self.execution_engine.implicit_dependencies());
+        // Type mismatches found by the last dry run
+        for issue in self.execution_engine.type_mismatches() {
+            result.add_issue(issue.clone());
+        }
+        if let (Some(environment), Some(root)) = (&self.python_environment, &self.project_path) {
+            for issue in Validator::check_python_environment(&self.canvas, environment, root) {
+                result.add_issue(issue);
+            }
+        }
         self.validation_issues = self.canvas.cells_with_issues(&result);
 
         let error_count = result.errors().len();
@@ -228,32 +294,133 @@ impl GraphCellEditorApp {
 
         self.execution_progress = Some(format!("Executing in {:?} mode...", mode));
 
-        // Create a new execution engine with the desired mode
+        let Some(params) = self.resolve_params() else {
+            return;
+        };
+
+        // Create a new execution engine with the desired mode, keeping the breakpoints
         let mut engine = ExecutionEngine::new(mode);
+        engine.set_error_policy(self.ui_state.error_policy);
+        engine.set_params(params);
+        engine.set_secrets(self.params.secrets.keys().cloned(), self.params.shell_secrets);
+        engine.set_python_interpreter(self.python_interpreter());
+        engine.set_project_dir(self.project_path.clone());
+        engine.set_sql_database(self.sql_database.clone());
+        engine.set_wasm_sandbox(self.wasm_sandbox);
+        for (cell_id, breakpoint) in self.execution_engine.breakpoints() {
+            engine.set_breakpoint(*cell_id, breakpoint.clone());
+        }
+        let result = match &self.ui_state.entry_point {
+            Some(name) => engine.execute_entry_point(&self.canvas, name),
+            None => engine.execute(&self.canvas),
+        };
 
-        match engine.execute(&self.canvas) {
+        // Keep the engine (and its log) even on failure so captured output stays visible
+        self.execution_engine = engine;
+        self.show_execution_result(result);
+    }
+
+    /// Run from a cell (or the subgraph between two cells), reusing cached upstream outputs
+    fn run_partial(&mut self, start: Ulid, end: Option<Ulid>) {
+        let Some(params) = self.resolve_params() else {
+            return;
+        };
+        self.execution_engine.set_mode(ExecutionMode::Run);
+        self.execution_engine.set_error_policy(self.ui_state.error_policy);
+        self.execution_engine.set_params(params);
+        self.execution_engine
+            .set_secrets(self.params.secrets.keys().cloned(), self.params.shell_secrets);
+        self.execution_engine.set_python_interpreter(self.python_interpreter());
+        self.execution_engine.set_project_dir(self.project_path.clone());
+        self.execution_engine.set_sql_database(self.sql_database.clone());
+        self.execution_engine.set_wasm_sandbox(self.wasm_sandbox);
+        let result = match end {
+            Some(end) => self.execution_engine.execute_subgraph(&self.canvas, start, end),
+            None => self.execution_engine.execute_from(&self.canvas, start),
+        };
+        self.show_execution_result(result);
+    }
+
+    /// Resolve run parameters, reporting a missing secret in the status bar
+    fn resolve_params(&mut self) -> Option<std::collections::BTreeMap<String, crate::CellData>> {
+        match self.params.resolve() {
+            Ok(params) => Some(params),
+            Err(e) => {
+                self.status_message = format!("❌ Cannot execute: {:#}", e);
+                None
+            }
+        }
+    }
+
+    /// Interpreter executable of the project's Python environment, if it declares one
+    fn python_interpreter(&self) -> Option<PathBuf> {
+        let root = self.project_path.as_deref()?;
+        Some(self.python_environment.as_ref()?.executable(root))
+    }
+
+    /// Resume a paused execution (step mode or breakpoint)
+    fn continue_canvas(&mut self) {
+        let result = self.execution_engine.continue_execution(&self.canvas);
+        self.show_execution_result(result);
+    }
+
+    /// Report the outcome of an execution in the status bar
+    fn show_execution_result(&mut self, result: Result<crate::ExecutionReport>) {
+        match result {
             Ok(report) => {
                 let status_msg = match report.status {
+                    crate::ExecutionStatus::Complete if !report.failed().is_empty() => {
+                        format!(
+                            "⚠ Execution completed with errors: {} succeeded, {} failed, {} skipped",
+                            report.succeeded().len(),
+                            report.failed().len(),
+                            report.skipped().len()
+                        )
+                    }
                     crate::ExecutionStatus::Complete => {
                         format!(
                             "✓ Execution completed: {} cells executed",
                             report.total_cells_executed
                         )
                     }
-                    crate::ExecutionStatus::Paused => {
-                        format!("⏸ Execution paused at step {}", report.step)
+                    crate::ExecutionStatus::Paused => match report.paused_at {
+                        Some(cell_id) => format!(
+                            "⏸ Breakpoint hit at cell {} (step {})",
+                            self.canvas
+                                .get_cell(cell_id)
+                                .map(|c| c.label())
+                                .unwrap_or_else(|| cell_id.to_string()),
+                            report.step
+                        ),
+                        None => format!("⏸ Execution paused at step {}", report.step),
+                    },
+                    crate::ExecutionStatus::DryRunComplete => {
+                        format!(
+                            "✓ Dry run completed: {} cells checked, {} unreachable branches, {} type mismatches",
+                            report.total_cells_executed,
+                            report.unreachable_branches.len(),
+                            report.type_mismatches.len()
+                        )
                     }
                     crate::ExecutionStatus::Error(ref e) => {
                         format!("❌ Execution error: {}", e)
                     }
+                    crate::ExecutionStatus::IterationLimitReached { to, max_iterations, .. } => {
+                        let name = self
+                            .canvas
+                            .get_cell(to)
+                            .map(|c| c.label())
+                            .unwrap_or_else(|| to.to_string());
+                        format!(
+                            "⚠ Loop at {} stopped after {} passes (iteration limit)",
+                            name, max_iterations
+                        )
+                    }
                     _ => "Execution status unknown".to_string(),
                 };
 
                 self.status_message = status_msg;
                 self.execution_progress = None;
-
-                // Store the engine for potential resume in Step mode
-                self.execution_engine = engine;
             }
             Err(e) => {
                 self.status_message = format!("❌ Execution error: {}", e);
@@ -286,6 +453,7 @@ impl GraphCellEditorApp {
                 ui.menu_button("View", |ui| {
                     ui.checkbox(&mut self.ui_state.show_grid, "Show Grid");
                     ui.checkbox(&mut self.ui_state.show_cell_ids, "Show Cell IDs");
+                    ui.checkbox(&mut self.ui_state.show_cell_output, "Show Cell Output");
                     ui.checkbox(&mut self.show_validation_panel, "Show Validation Panel");
                     if ui.button("Reset Zoom").clicked() {
                         self.zoom = 1.0;
@@ -361,6 +529,45 @@ impl GraphCellEditorApp {
                 ui.separator();
 
                 // Execution controls
+                let entry_points = self.canvas.entry_points();
+                if self
+                    .ui_state
+                    .entry_point
+                    .as_ref()
+                    .is_some_and(|name| !entry_points.iter().any(|(n, _)| n == name))
+                {
+                    self.ui_state.entry_point = None;
+                }
+                if !entry_points.is_empty() {
+                    egui::ComboBox::from_id_salt("entry_point")
+                        .selected_text(
+                            self.ui_state
+                                .entry_point
+                                .as_deref()
+                                .unwrap_or("Start point"),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.ui_state.entry_point, None, "Start point");
+                            for (name, _) in &entry_points {
+                                ui.selectable_value(
+                                    &mut self.ui_state.entry_point,
+                                    Some(name.clone()),
+                                    name,
+                                );
+                            }
+                        });
+                }
+                egui::ComboBox::from_id_salt("error_policy")
+                    .selected_text(format!("On error: {}", self.ui_state.error_policy.label()))
+                    .show_ui(ui, |ui| {
+                        for policy in crate::ErrorPolicy::ALL {
+                            ui.selectable_value(
+                                &mut self.ui_state.error_policy,
+                                policy,
+                                policy.label(),
+                            );
+                        }
+                    });
                 if ui.button("▶ Run").clicked() {
                     self.execute_canvas(ExecutionMode::Run);
                 }
@@ -370,6 +577,11 @@ impl GraphCellEditorApp {
                 if ui.button("🔍 Dry Run").clicked() {
                     self.execute_canvas(ExecutionMode::DryRun);
                 }
+                if matches!(self.execution_engine.status(), crate::ExecutionStatus::Paused)
+                    && ui.button("⏭ Continue").clicked()
+                {
+                    self.continue_canvas();
+                }
 
                 ui.separator();
 
@@ -405,6 +617,9 @@ impl GraphCellEditorApp {
                 });
         }
 
+        // Breakpoint inspector (while paused before a cell)
+        self.render_breakpoint_inspector(ctx);
+
         // Right panel (properties)
         egui::SidePanel::right("properties_panel")
             .default_width(300.0)
@@ -418,6 +633,54 @@ impl GraphCellEditorApp {
         });
     }
 
+    /// Show the inputs a paused cell is about to receive, with a button to resume
+    fn render_breakpoint_inspector(&mut self, ctx: &egui::Context) {
+        let Some((cell_id, inputs)) = self.execution_engine.paused_cell() else {
+            return;
+        };
+        let title = format!(
+            "⏸ Paused at {}",
+            self.canvas
+                .get_cell(cell_id)
+                .map(|c| c.label())
+                .unwrap_or_else(|| cell_id.to_string())
+        );
+        let sources: Vec<String> = self
+            .canvas
+            .get_incoming_relationships(cell_id)
+            .iter()
+            .map(|rel| {
+                self.canvas
+                    .get_cell(rel.from)
+                    .map(|c| c.label())
+                    .unwrap_or_else(|| rel.from.to_string())
+            })
+            .collect();
+        let inputs = inputs.to_vec();
+
+        let mut resume = false;
+        egui::Window::new(title)
+            .id(egui::Id::new("breakpoint_inspector"))
+            .resizable(true)
+            .show(ctx, |ui| {
+                if inputs.is_empty() {
+                    ui.label("No inputs");
+                }
+                for (i, value) in inputs.iter().enumerate() {
+                    let source = sources.get(i).map(String::as_str).unwrap_or("?");
+                    ui.monospace(format!("input_{} ← {}: {:?}", i, source, value));
+                }
+                ui.separator();
+                if ui.button("⏭ Continue").clicked() {
+                    resume = true;
+                }
+            });
+
+        if resume {
+            self.continue_canvas();
+        }
+    }
+
     /// Render the properties panel
     fn render_properties_panel(&mut self, ui: &mut egui::Ui) {
         ui.heading("Properties");
@@ -431,6 +694,8 @@ impl GraphCellEditorApp {
                     cell.cell_type,
                     cell.content.clone(),
                     cell.is_start_point,
+                    cell.entry_point.clone(),
+                    cell.error_policy,
                     cell.bounds,
                     cell.computed_result,
                     cell.result_target_cell,
@@ -442,6 +707,8 @@ impl GraphCellEditorApp {
                 cell_type_orig,
                 content,
                 is_start_orig,
+                entry_point_orig,
+                error_policy_orig,
                 bounds,
                 computed_result,
                 result_target_cell,
@@ -473,6 +740,15 @@ impl GraphCellEditorApp {
                         ui.selectable_value(&mut cell_type, CellType::NumberInt, "Integer");
                         ui.selectable_value(&mut cell_type, CellType::NumberFloat, "Float");
                         ui.selectable_value(&mut cell_type, CellType::NumberCurrency, "Currency");
+                        ui.selectable_value(&mut cell_type, CellType::Shell, "Shell");
+                        ui.selectable_value(&mut cell_type, CellType::Sql, "SQL");
+                        ui.selectable_value(&mut cell_type, CellType::Script, "Script (Rhai)");
+                        ui.selectable_value(&mut cell_type, CellType::Wasm, "WASM");
+                        ui.selectable_value(&mut cell_type, CellType::Json, "JSON");
+                        ui.selectable_value(&mut cell_type, CellType::Csv, "CSV");
+                        ui.selectable_value(&mut cell_type, CellType::Image, "Image");
+                        ui.selectable_value(&mut cell_type, CellType::Chart, "Chart");
+                        ui.selectable_value(&mut cell_type, CellType::Date, "Date");
                     });
 
                 if cell_type != cell_type_orig {
@@ -565,6 +841,7 @@ impl GraphCellEditorApp {
                                                     };
                                                     target.content = CellContent::inline(formatted);
                                                 }
+                                                crate::dates::store_day_number(target, result);
                                             }
                                         }
                                     }
@@ -623,6 +900,7 @@ impl GraphCellEditorApp {
                                                 };
                                                 target.content = CellContent::inline(formatted);
                                             }
+                                            crate::dates::store_day_number(target, result);
                                         }
                                     }
 
@@ -651,6 +929,104 @@ impl GraphCellEditorApp {
                     }
                 }
 
+                if matches!(cell_type, CellType::Date) {
+                    ui.separator();
+                    if let Some(cell_mut) = self.canvas.get_cell_mut(cell_id) {
+                        ui.label("Date Format:");
+                        ui.text_edit_singleline(&mut cell_mut.date_format);
+                        ui.label("Time Zone:");
+                        ui.text_edit_singleline(&mut cell_mut.time_zone);
+                        let checked = crate::dates::check_format(&cell_mut.date_format)
+                            .and_then(|_| crate::dates::parse_time_zone(&cell_mut.time_zone));
+                        if let Err(e) = checked {
+                            ui.colored_label(Color32::from_rgb(200, 0, 0), format!("⚠ {}", e));
+                        }
+                    }
+                }
+
+                if matches!(cell_type, CellType::Python) {
+                    ui.separator();
+                    if let Some(cell_mut) = self.canvas.get_cell_mut(cell_id) {
+                        ui.horizontal(|ui| {
+                            ui.label("Timeout (s):");
+                            let mut timeout = cell_mut
+                                .python_timeout_secs
+                                .unwrap_or(crate::python_env::DEFAULT_PYTHON_TIMEOUT_SECS);
+                            let drag = egui::DragValue::new(&mut timeout).range(1..=86_400);
+                            if ui.add(drag).changed() {
+                                cell_mut.python_timeout_secs = Some(timeout);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Only applies when the project names a Python interpreter");
+                    }
+                }
+
+                if matches!(cell_type, CellType::Shell) {
+                    ui.separator();
+                    if let Some(cell_mut) = self.canvas.get_cell_mut(cell_id) {
+                        ui.horizontal(|ui| {
+                            ui.label("Timeout (s):");
+                            let mut timeout = cell_mut
+                                .shell_timeout_secs
+                                .unwrap_or(crate::shell::DEFAULT_SHELL_TIMEOUT_SECS);
+                            let drag = egui::DragValue::new(&mut timeout).range(1..=86_400);
+                            if ui.add(drag).changed() {
+                                cell_mut.shell_timeout_secs = Some(timeout);
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Working dir:");
+                            let mut dir = cell_mut
+                                .working_dir
+                                .as_ref()
+                                .map(|dir| dir.display().to_string())
+                                .unwrap_or_default();
+                            let edit = egui::TextEdit::singleline(&mut dir)
+                                .hint_text("project directory");
+                            if ui.add(edit).changed() {
+                                let dir = dir.trim();
+                                cell_mut.working_dir =
+                                    (!dir.is_empty()).then(|| PathBuf::from(dir));
+                            }
+                        });
+                    }
+                }
+
+                if matches!(cell_type, CellType::Json) {
+                    ui.separator();
+                    ui.label("Schema:");
+                    if let Some(cell_mut) = self.canvas.get_cell_mut(cell_id) {
+                        let draft = self.ui_state.schema_drafts.entry(cell_id).or_insert_with(|| {
+                            cell_mut
+                                .json_schema
+                                .as_ref()
+                                .and_then(|schema| serde_json::to_string_pretty(schema).ok())
+                                .unwrap_or_default()
+                        });
+                        let edit = egui::TextEdit::multiline(draft)
+                            .font(FontId::monospace(12.0))
+                            .hint_text("no schema");
+                        if ui.add(edit).changed() {
+                            if draft.trim().is_empty() {
+                                cell_mut.json_schema = None;
+                            } else if let Ok(schema) = serde_json::from_str(draft) {
+                                cell_mut.json_schema = Some(schema);
+                            }
+                        }
+                        if !draft.trim().is_empty() {
+                            let checked = crate::schema::parse_json_content(draft)
+                                .and_then(|schema| crate::schema::check_schema(&schema));
+                            if let Err(e) = checked {
+                                ui.colored_label(
+                                    Color32::from_rgb(255, 110, 110),
+                                    format!("⚠ {}", e),
+                                );
+                            }
+                        }
+                    }
+                }
+
                 ui.separator();
 
                 // Content
@@ -707,6 +1083,7 @@ impl GraphCellEditorApp {
                                                         target.content =
                                                             CellContent::inline(formatted);
                                                     }
+                                                    crate::dates::store_day_number(target, result);
                                                 }
                                             }
                                         }
@@ -729,12 +1106,118 @@ impl GraphCellEditorApp {
 
                 // Start point checkbox
                 let mut is_start = is_start_orig;
-                if ui.checkbox(&mut is_start, "Start Point").changed() {
-                    if is_start {
-                        let _ = self.canvas.set_start_point(cell_id);
+                if ui.checkbox(&mut is_start, "Start Point").changed() && is_start {
+                    let _ = self.canvas.set_start_point(cell_id);
+                }
+
+                // Error policy override
+                let mut cell_policy = error_policy_orig;
+                egui::ComboBox::from_id_salt("cell_error_policy")
+                    .selected_text(format!(
+                        "On error: {}",
+                        cell_policy.map_or("Run default", |p| p.label())
+                    ))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut cell_policy, None, "Run default");
+                        for policy in crate::ErrorPolicy::ALL {
+                            ui.selectable_value(
+                                &mut cell_policy,
+                                Some(policy),
+                                policy.label(),
+                            );
+                        }
+                    });
+                if cell_policy != error_policy_orig {
+                    if let Some(cell) = self.canvas.get_cell_mut(cell_id) {
+                        cell.error_policy = cell_policy;
                     }
                 }
 
+                // Named entry point
+                ui.horizontal(|ui| {
+                    ui.label("Entry point:");
+                    let mut entry_name = entry_point_orig.clone().unwrap_or_default();
+                    if ui
+                        .add(egui::TextEdit::singleline(&mut entry_name).hint_text("e.g. train"))
+                        .changed()
+                    {
+                        let name = (!entry_name.trim().is_empty()).then_some(entry_name);
+                        if let Err(e) = self.canvas.set_entry_point(cell_id, name) {
+                            self.status_message = format!("❌ {}", e);
+                        }
+                    }
+                });
+
+                // Partial runs reuse the cached outputs of the previous run
+                if ui.button("▶ Run from here").clicked() {
+                    self.run_partial(cell_id, None);
+                }
+                let mut downstream: Vec<(Ulid, String)> = self
+                    .canvas
+                    .downstream_cells(cell_id)
+                    .into_iter()
+                    .filter(|id| *id != cell_id)
+                    .filter_map(|id| self.canvas.get_cell(id).map(|c| (id, c.label())))
+                    .collect();
+                downstream.sort_by(|a, b| a.1.cmp(&b.1));
+                if !downstream.is_empty() {
+                    let mut end = self
+                        .ui_state
+                        .subgraph_end
+                        .filter(|end| downstream.iter().any(|(id, _)| id == end));
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("subgraph_end")
+                            .selected_text(
+                                end.and_then(|end| self.canvas.get_cell(end))
+                                    .map(|c| c.label())
+                                    .unwrap_or_else(|| "Subgraph end…".to_string()),
+                            )
+                            .show_ui(ui, |ui| {
+                                for (id, label) in &downstream {
+                                    ui.selectable_value(&mut end, Some(*id), label);
+                                }
+                            });
+                        if let Some(end) = end {
+                            if ui.button("▶ Run subgraph").clicked() {
+                                self.run_partial(cell_id, Some(end));
+                            }
+                        }
+                    });
+                    self.ui_state.subgraph_end = end;
+                }
+
+                // Breakpoint (pauses run mode before this cell executes)
+                let breakpoint = self.execution_engine.breakpoints().get(&cell_id).cloned();
+                let mut has_breakpoint = breakpoint.is_some();
+                if ui.checkbox(&mut has_breakpoint, "Breakpoint").changed() {
+                    if has_breakpoint {
+                        self.execution_engine
+                            .set_breakpoint(cell_id, crate::Breakpoint::new());
+                    } else {
+                        self.execution_engine.remove_breakpoint(cell_id);
+                    }
+                }
+                if let Some(breakpoint) = breakpoint {
+                    ui.horizontal(|ui| {
+                        ui.label("Pause if:");
+                        let mut condition = breakpoint.condition.unwrap_or_default();
+                        if ui
+                            .add(
+                                egui::TextEdit::singleline(&mut condition)
+                                    .hint_text("always, or e.g. input_0 > 10"),
+                            )
+                            .changed()
+                        {
+                            let breakpoint = if condition.trim().is_empty() {
+                                crate::Breakpoint::new()
+                            } else {
+                                crate::Breakpoint::conditional(condition)
+                            };
+                            self.execution_engine.set_breakpoint(cell_id, breakpoint);
+                        }
+                    });
+                }
+
                 ui.separator();
 
                 // Preview Mode Override
@@ -813,8 +1296,20 @@ impl GraphCellEditorApp {
                 }
 
                 // Relationships
+                let port_issues = crate::validation::Validator::check_ports(&self.canvas);
+                let mut port_edit = None;
+                let mut input_move = None;
+                let mut transform_edit = None;
+                let mut guard_edit = None;
+                let mut loop_edit = None;
+
                 ui.label("Outgoing Relationships:");
-                let outgoing = self.canvas.get_outgoing_relationships(cell_id);
+                let outgoing: Vec<_> = self
+                    .canvas
+                    .get_outgoing_relationships(cell_id)
+                    .into_iter()
+                    .cloned()
+                    .collect();
                 if outgoing.is_empty() {
                     ui.label("  (none)");
                 } else {
@@ -823,30 +1318,346 @@ impl GraphCellEditorApp {
                             let target_name =
                                 target.name.clone().unwrap_or_else(|| format!("{}", rel.to));
                             ui.label(format!("  → {}", target_name));
+                            ui.horizontal(|ui| {
+                                ui.label("    output key:");
+                                let mut key = rel.source_key.clone().unwrap_or_default();
+                                if ui
+                                    .add(egui::TextEdit::singleline(&mut key).hint_text("result"))
+                                    .changed()
+                                {
+                                    let key = Some(key).filter(|k| !k.is_empty());
+                                    port_edit =
+                                        Some((rel.from, rel.to, key, rel.target_input.clone()));
+                                }
+                            });
+                            if let Some(transform) = Self::transform_editor(ui, &rel) {
+                                transform_edit = Some((rel.from, rel.to, transform));
+                            }
+                            let id_salt = (rel.from, rel.to);
+                            if let Some(guard) =
+                                Self::guard_editor(ui, "    condition:", id_salt, &rel.guard)
+                            {
+                                guard_edit = Some((rel.from, rel.to, guard));
+                            }
+                            if let Some(loop_config) = Self::loop_editor(ui, &rel) {
+                                loop_edit = Some((rel.from, rel.to, loop_config));
+                            }
+                            Self::show_port_issues(ui, &port_issues, &rel);
                         }
                     }
                 }
 
                 ui.label("Incoming Relationships:");
-                let incoming = self.canvas.get_incoming_relationships(cell_id);
+                let incoming: Vec<_> = self
+                    .canvas
+                    .get_incoming_relationships(cell_id)
+                    .into_iter()
+                    .cloned()
+                    .collect();
                 if incoming.is_empty() {
                     ui.label("  (none)");
                 } else {
-                    for rel in incoming {
+                    let input_count = incoming.len();
+                    for (index, rel) in incoming.into_iter().enumerate() {
                         if let Some(source) = self.canvas.get_cell(rel.from) {
                             let source_name = source
                                 .name
                                 .clone()
                                 .unwrap_or_else(|| format!("{}", rel.from));
-                            ui.label(format!("  ← {}", source_name));
+                            ui.horizontal(|ui| {
+                                ui.label(format!("  input_{} ← {}", index, source_name));
+                                if ui
+                                    .add_enabled(index > 0, egui::Button::new("⬆").small())
+                                    .on_hover_text("Move input up")
+                                    .clicked()
+                                {
+                                    input_move = Some((rel.from, index - 1));
+                                }
+                                if ui
+                                    .add_enabled(
+                                        index + 1 < input_count,
+                                        egui::Button::new("⬇").small(),
+                                    )
+                                    .on_hover_text("Move input down")
+                                    .clicked()
+                                {
+                                    input_move = Some((rel.from, index + 1));
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("    input name:");
+                                let mut name = rel.target_input.clone().unwrap_or_default();
+                                if ui
+                                    .add(egui::TextEdit::singleline(&mut name).hint_text("input_N"))
+                                    .changed()
+                                {
+                                    let name = Some(name).filter(|n| !n.is_empty());
+                                    port_edit =
+                                        Some((rel.from, rel.to, rel.source_key.clone(), name));
+                                }
+                            });
+                            Self::show_port_issues(ui, &port_issues, &rel);
                         }
                     }
                 }
+
+                if let Some((from, to, transform)) = transform_edit {
+                    if let Err(e) = self.canvas.set_relationship_transform(from, to, transform) {
+                        self.status_message = format!("⚠ {}", e);
+                    }
+                }
+
+                if let Some((from, to, guard)) = guard_edit {
+                    if let Err(e) = self.canvas.set_relationship_guard(from, to, guard) {
+                        self.status_message = format!("⚠ {}", e);
+                    }
+                }
+
+                if let Some((from, to, loop_config)) = loop_edit {
+                    if let Err(e) = self.canvas.set_relationship_loop(from, to, loop_config) {
+                        self.status_message = format!("⚠ {}", e);
+                    }
+                }
+
+                if let Some((from, new_index)) = input_move {
+                    if let Err(e) = self.canvas.move_input(cell_id, from, new_index) {
+                        self.status_message = format!("⚠ {}", e);
+                    }
+                }
+
+                if let Some((from, to, source_key, target_input)) = port_edit {
+                    if let Err(e) =
+                        self.canvas.set_relationship_ports(from, to, source_key, target_input)
+                    {
+                        self.status_message = format!("⚠ {}", e);
+                    }
+                }
             }
         } else {
             ui.label("No cell selected");
             ui.separator();
             ui.label("Click on a cell to view its properties");
+            ui.separator();
+            self.render_parameters(ui);
+        }
+    }
+
+    /// List run parameters and secrets, with a `key=value` field to add them
+    fn render_parameters(&mut self, ui: &mut egui::Ui) {
+        ui.label("Run parameters:");
+        let mut removed = None;
+        for (name, value) in &self.params.parameters {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("{} = {}", name, value));
+                if ui.small_button("🗑").clicked() {
+                    removed = Some(name.clone());
+                }
+            });
+        }
+        for (name, env_var) in &self.params.secrets {
+            ui.horizontal(|ui| {
+                let set = if std::env::var_os(env_var).is_some() { "✓" } else { "✗" };
+                ui.monospace(format!("{} ← ${} {}", name, env_var, set))
+                    .on_hover_text("Secret: read from the environment, never saved");
+                if ui.small_button("🗑").clicked() {
+                    removed = Some(name.clone());
+                }
+            });
+        }
+        if let Some(name) = removed {
+            self.params.remove(&name);
+        }
+
+        ui.add(egui::TextEdit::singleline(&mut self.ui_state.new_param).hint_text("key=value"));
+        ui.horizontal(|ui| {
+            let added = if ui.button("Set parameter").clicked() {
+                Some(self.params.apply_override(&self.ui_state.new_param))
+            } else if ui
+                .button("Set secret")
+                .on_hover_text("key=ENV_VAR: the value is read from that environment variable")
+                .clicked()
+            {
+                Some(match self.ui_state.new_param.split_once('=') {
+                    Some((name, env_var)) => self.params.set_secret(name.trim(), env_var.trim()),
+                    None => Err(anyhow::anyhow!("Expected key=ENV_VAR")),
+                })
+            } else {
+                None
+            };
+            match added {
+                Some(Ok(())) => self.ui_state.new_param.clear(),
+                Some(Err(e)) => self.status_message = format!("❌ {}", e),
+                None => {}
+            }
+        });
+    }
+
+    /// Edit the transform on a relationship; returns the new transform when it changes
+    fn transform_editor(
+        ui: &mut egui::Ui,
+        rel: &crate::Relationship,
+    ) -> Option<Option<crate::Transform>> {
+        use crate::{CoerceType, Transform};
+
+        let mut edited = rel.transform.clone();
+        ui.horizontal(|ui| {
+            ui.label("    transform:");
+            let current = edited.as_ref().map(|t| t.kind_name()).unwrap_or("None");
+            egui::ComboBox::from_id_salt(("transform", rel.from, rel.to))
+                .selected_text(current)
+                .show_ui(ui, |ui| {
+                    let options = [
+                        None,
+                        Some(Transform::Math("x".to_string())),
+                        Some(Transform::JsonPath("$".to_string())),
+                        Some(Transform::Coerce(CoerceType::Text)),
+                        Some(Transform::Python("lambda x: x".to_string())),
+                    ];
+                    for option in options {
+                        let name = option.as_ref().map(|t| t.kind_name()).unwrap_or("None");
+                        if ui.selectable_label(current == name, name).clicked() && current != name
+                        {
+                            edited = option;
+                        }
+                    }
+                });
+
+            match &mut edited {
+                Some(Transform::Math(text))
+                | Some(Transform::JsonPath(text))
+                | Some(Transform::Python(text)) => {
+                    ui.text_edit_singleline(text);
+                }
+                Some(Transform::Coerce(target)) => {
+                    egui::ComboBox::from_id_salt(("coerce", rel.from, rel.to))
+                        .selected_text(format!("{:?}", target))
+                        .show_ui(ui, |ui| {
+                            for option in [
+                                CoerceType::Text,
+                                CoerceType::Number,
+                                CoerceType::Boolean,
+                                CoerceType::Json,
+                                CoerceType::Table,
+                            ] {
+                                ui.selectable_value(target, option, format!("{:?}", option));
+                            }
+                        });
+                }
+                None => {}
+            }
+        });
+
+        if let Some(Err(e)) = edited.as_ref().map(|t| t.validate()) {
+            ui.colored_label(Color32::from_rgb(255, 110, 110), format!("    ⚠ {}", e));
+        }
+
+        (edited != rel.transform).then_some(edited)
+    }
+
+    /// Edit a relationship condition (guard or loop exit); returns the new one when it changes
+    fn guard_editor(
+        ui: &mut egui::Ui,
+        label: &str,
+        id_salt: impl std::hash::Hash + Copy,
+        guard: &Option<crate::Guard>,
+    ) -> Option<Option<crate::Guard>> {
+        use crate::{CompareOp, Guard};
+
+        let mut edited = guard.clone();
+        ui.horizontal(|ui| {
+            ui.label(label);
+            let current = edited.as_ref().map(|g| g.kind_name()).unwrap_or("Always");
+            egui::ComboBox::from_id_salt(("guard", id_salt))
+                .selected_text(current)
+                .show_ui(ui, |ui| {
+                    let options = [
+                        None,
+                        Some(Guard::Truthy),
+                        Some(Guard::Falsy),
+                        Some(Guard::Compare {
+                            op: CompareOp::Eq,
+                            value: String::new(),
+                        }),
+                        Some(Guard::Math("x > 0".to_string())),
+                    ];
+                    for option in options {
+                        let name = option.as_ref().map(|g| g.kind_name()).unwrap_or("Always");
+                        if ui.selectable_label(current == name, name).clicked() && current != name
+                        {
+                            edited = option;
+                        }
+                    }
+                });
+
+            match &mut edited {
+                Some(Guard::Compare { op, value }) => {
+                    egui::ComboBox::from_id_salt(("guard_op", id_salt))
+                        .width(40.0)
+                        .selected_text(op.symbol())
+                        .show_ui(ui, |ui| {
+                            for option in CompareOp::ALL {
+                                ui.selectable_value(op, option, option.symbol());
+                            }
+                        });
+                    ui.text_edit_singleline(value);
+                }
+                Some(Guard::Math(expr)) => {
+                    ui.text_edit_singleline(expr);
+                }
+                _ => {}
+            }
+        });
+
+        if let Some(Err(e)) = edited.as_ref().map(|g| g.validate()) {
+            ui.colored_label(Color32::from_rgb(255, 110, 110), format!("    ⚠ {}", e));
+        }
+
+        (edited != *guard).then_some(edited)
+    }
+
+    /// Edit the loop marking of a relationship; returns the new config when it changes
+    fn loop_editor(
+        ui: &mut egui::Ui,
+        rel: &crate::Relationship,
+    ) -> Option<Option<crate::LoopConfig>> {
+        let mut edited = rel.loop_config.clone();
+        ui.horizontal(|ui| {
+            let mut is_loop = edited.is_some();
+            if ui
+                .checkbox(&mut is_loop, "loop back edge")
+                .on_hover_text("Following this relationship starts the next pass of a loop")
+                .changed()
+            {
+                edited = is_loop.then(|| crate::LoopConfig::new(10));
+            }
+            if let Some(config) = &mut edited {
+                ui.label("max passes:");
+                ui.add(egui::DragValue::new(&mut config.max_iterations).range(1..=100_000));
+            }
+        });
+        if let Some(config) = &mut edited {
+            let id_salt = ("loop_exit", rel.from, rel.to);
+            if let Some(exit) = Self::guard_editor(ui, "    exit when:", id_salt, &config.exit_when) {
+                config.exit_when = exit;
+            }
+        }
+
+        (edited != rel.loop_config).then_some(edited)
+    }
+
+    /// Show port mismatches reported for a relationship
+    fn show_port_issues(
+        ui: &mut egui::Ui,
+        issues: &[crate::validation::ValidationIssue],
+        rel: &crate::Relationship,
+    ) {
+        for issue in issues {
+            if issue.affected_cells.contains(&rel.from) && issue.affected_cells.contains(&rel.to) {
+                ui.colored_label(
+                    Color32::from_rgb(230, 140, 0),
+                    format!("    ⚠ {}", issue.message),
+                );
+            }
         }
     }
 
@@ -1038,6 +1849,13 @@ impl GraphCellEditorApp {
             self.draw_cell_content(ui, canvas_rect, cell);
         }
 
+        // Draw captured console output under executed cells
+        if self.ui_state.show_cell_output {
+            for cell in &cells {
+                self.draw_cell_output(ui, canvas_rect, cell);
+            }
+        }
+
         // Draw resize handles for selected cell
         if let Some(selected_id) = self.selected_cell {
             if let Some(cell) = cells.iter().find(|c| c.id == selected_id) {
@@ -1048,7 +1866,7 @@ impl GraphCellEditorApp {
         // Draw all relationships
         let relationships: Vec<_> = self.canvas.relationships().values().cloned().collect();
         for rel in &relationships {
-            self.draw_relationship(&painter, canvas_rect, &rel);
+            self.draw_relationship(&painter, canvas_rect, rel);
         }
 
         // Draw inline editor overlay if editing a cell
@@ -1240,6 +2058,15 @@ impl GraphCellEditorApp {
                 CellType::NumberInt => Color32::from_rgb(220, 255, 220),
                 CellType::NumberFloat => Color32::from_rgb(220, 255, 255),
                 CellType::NumberCurrency => Color32::from_rgb(255, 255, 220),
+                CellType::Shell => Color32::from_rgb(235, 230, 250),
+                CellType::Sql => Color32::from_rgb(255, 235, 215),
+                CellType::Script => Color32::from_rgb(245, 225, 235),
+                CellType::Wasm => Color32::from_rgb(225, 225, 245),
+                CellType::Json => Color32::from_rgb(235, 245, 225),
+                CellType::Csv => Color32::from_rgb(225, 240, 240),
+                CellType::Image => Color32::from_rgb(240, 240, 240),
+                CellType::Chart => Color32::from_rgb(250, 245, 230),
+                CellType::Date => Color32::from_rgb(255, 240, 225),
             };
             (fill, Color32::DARK_GRAY, 2.0)
         };
@@ -1257,6 +2084,17 @@ impl GraphCellEditorApp {
             stroke_color
         };
 
+        // Highlight the cell execution is paused at
+        let paused_here = self
+            .execution_engine
+            .paused_cell()
+            .is_some_and(|(id, _)| id == cell.id);
+        let (final_stroke_color, stroke_width) = if paused_here {
+            (Color32::from_rgb(200, 0, 0), 4.0)
+        } else {
+            (final_stroke_color, stroke_width)
+        };
+
         // Draw cell rectangle
         painter.rect(
             screen_rect,
@@ -1304,6 +2142,26 @@ impl GraphCellEditorApp {
                 Color32::from_rgb(255, 140, 0),
             );
         }
+
+        // Draw entry point name in the lower left corner
+        if let Some(entry_point) = &cell.entry_point {
+            painter.text(
+                screen_rect.left_bottom() + vec2(5.0, -5.0),
+                Align2::LEFT_BOTTOM,
+                format!("⏵ {}", entry_point),
+                FontId::proportional(12.0),
+                Color32::from_rgb(255, 140, 0),
+            );
+        }
+
+        // Draw breakpoint marker (red dot in the left margin of the header)
+        if self.execution_engine.breakpoints().contains_key(&cell.id) {
+            painter.circle_filled(
+                screen_rect.left_top() + vec2(-6.0, 12.0),
+                5.0,
+                Color32::from_rgb(200, 0, 0),
+            );
+        }
     }
 
     /// Draw cell content with scrollable UI widget
@@ -1398,6 +2256,98 @@ impl GraphCellEditorApp {
                     }
                 }
             }
+            CellType::Date => {
+                // The latest output (a date received), else the content, in the cell's format;
+                // content that doesn't parse is shown as text below
+                let output = self
+                    .execution_engine
+                    .log()
+                    .iter()
+                    .rev()
+                    .find(|e| e.cell_id == cell.id)
+                    .and_then(|e| match &e.output {
+                        crate::CellData::DateTime(value) => Some(*value),
+                        _ => None,
+                    });
+                let formatted = output
+                    .map_or_else(|| crate::dates::cell_datetime(cell), Ok)
+                    .and_then(|value| crate::dates::format_for_cell(value, cell));
+                if let Ok(formatted) = formatted {
+                    let mut child_ui = ui.new_child(
+                        egui::UiBuilder::new()
+                            .max_rect(content_rect)
+                            .layout(egui::Layout::top_down(egui::Align::LEFT)),
+                    );
+
+                    egui::ScrollArea::both()
+                        .id_salt(cell.id)
+                        .auto_shrink([false, false])
+                        .show(&mut child_ui, |ui| {
+                            ui.label(
+                                egui::RichText::new(formatted)
+                                    .size(14.0)
+                                    .family(egui::FontFamily::Monospace),
+                            );
+                        });
+                    return;
+                }
+            }
+            CellType::Json => {
+                // Collapsible tree; content that doesn't parse is shown as text below
+                let parsed = cell.content.as_str().map(crate::schema::parse_json_content);
+                if let Some(Ok(value)) = parsed {
+                    let mut child_ui = ui.new_child(
+                        egui::UiBuilder::new()
+                            .max_rect(content_rect)
+                            .layout(egui::Layout::top_down(egui::Align::LEFT)),
+                    );
+
+                    egui::ScrollArea::both()
+                        .id_salt(cell.id)
+                        .auto_shrink([false, false])
+                        .show(&mut child_ui, |ui| {
+                            Self::json_tree(ui, "$", &value, (cell.id, String::new()));
+                        });
+                    return;
+                }
+            }
+            CellType::Csv => {
+                // Parsed once per content change; external files may be large
+                let parsed = self
+                    .ui_state
+                    .csv_tables
+                    .get(&cell.id)
+                    .is_some_and(|(content, _)| *content == cell.content);
+                if !parsed {
+                    let table =
+                        crate::table::read_csv_content(&cell.content, self.project_path.as_deref())
+                            .map_err(|e| e.to_string());
+                    self.ui_state
+                        .csv_tables
+                        .insert(cell.id, (cell.content.clone(), table));
+                }
+
+                let mut child_ui = ui.new_child(
+                    egui::UiBuilder::new()
+                        .max_rect(content_rect)
+                        .layout(egui::Layout::top_down(egui::Align::LEFT)),
+                );
+                match &self.ui_state.csv_tables[&cell.id].1 {
+                    Ok(table) => Self::table_grid(&mut child_ui, cell.id, table),
+                    Err(e) => {
+                        child_ui.colored_label(Color32::from_rgb(200, 0, 0), format!("⚠ {}", e));
+                    }
+                }
+                return;
+            }
+            CellType::Image => {
+                self.draw_image(ui, content_rect, cell);
+                return;
+            }
+            CellType::Chart => {
+                self.draw_chart(ui, content_rect, cell);
+                return;
+            }
             _ => {}
         }
 
@@ -1500,6 +2450,308 @@ impl GraphCellEditorApp {
         }
     }
 
+    /// Show an Image cell's latest output (an upstream image passed through), else the
+    /// image in its content
+    fn draw_image(&mut self, ui: &mut egui::Ui, content_rect: Rect, cell: &crate::Cell) {
+        fn hash(bytes: &[u8]) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            bytes.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let output = self
+            .execution_engine
+            .log()
+            .iter()
+            .rev()
+            .find(|e| e.cell_id == cell.id)
+            .and_then(|e| match &e.output {
+                crate::CellData::Binary(bytes) => Some(bytes),
+                _ => None,
+            });
+        let output_hash = output.map(|bytes| hash(bytes));
+        let loaded = self
+            .ui_state
+            .images
+            .get(&cell.id)
+            .is_some_and(|(content, hash, _)| *content == cell.content && *hash == output_hash);
+        if !loaded {
+            let bytes = match output {
+                Some(bytes) => Ok(bytes.clone()),
+                None => crate::images::read_image_content(
+                    &cell.content,
+                    self.project_path.as_deref(),
+                ),
+            };
+            // Textures are cached by URI, so it changes with the bytes
+            let image = bytes
+                .and_then(|bytes| {
+                    let format = crate::images::ensure_image(&bytes)?;
+                    let uri =
+                        format!("bytes://{}-{:x}.{}", cell.id, hash(&bytes), format.extension());
+                    Ok((uri, Arc::from(bytes)))
+                })
+                .map_err(|e| e.to_string());
+            self.ui_state
+                .images
+                .insert(cell.id, (cell.content.clone(), output_hash, image));
+        }
+
+        let mut child_ui = ui.new_child(
+            egui::UiBuilder::new()
+                .max_rect(content_rect)
+                .layout(egui::Layout::centered_and_justified(egui::Direction::TopDown)),
+        );
+        match &self.ui_state.images[&cell.id].2 {
+            Ok((uri, bytes)) => {
+                let source = egui::load::Bytes::Shared(bytes.clone());
+                child_ui.add(egui::Image::from_bytes(uri.clone(), source).shrink_to_fit());
+            }
+            Err(_) if cell.content.is_empty() => {
+                child_ui.label(egui::RichText::new("No image").color(Color32::GRAY));
+            }
+            Err(e) => {
+                child_ui.colored_label(Color32::from_rgb(200, 0, 0), format!("⚠ {}", e));
+            }
+        }
+    }
+
+    /// Plot a Chart cell's latest output in the cell with egui_plot
+    fn draw_chart(&self, ui: &mut egui::Ui, content_rect: Rect, cell: &crate::Cell) {
+        use crate::chart::{min_spacing, series_color, ChartKind};
+        use egui_plot::{Bar, BarChart, Legend, Line, Plot, Points};
+
+        let chart = self
+            .execution_engine
+            .log()
+            .iter()
+            .rev()
+            .find(|e| e.cell_id == cell.id)
+            .and_then(|e| match &e.output {
+                crate::CellData::Json(value) => {
+                    serde_json::from_value::<crate::Chart>(value.clone()).ok()
+                }
+                _ => None,
+            });
+        let painter = ui.painter_at(content_rect);
+        let Some(chart) = chart else {
+            painter.text(
+                content_rect.center(),
+                Align2::CENTER_CENTER,
+                "Run to draw the chart",
+                FontId::proportional(12.0),
+                Color32::GRAY,
+            );
+            return;
+        };
+
+        painter.rect_filled(content_rect, 0.0, Color32::WHITE);
+        let mut child_ui = ui.new_child(
+            egui::UiBuilder::new()
+                .max_rect(content_rect)
+                .layout(egui::Layout::top_down(egui::Align::Center)),
+        );
+        if let Some(title) = &chart.title {
+            child_ui.label(egui::RichText::new(title).size(11.0).color(Color32::BLACK));
+        }
+
+        // The plot only senses hover, so the cell can still be dragged by its chart
+        let mut plot = Plot::new((cell.id, "chart"))
+            .sense(Sense::hover())
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .allow_double_click_reset(false);
+        if chart.series.len() > 1 {
+            plot = plot.legend(Legend::default());
+        }
+        if !chart.categories.is_empty() {
+            // Label whole positions with their category, and nothing in between
+            let categories = chart.categories.clone();
+            plot = plot.x_axis_formatter(move |mark, _| {
+                let index = mark.value.round();
+                match categories.get(index as usize) {
+                    Some(category) if index >= 0.0 && mark.value == index => category.clone(),
+                    _ => String::new(),
+                }
+            });
+        }
+
+        let rgb = |[r, g, b]: [u8; 3]| Color32::from_rgb(r, g, b);
+        let bar_width = min_spacing(&chart) * 0.8 / chart.series.len().max(1) as f64;
+        plot.show(&mut child_ui, |plot_ui| {
+            for (index, series) in chart.series.iter().enumerate() {
+                let color = rgb(series_color(index));
+                let points = series.points.clone();
+                match chart.kind {
+                    ChartKind::Line => {
+                        plot_ui.line(Line::new(points).name(&series.name).color(color).width(2.0))
+                    }
+                    ChartKind::Scatter => plot_ui
+                        .points(Points::new(points).name(&series.name).color(color).radius(3.0)),
+                    // Series sit side by side within each slot
+                    ChartKind::Bar => {
+                        let offset = (index as f64 - (chart.series.len() as f64 - 1.0) / 2.0)
+                            * bar_width;
+                        let bars = points
+                            .iter()
+                            .map(|[x, y]| Bar::new(x + offset, *y).width(bar_width))
+                            .collect();
+                        plot_ui.bar_chart(BarChart::new(bars).name(&series.name).color(color));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Show a table as a grid, laying out only the rows scrolled into view
+    fn table_grid(ui: &mut egui::Ui, id: Ulid, table: &crate::Table) {
+        const COLUMN_WIDTH: f32 = 80.0;
+        const ROW_HEIGHT: f32 = 16.0;
+
+        let field = |ui: &mut egui::Ui, text: egui::RichText| {
+            ui.add_sized(
+                [COLUMN_WIDTH, ROW_HEIGHT],
+                egui::Label::new(text.size(12.0).family(egui::FontFamily::Monospace)).truncate(),
+            );
+        };
+
+        egui::ScrollArea::horizontal()
+            .id_salt((id, "columns"))
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for column in table.columns() {
+                        field(ui, egui::RichText::new(&column.name).strong());
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .id_salt((id, "rows"))
+                    .auto_shrink([true, false])
+                    .show_rows(ui, ROW_HEIGHT, table.row_count(), |ui, rows| {
+                        for row in rows {
+                            ui.horizontal(|ui| {
+                                for column in table.columns() {
+                                    let text = match column.values.json_value(row) {
+                                        serde_json::Value::Null => String::new(),
+                                        serde_json::Value::String(s) => s,
+                                        other => other.to_string(),
+                                    };
+                                    field(ui, egui::RichText::new(text));
+                                }
+                            });
+                        }
+                    });
+            });
+    }
+
+    /// Show a JSON value as a tree: objects and arrays collapse, scalars are labels
+    ///
+    /// `path` (the cell and the value's JSON pointer) keeps each node's open state apart.
+    fn json_tree(ui: &mut egui::Ui, key: &str, value: &serde_json::Value, path: (Ulid, String)) {
+        let children: Vec<(String, &serde_json::Value)> = match value {
+            serde_json::Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+            serde_json::Value::Array(items) => {
+                items.iter().enumerate().map(|(i, v)| (i.to_string(), v)).collect()
+            }
+            scalar => {
+                ui.label(
+                    egui::RichText::new(format!("{}: {}", key, scalar))
+                        .size(12.0)
+                        .family(egui::FontFamily::Monospace),
+                );
+                return;
+            }
+        };
+
+        let summary = match value {
+            serde_json::Value::Object(_) => format!("{} {{{}}}", key, children.len()),
+            _ => format!("{} [{}]", key, children.len()),
+        };
+        egui::CollapsingHeader::new(egui::RichText::new(summary).size(12.0))
+            .id_salt(&path)
+            .default_open(path.1.is_empty())
+            .show(ui, |ui| {
+                for (child_key, child) in children {
+                    let escaped = child_key.replace('~', "~0").replace('/', "~1");
+                    let pointer = format!("{}/{}", path.1, escaped);
+                    Self::json_tree(ui, &child_key, child, (path.0, pointer));
+                }
+            });
+    }
+
+    /// Draw the stdout/stderr/traceback panel under a cell from its latest log entry
+    fn draw_cell_output(&self, ui: &mut egui::Ui, canvas_rect: Rect, cell: &crate::Cell) {
+        let entry = match self
+            .execution_engine
+            .log()
+            .iter()
+            .rev()
+            .find(|e| e.cell_id == cell.id)
+        {
+            Some(e) if e.has_console_output() => e,
+            _ => return,
+        };
+
+        let screen_rect = self.canvas_to_screen_rect(&cell.bounds, canvas_rect);
+        let line_count = entry.stdout.lines().count()
+            + entry.stderr.lines().count()
+            + entry
+                .traceback
+                .as_ref()
+                .map(|tb| tb.to_string().lines().count())
+                .unwrap_or(0);
+        let height = (line_count as f32 * 14.0 + 10.0).clamp(24.0, 120.0);
+        let panel_rect = Rect::from_min_size(
+            screen_rect.left_bottom() + vec2(0.0, 4.0),
+            vec2(screen_rect.width(), height),
+        );
+
+        ui.painter()
+            .rect_filled(panel_rect, 4.0, Color32::from_rgb(30, 30, 30));
+
+        let mut child_ui = ui.new_child(
+            egui::UiBuilder::new()
+                .max_rect(panel_rect.shrink(4.0))
+                .layout(egui::Layout::top_down(egui::Align::LEFT)),
+        );
+
+        egui::ScrollArea::both()
+            .id_salt(("cell_output", cell.id))
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show(&mut child_ui, |ui| {
+                for line in entry.stdout.lines() {
+                    ui.label(
+                        egui::RichText::new(line)
+                            .size(11.0)
+                            .family(egui::FontFamily::Monospace)
+                            .color(Color32::from_gray(220)),
+                    );
+                }
+                for line in entry.stderr.lines() {
+                    ui.label(
+                        egui::RichText::new(line)
+                            .size(11.0)
+                            .family(egui::FontFamily::Monospace)
+                            .color(Color32::from_rgb(255, 180, 80)),
+                    );
+                }
+                if let Some(traceback) = &entry.traceback {
+                    for line in traceback.to_string().lines() {
+                        ui.label(
+                            egui::RichText::new(line)
+                                .size(11.0)
+                                .family(egui::FontFamily::Monospace)
+                                .color(Color32::from_rgb(255, 110, 110)),
+                        );
+                    }
+                }
+            });
+    }
+
     /// Draw a relationship arrow
     fn draw_relationship(
         &self,
@@ -1522,8 +2774,12 @@ impl GraphCellEditorApp {
         let from_center = from_rect.center();
         let to_center = to_rect.center();
 
-        // Draw arrow
-        let arrow_color = Color32::from_rgb(0, 100, 200);
+        // Draw arrow (greyed out if the last run skipped this branch)
+        let arrow_color = if self.execution_engine.was_skipped(rel.from, rel.to) {
+            Color32::from_gray(170)
+        } else {
+            Color32::from_rgb(0, 100, 200)
+        };
         let stroke = Stroke::new(2.0, arrow_color);
 
         painter.line_segment([from_center, to_center], stroke);
@@ -1543,6 +2799,37 @@ impl GraphCellEditorApp {
             stroke,
         );
         painter.add(arrow_shape);
+
+        // Port and transform label at the midpoint
+        let mut labels = Vec::new();
+        if rel.source_key.is_some() || rel.target_input.is_some() {
+            labels.push(format!(
+                "{} → {}",
+                rel.source_key.as_deref().unwrap_or("result"),
+                rel.target_input.as_deref().unwrap_or("input_N")
+            ));
+        }
+        if let Some(transform) = &rel.transform {
+            labels.push(format!("[{}]", transform));
+        }
+        if let Some(guard) = &rel.guard {
+            labels.push(guard.to_string());
+        }
+        if let Some(config) = &rel.loop_config {
+            labels.push(format!("⟲ ≤{}", config.max_iterations));
+            if let Some(exit) = &config.exit_when {
+                labels.push(format!("exit {}", exit));
+            }
+        }
+        if !labels.is_empty() {
+            painter.text(
+                from_center + (to_center - from_center) * 0.5,
+                egui::Align2::CENTER_BOTTOM,
+                labels.join(" "),
+                egui::FontId::proportional(11.0),
+                arrow_color,
+            );
+        }
     }
 
     /// Draw grid
@@ -1604,8 +2891,8 @@ impl GraphCellEditorApp {
         // Add some padding
         let editor_rect = screen_rect.shrink(5.0);
 
-        // Use allocate_ui_at_rect for the editor
-        ui.allocate_ui_at_rect(editor_rect, |ui| {
+        // Use a child UI placed at the editor rect
+        ui.allocate_new_ui(egui::UiBuilder::new().max_rect(editor_rect), |ui| {
             // Dark semi-transparent background
             ui.painter().rect_filled(
                 editor_rect,
@@ -1690,6 +2977,7 @@ impl GraphCellEditorApp {
                                                 };
                                                 target.content = CellContent::inline(formatted);
                                             }
+                                            crate::dates::store_day_number(target, result);
                                         }
                                     }
 
@@ -1,6 +1,9 @@
-use crate::{Canvas, CellType};
+use crate::schema::{check_schema, parse_json_content, schema_violations};
+use crate::{Canvas, CellType, DataType, PythonEnvironment, Relationship};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
 use ulid::Ulid;
 
 /// Validation severity level
@@ -29,6 +32,25 @@ pub enum ValidationIssueType {
     MissingReference,
     TypeMismatch,
     SyntaxError,
+    ImplicitDependency,
+    PortMismatch,
+    WriteConflict,
+    PythonEnvironment,
+    MissingModule,
+    SchemaViolation,
+}
+
+/// Cells that would write to the same cell in one execution step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteConflict {
+    /// Cell receiving the writes
+    pub target: Ulid,
+    /// Step the writers run in (the start cell runs in step 1)
+    pub step: usize,
+    /// Path from the start cell to the target through each writer
+    pub paths: Vec<Vec<Ulid>>,
+    /// Whether a conflicting relationship is guarded (the writes may not happen together)
+    pub guarded: bool,
 }
 
 /// Complete validation result
@@ -102,8 +124,9 @@ impl Validator {
     pub fn validate(canvas: &Canvas) -> ValidationResult {
         let mut result = ValidationResult::new();
 
-        // Check for start point
-        if canvas.get_start_point().is_none() && canvas.cell_count() > 0 {
+        // Check for start point (any entry point will do)
+        let start_cells = canvas.start_cells();
+        if start_cells.is_empty() && canvas.cell_count() > 0 {
             result.add_issue(ValidationIssue {
                 severity: ValidationSeverity::Error,
                 message: "No start point set. Execution cannot begin.".to_string(),
@@ -117,7 +140,7 @@ impl Validator {
             result.add_issue(ValidationIssue {
                 severity: ValidationSeverity::Warning,
                 message: format!(
-                    "Cycle detected in relationship graph involving {} cells. This may cause infinite loops.",
+                    "Cycle detected in relationship graph involving {} cells. This may cause infinite loops; mark a relationship in it as a loop to bound it.",
                     cycle_cells.len()
                 ),
                 affected_cells: cycle_cells,
@@ -125,14 +148,14 @@ impl Validator {
             });
         }
 
-        // Detect orphan cells (unreachable from start point)
-        if let Some(start) = canvas.get_start_point() {
-            let orphans = Self::find_orphan_cells(canvas, start.id);
+        // Detect orphan cells (unreachable from every entry point)
+        if !start_cells.is_empty() {
+            let orphans = Self::find_orphan_cells(canvas, &start_cells);
             if !orphans.is_empty() {
                 result.add_issue(ValidationIssue {
                     severity: ValidationSeverity::Info,
                     message: format!(
-                        "{} cell(s) are unreachable from the start point and will not execute.",
+                        "{} cell(s) are unreachable from the start point and entry points and will not execute.",
                         orphans.len()
                     ),
                     affected_cells: orphans,
@@ -142,31 +165,440 @@ impl Validator {
         }
 
         // Validate cell references
+        let mut implicit_deps = Vec::new();
         for cell in canvas.cells().values() {
             if cell.cell_type == CellType::Python {
                 if let Some(content) = cell.content.as_str() {
-                    // Check for cell: references
-                    if content.contains("cell:") {
-                        let missing_refs = Self::check_cell_references(canvas, cell.id, content);
-                        if !missing_refs.is_empty() {
-                            result.add_issue(ValidationIssue {
-                                severity: ValidationSeverity::Error,
-                                message: format!(
-                                    "Missing cell references: {}",
-                                    missing_refs.join(", ")
-                                ),
-                                affected_cells: vec![cell.id],
-                                issue_type: ValidationIssueType::MissingReference,
-                            });
+                    let missing_refs = Self::check_cell_references(canvas, cell.id, content);
+                    if !missing_refs.is_empty() {
+                        result.add_issue(ValidationIssue {
+                            severity: ValidationSeverity::Error,
+                            message: format!(
+                                "Missing cell references: {}",
+                                missing_refs.join(", ")
+                            ),
+                            affected_cells: vec![cell.id],
+                            issue_type: ValidationIssueType::MissingReference,
+                        });
+                    }
+
+                    for reference in python_cell_references(content) {
+                        if let Some(target) = canvas.resolve_cell_reference(&reference) {
+                            implicit_deps.push((cell.id, target.id));
                         }
                     }
                 }
             }
         }
 
+        for issue in Self::check_implicit_dependencies(canvas, &implicit_deps) {
+            result.add_issue(issue);
+        }
+
+        for issue in Self::check_ports(canvas) {
+            result.add_issue(issue);
+        }
+
+        for issue in Self::check_write_conflicts(canvas) {
+            result.add_issue(issue);
+        }
+
+        for issue in Self::check_json_cells(canvas) {
+            result.add_issue(issue);
+        }
+
+        // Loop back edges should close a cycle
+        for rel in canvas.relationships().values() {
+            if rel.loop_config.is_some()
+                && !Self::find_upstream_cells(canvas, rel.from).contains(&rel.to)
+            {
+                result.add_issue(ValidationIssue {
+                    severity: ValidationSeverity::Warning,
+                    message: "Relationship is marked as a loop but doesn't close a cycle."
+                        .to_string(),
+                    affected_cells: vec![rel.from, rel.to],
+                    issue_type: ValidationIssueType::Cycle,
+                });
+            }
+        }
+
         result
     }
 
+    /// Check a project's Python environment: the interpreter must run, the requirements
+    /// must be installed and every module a Python cell imports must be importable
+    pub fn check_python_environment(
+        canvas: &Canvas,
+        environment: &PythonEnvironment,
+        project_root: &Path,
+    ) -> Vec<ValidationIssue> {
+        let mut imports: BTreeMap<String, Vec<Ulid>> = BTreeMap::new();
+        for cell in canvas.cells().values() {
+            if cell.cell_type == CellType::Python {
+                for module in python_imports(cell.content.as_str().unwrap_or("")) {
+                    imports.entry(module).or_default().push(cell.id);
+                }
+            }
+        }
+        let modules: Vec<String> = imports.keys().cloned().collect();
+
+        let interpreter = environment.executable(project_root);
+        let report = match environment.check(project_root, &modules) {
+            Ok(report) => report,
+            Err(e) => {
+                return vec![ValidationIssue {
+                    severity: ValidationSeverity::Error,
+                    message: format!("Python environment is unusable: {:#}", e),
+                    affected_cells: vec![],
+                    issue_type: ValidationIssueType::PythonEnvironment,
+                }];
+            }
+        };
+
+        let mut issues = Vec::new();
+        for requirement in report.missing_requirements {
+            issues.push(ValidationIssue {
+                severity: ValidationSeverity::Error,
+                message: format!(
+                    "Requirement '{}' is not installed in {}",
+                    requirement,
+                    interpreter.display()
+                ),
+                affected_cells: vec![],
+                issue_type: ValidationIssueType::PythonEnvironment,
+            });
+        }
+        for module in report.missing_modules {
+            issues.push(ValidationIssue {
+                severity: ValidationSeverity::Error,
+                message: format!("No module named '{}' in {}", module, interpreter.display()),
+                affected_cells: imports.remove(&module).unwrap_or_default(),
+                issue_type: ValidationIssueType::MissingModule,
+            });
+        }
+        issues
+    }
+
+    /// Report cells that would be written twice in one step, from every start cell
+    ///
+    /// Conflicts through guarded relationships are warnings, since the guards may not all
+    /// hold in the same run.
+    pub fn check_write_conflicts(canvas: &Canvas) -> Vec<ValidationIssue> {
+        let label = |id: Ulid| {
+            canvas
+                .get_cell(id)
+                .map(|c| c.label())
+                .unwrap_or_else(|| id.to_string())
+        };
+
+        let mut issues = Vec::new();
+        let mut reported = HashSet::new();
+        for start in canvas.start_cells() {
+            for conflict in Self::find_write_conflicts(canvas, start) {
+                if !reported.insert(conflict.target) {
+                    continue;
+                }
+
+                let writers: Vec<Ulid> = conflict
+                    .paths
+                    .iter()
+                    .filter_map(|path| path.iter().rev().nth(1).copied())
+                    .collect();
+                let paths: Vec<String> = conflict
+                    .paths
+                    .iter()
+                    .map(|path| path.iter().map(|&id| label(id)).collect::<Vec<_>>().join(" → "))
+                    .collect();
+                let mut affected_cells = vec![conflict.target];
+                affected_cells.extend(&writers);
+
+                issues.push(ValidationIssue {
+                    severity: if conflict.guarded {
+                        ValidationSeverity::Warning
+                    } else {
+                        ValidationSeverity::Error
+                    },
+                    message: format!(
+                        "Cell {} would be written by {} cells in step {}: {}",
+                        label(conflict.target),
+                        writers.len(),
+                        conflict.step,
+                        paths.join("; ")
+                    ),
+                    affected_cells,
+                    issue_type: ValidationIssueType::WriteConflict,
+                });
+            }
+        }
+        issues
+    }
+
+    /// Simulate step scheduling from a start cell and find cells written twice in a step
+    ///
+    /// Mirrors the execution engine: each step runs the cells queued by the previous one,
+    /// and a cell queued by two cells of the same step is a conflict. Every relationship is
+    /// assumed to be followed except loop back edges.
+    pub fn find_write_conflicts(canvas: &Canvas, start: Ulid) -> Vec<WriteConflict> {
+        let mut conflicts = Vec::new();
+        let mut reported = HashSet::new();
+        let mut paths: HashMap<Ulid, Vec<Ulid>> = HashMap::from([(start, vec![start])]);
+        let mut step_cells = vec![start];
+        let mut step = 1;
+
+        // Without loop edges a cycle would never end; no acyclic path is longer than this
+        while !step_cells.is_empty() && step <= canvas.cell_count() {
+            step_cells.sort();
+            let mut writes: BTreeMap<Ulid, Vec<&Relationship>> = BTreeMap::new();
+            for &cell_id in &step_cells {
+                for rel in canvas.get_outgoing_relationships(cell_id) {
+                    if rel.loop_config.is_none() {
+                        writes.entry(rel.to).or_default().push(rel);
+                    }
+                }
+            }
+
+            let mut next_paths = HashMap::new();
+            for (&target, rels) in &writes {
+                let target_paths: Vec<Vec<Ulid>> = rels
+                    .iter()
+                    .map(|rel| {
+                        let mut path = paths[&rel.from].clone();
+                        path.push(target);
+                        path
+                    })
+                    .collect();
+                next_paths.insert(target, target_paths[0].clone());
+
+                if rels.len() > 1 && reported.insert(target) {
+                    conflicts.push(WriteConflict {
+                        target,
+                        step,
+                        paths: target_paths,
+                        guarded: rels.iter().any(|rel| rel.guard.is_some()),
+                    });
+                }
+            }
+
+            paths = next_paths;
+            step_cells = writes.into_keys().collect();
+            step += 1;
+        }
+
+        conflicts
+    }
+
+    /// Check JSON cells: inline content must parse, schemas must be valid, and the content
+    /// and data from upstream JSON cells must match the schema
+    ///
+    /// Each schema violation is its own issue, located by a JSON pointer.
+    pub fn check_json_cells(canvas: &Canvas) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let mut cells: Vec<_> = canvas
+            .cells()
+            .values()
+            .filter(|cell| cell.cell_type == CellType::Json)
+            .collect();
+        cells.sort_by_key(|cell| cell.id);
+
+        for cell in cells {
+            let content = cell.content.as_str().map(parse_json_content);
+            if let Some(Err(e)) = &content {
+                issues.push(ValidationIssue {
+                    severity: ValidationSeverity::Error,
+                    message: format!("Cell {}: {}", cell.label(), e),
+                    affected_cells: vec![cell.id],
+                    issue_type: ValidationIssueType::SyntaxError,
+                });
+            }
+
+            let Some(schema) = &cell.json_schema else {
+                continue;
+            };
+            if let Err(e) = check_schema(schema) {
+                issues.push(ValidationIssue {
+                    severity: ValidationSeverity::Error,
+                    message: format!("Cell {}: {}", cell.label(), e),
+                    affected_cells: vec![cell.id],
+                    issue_type: ValidationIssueType::SchemaViolation,
+                });
+                continue;
+            }
+
+            // Values known statically: the cell's own content and upstream JSON cells'
+            let mut values = Vec::new();
+            if let Some(Ok(value)) = content {
+                values.push((format!("Cell {}", cell.label()), value, vec![cell.id]));
+            }
+            let mut incoming = canvas.get_incoming_relationships(cell.id);
+            incoming.sort_by_key(|rel| rel.from);
+            for rel in incoming {
+                if rel.source_key.is_some() || rel.transform.is_some() {
+                    continue;
+                }
+                let Some(source) = canvas.get_cell(rel.from) else {
+                    continue;
+                };
+                if source.cell_type != CellType::Json {
+                    continue;
+                }
+                if let Some(Ok(value)) = source.content.as_str().map(parse_json_content) {
+                    let what = format!("Data from {} into {}", source.label(), cell.label());
+                    values.push((what, value, vec![rel.from, cell.id]));
+                }
+            }
+
+            for (what, value, affected_cells) in values {
+                for violation in schema_violations(schema, &value).unwrap_or_default() {
+                    issues.push(ValidationIssue {
+                        severity: ValidationSeverity::Error,
+                        message: format!("{} doesn't match the schema at {}", what, violation),
+                        affected_cells: affected_cells.clone(),
+                        issue_type: ValidationIssueType::SchemaViolation,
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Check named ports on relationships against their source and target cells
+    pub fn check_ports(canvas: &Canvas) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let mut relationships: Vec<_> = canvas.relationships().values().collect();
+        relationships.sort_by_key(|rel| (rel.to, rel.from));
+
+        let mut input_names: HashMap<(Ulid, &str), Ulid> = HashMap::new();
+        for rel in relationships {
+            let (Some(source), Some(target)) = (canvas.get_cell(rel.from), canvas.get_cell(rel.to))
+            else {
+                continue;
+            };
+
+            if let Some(key) = &rel.source_key {
+                let known = match (source.cell_type, source.content.as_str()) {
+                    (CellType::Python, Some(content)) => {
+                        python_output_keys(content).iter().any(|k| k == key)
+                    }
+                    _ => false,
+                };
+                if !known {
+                    issues.push(ValidationIssue {
+                        severity: ValidationSeverity::Warning,
+                        message: format!(
+                            "Cell {} has no output port '{}' (used by the relationship to {}).",
+                            source.label(),
+                            key,
+                            target.label()
+                        ),
+                        affected_cells: vec![rel.from, rel.to],
+                        issue_type: ValidationIssueType::PortMismatch,
+                    });
+                }
+            }
+
+            if let Some(name) = &rel.target_input {
+                if !crate::Relationship::is_valid_port_name(name) {
+                    issues.push(ValidationIssue {
+                        severity: ValidationSeverity::Error,
+                        message: format!(
+                            "Input port '{}' on cell {} is not a valid variable name.",
+                            name,
+                            target.label()
+                        ),
+                        affected_cells: vec![rel.to],
+                        issue_type: ValidationIssueType::PortMismatch,
+                    });
+                } else if crate::Relationship::is_reserved_port_name(name) {
+                    let python_names = crate::Relationship::RESERVED_PORT_NAMES;
+                    let hidden = if python_names.contains(&name.as_str()) {
+                        format!("the Python runtime's '{}'", name)
+                    } else {
+                        "the Shell variable INPUT_COUNT".to_string()
+                    };
+                    issues.push(ValidationIssue {
+                        severity: ValidationSeverity::Error,
+                        message: format!(
+                            "Input port '{}' on cell {} would hide {}.",
+                            name,
+                            target.label(),
+                            hidden
+                        ),
+                        affected_cells: vec![rel.to],
+                        issue_type: ValidationIssueType::PortMismatch,
+                    });
+                } else if let Some(other) = input_names.insert((rel.to, name.as_str()), rel.from) {
+                    issues.push(ValidationIssue {
+                        severity: ValidationSeverity::Error,
+                        message: format!(
+                            "Input port '{}' on cell {} is fed by more than one relationship.",
+                            name,
+                            target.label()
+                        ),
+                        affected_cells: vec![rel.to, other, rel.from],
+                        issue_type: ValidationIssueType::PortMismatch,
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Check implicit dependencies (reader, read cell) created through the `cells` API
+    ///
+    /// A read is only safe if the read cell is upstream of the reader in the relationship
+    /// graph; otherwise its output may not exist yet when the reader runs.
+    pub fn check_implicit_dependencies(
+        canvas: &Canvas,
+        dependencies: &[(Ulid, Ulid)],
+    ) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let mut seen = HashSet::new();
+
+        for &(reader, source) in dependencies {
+            if reader == source || !seen.insert((reader, source)) {
+                continue;
+            }
+            let (Some(reader_cell), Some(source_cell)) =
+                (canvas.get_cell(reader), canvas.get_cell(source))
+            else {
+                continue;
+            };
+
+            if !Self::find_upstream_cells(canvas, reader).contains(&source) {
+                issues.push(ValidationIssue {
+                    severity: ValidationSeverity::Warning,
+                    message: format!(
+                        "Cell {} reads cell {}, which is not upstream of it and may not have run yet.",
+                        reader_cell.label(),
+                        source_cell.label()
+                    ),
+                    affected_cells: vec![reader, source],
+                    issue_type: ValidationIssueType::ImplicitDependency,
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// Find all cells upstream of a cell (reachable via incoming relationships)
+    fn find_upstream_cells(canvas: &Canvas, cell_id: Ulid) -> HashSet<Ulid> {
+        let mut upstream = HashSet::new();
+        let mut queue = vec![cell_id];
+
+        while let Some(current) = queue.pop() {
+            for rel in canvas.get_incoming_relationships(current) {
+                if upstream.insert(rel.from) {
+                    queue.push(rel.from);
+                }
+            }
+        }
+
+        upstream
+    }
+
     /// Detect cycles in the relationship graph using DFS
     fn detect_cycles(canvas: &Canvas) -> Option<Vec<Ulid>> {
         let mut visited = HashSet::new();
@@ -201,8 +633,11 @@ impl Validator {
         visited.insert(cell_id);
         rec_stack.insert(cell_id);
 
-        // Check all outgoing relationships
+        // Check all outgoing relationships (loop back edges bound their cycle)
         for rel in canvas.get_outgoing_relationships(cell_id) {
+            if rel.loop_config.is_some() {
+                continue;
+            }
             let target = rel.to;
 
             if !visited.contains(&target) {
@@ -222,10 +657,10 @@ impl Validator {
         false
     }
 
-    /// Find orphan cells (unreachable from start point)
-    fn find_orphan_cells(canvas: &Canvas, start_id: Ulid) -> Vec<Ulid> {
+    /// Find orphan cells (unreachable from all start cells)
+    fn find_orphan_cells(canvas: &Canvas, start_cells: &[Ulid]) -> Vec<Ulid> {
         let mut reachable = HashSet::new();
-        let mut queue = vec![start_id];
+        let mut queue = start_cells.to_vec();
 
         // BFS to find all reachable cells
         while let Some(cell_id) = queue.pop() {
@@ -251,33 +686,11 @@ impl Validator {
 
     /// Check for missing cell references in Python code
     fn check_cell_references(canvas: &Canvas, _cell_id: Ulid, content: &str) -> Vec<String> {
-        let mut missing = Vec::new();
-
-        // Simple regex-like search for "from cell:Name" or "import cell:Name"
-        for line in content.lines() {
-            if line.contains("cell:") {
-                // Extract cell name after "cell:"
-                if let Some(start) = line.find("cell:") {
-                    let after_colon = &line[start + 5..];
-                    let name = after_colon
-                        .split(|c: char| !c.is_alphanumeric() && c != '_')
-                        .next()
-                        .unwrap_or("");
-
-                    if !name.is_empty() {
-                        // Check if this cell name exists
-                        let exists = canvas
-                            .cells()
-                            .values()
-                            .any(|c| c.name.as_deref() == Some(name) && c.cell_type == CellType::Python);
-
-                        if !exists {
-                            missing.push(format!("cell:{}", name));
-                        }
-                    }
-                }
-            }
-        }
+        let mut missing: Vec<String> = python_cell_references(content)
+            .into_iter()
+            .filter(|name| canvas.resolve_cell_reference(name).is_none())
+            .map(|name| format!("cell:{}", name))
+            .collect();
 
         missing.sort();
         missing.dedup();
@@ -285,11 +698,113 @@ impl Validator {
     }
 }
 
+/// Extract the cells a Python cell refers to
+///
+/// Recognises `cell:Name` (including `from cell:Name import ...`), `cells["Name"]`,
+/// `cells.get("Name")` and `cells.Name`.
+pub fn python_cell_references(content: &str) -> Vec<String> {
+    let patterns = [
+        r"(?:^|[^\w.])cell:(\w+)",
+        r#"\bcells\[\s*['"]([^'"]+)['"]\s*\]"#,
+        r#"\bcells\.get\(\s*['"]([^'"]+)['"]"#,
+        r"\bcells\.([A-Za-z_]\w*)",
+    ];
+    // Methods on the `cells` object that aren't cell lookups
+    let api_methods = ["get", "import_from"];
+
+    let mut references = Vec::new();
+    for pattern in patterns {
+        let re = Regex::new(pattern).unwrap();
+        for caps in re.captures_iter(content) {
+            let name = caps[1].to_string();
+            if api_methods.contains(&name.as_str()) || references.contains(&name) {
+                continue;
+            }
+            references.push(name);
+        }
+    }
+    references
+}
+
+/// Top-level modules a Python cell imports (`import a.b, c` → `a`, `c`)
+///
+/// Relative imports and `cell:` imports are skipped.
+pub fn python_imports(content: &str) -> Vec<String> {
+    let import_re = Regex::new(r"^\s*import\s+(.+)$").unwrap();
+    let from_re = Regex::new(r"^\s*from\s+(\w+)[\w.]*\s+import\b").unwrap();
+    let identifier_re = Regex::new(r"^[A-Za-z_]\w*$").unwrap();
+
+    let mut modules: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let names: Vec<&str> = if let Some(caps) = import_re.captures(line) {
+            caps.get(1)
+                .unwrap()
+                .as_str()
+                .split(',')
+                .filter_map(|item| item.split_whitespace().next()?.split('.').next())
+                .collect()
+        } else if let Some(caps) = from_re.captures(line) {
+            vec![caps.get(1).unwrap().as_str()]
+        } else {
+            continue;
+        };
+        for name in names {
+            if identifier_re.is_match(name) && !modules.iter().any(|m| m == name) {
+                modules.push(name.to_string());
+            }
+        }
+    }
+    modules
+}
+
+/// Extract the output ports a Python cell declares with literal `set_output` keys
+///
+/// `result` is always included since it's the default key.
+pub fn python_output_keys(content: &str) -> Vec<String> {
+    let re = Regex::new(r#"\bset_output\(.*,\s*(?:key\s*=\s*)?['"](\w+)['"]\s*\)"#).unwrap();
+    let mut keys = vec!["result".to_string()];
+    for caps in re.captures_iter(content) {
+        let key = caps[1].to_string();
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Module-level type annotations in Python cell code, e.g. `input_0: float` or `result: str`
+///
+/// Annotations on `input_N` or a named input declare what the cell expects; an annotation
+/// on `result` declares its output type. Unknown type names are ignored.
+pub fn python_type_annotations(content: &str) -> Vec<(String, DataType)> {
+    let re = Regex::new(r"(?m)^(\w+)[ \t]*:[ \t]*([A-Za-z_][\w.]*)").unwrap();
+    re.captures_iter(content)
+        .filter_map(|caps| {
+            let type_name = caps[2].rsplit('.').next().unwrap_or_default();
+            let data_type = match type_name {
+                "int" | "float" | "complex" => DataType::Number,
+                "str" => DataType::Text,
+                "bool" => DataType::Boolean,
+                "dict" | "list" | "tuple" | "Dict" | "List" | "Tuple" => DataType::Json,
+                "bytes" | "bytearray" => DataType::Binary,
+                "datetime" | "date" => DataType::DateTime,
+                "None" => DataType::None,
+                "Any" | "object" => DataType::Any,
+                _ => return None,
+            };
+            Some((caps[1].to_string(), data_type))
+        })
+        .collect()
+}
+
 /// Extension trait for Canvas to add validation
 pub trait ValidatedCanvas {
     /// Validate the canvas
     fn validate(&self) -> ValidationResult;
 
+    /// Validate the canvas, also checking implicit dependencies recorded during execution
+    fn validate_with_dependencies(&self, dependencies: &[(Ulid, Ulid)]) -> ValidationResult;
+
     /// Get cells with validation issues
     fn cells_with_issues(&self, result: &ValidationResult) -> HashMap<Ulid, ValidationSeverity>;
 }
@@ -299,6 +814,16 @@ impl ValidatedCanvas for Canvas {
         Validator::validate(self)
     }
 
+    fn validate_with_dependencies(&self, dependencies: &[(Ulid, Ulid)]) -> ValidationResult {
+        let mut result = Validator::validate(self);
+        for issue in Validator::check_implicit_dependencies(self, dependencies) {
+            if !result.issues.contains(&issue) {
+                result.add_issue(issue);
+            }
+        }
+        result
+    }
+
     fn cells_with_issues(&self, result: &ValidationResult) -> HashMap<Ulid, ValidationSeverity> {
         let mut cells = HashMap::new();
 
@@ -365,6 +890,13 @@ mod tests {
 
         let warnings = result.warnings();
         assert!(warnings.iter().any(|w| w.issue_type == ValidationIssueType::Cycle));
+
+        // Marking the back edge as a loop bounds the cycle
+        canvas
+            .set_relationship_loop(cell2, cell1, Some(crate::LoopConfig::new(5)))
+            .unwrap();
+        let result = Validator::validate(&canvas);
+        assert!(!result.issues.iter().any(|i| i.issue_type == ValidationIssueType::Cycle));
     }
 
     #[test]
@@ -377,7 +909,7 @@ mod tests {
             CellContent::inline("pass"),
         );
 
-        let _cell2 = canvas.create_cell(
+        let cell2 = canvas.create_cell(
             CellType::Python,
             Rectangle::new(150.0, 0.0, 100.0, 100.0),
             CellContent::inline("pass"),
@@ -390,6 +922,14 @@ mod tests {
 
         let info = result.info();
         assert!(info.iter().any(|i| i.issue_type == ValidationIssueType::OrphanCell));
+
+        // Cells reachable from any entry point are not orphans
+        canvas.set_entry_point(cell2, Some("report".to_string())).unwrap();
+        let result = Validator::validate(&canvas);
+        assert!(!result
+            .info()
+            .iter()
+            .any(|i| i.issue_type == ValidationIssueType::OrphanCell));
     }
 
     #[test]
@@ -411,6 +951,257 @@ mod tests {
         assert!(errors.iter().any(|e| e.issue_type == ValidationIssueType::MissingReference));
     }
 
+    #[test]
+    fn test_python_cell_references() {
+        let refs = python_cell_references(
+            "from cell:Load import df\nx = cells['Clean'].output\ny = cells.Stats\nz = cells.get(\"A7\")",
+        );
+        assert_eq!(refs, vec!["Load", "Clean", "A7", "Stats"]);
+    }
+
+    #[test]
+    fn test_python_imports() {
+        let imports = python_imports(
+            "import os, numpy as np\nimport cell:Load\n  from xml.dom import minidom\n\
+             from . import sibling\nfrom cell:Load import df\nimport os.path  # again",
+        );
+        assert_eq!(imports, vec!["os", "numpy", "xml"]);
+    }
+
+    #[test]
+    fn test_python_environment_issues() {
+        let mut canvas = Canvas::new();
+        let cell = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("import json\nimport no_such_module_gce\nset_output(1)"),
+        );
+        let root = std::env::temp_dir();
+
+        let mut environment = PythonEnvironment::new("python3");
+        environment.requirements = vec!["no-such-package-gce".to_string()];
+        let issues = Validator::check_python_environment(&canvas, &environment, &root);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].issue_type, ValidationIssueType::PythonEnvironment);
+        assert!(issues[0].message.contains("no-such-package-gce"));
+        assert_eq!(issues[1].issue_type, ValidationIssueType::MissingModule);
+        assert!(issues[1].message.contains("no_such_module_gce"));
+        assert_eq!(issues[1].affected_cells, vec![cell]);
+
+        let missing = PythonEnvironment::new("no-such-venv-gce/bin/python");
+        let issues = Validator::check_python_environment(&canvas, &missing, &root);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, ValidationSeverity::Error);
+        assert!(issues[0].message.contains("unusable"));
+    }
+
+    #[test]
+    fn test_port_mismatches() {
+        assert_eq!(
+            python_output_keys("set_output(a, key='train')\nset_output(b, \"test\")"),
+            vec!["result", "train", "test"]
+        );
+
+        let mut canvas = Canvas::new();
+        let split = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(rows[:80], key='train')"),
+        );
+        let other = canvas.create_cell(
+            CellType::NumberInt,
+            Rectangle::new(0.0, 150.0, 100.0, 100.0),
+            CellContent::inline("5"),
+        );
+        let target = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(data)"),
+        );
+        canvas.create_relationship(split, target).unwrap();
+        canvas.create_relationship(other, target).unwrap();
+        canvas.set_start_point(split).unwrap();
+
+        canvas
+            .set_relationship_ports(split, target, Some("train".to_string()), Some("data".to_string()))
+            .unwrap();
+        assert!(Validator::check_ports(&canvas).is_empty());
+
+        // Unknown output key, and two relationships feeding the same input
+        canvas
+            .set_relationship_ports(split, target, Some("tset".to_string()), Some("data".to_string()))
+            .unwrap();
+        canvas
+            .set_relationship_ports(other, target, None, Some("data".to_string()))
+            .unwrap();
+        let issues = Validator::check_ports(&canvas);
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|i| i.issue_type == ValidationIssueType::PortMismatch));
+        assert!(issues.iter().any(|i| i.severity == ValidationSeverity::Warning));
+        assert!(issues.iter().any(|i| i.severity == ValidationSeverity::Error));
+
+        // A reserved name loaded from disk is still caught
+        let rel = canvas.relationships_mut().get_mut(&(other, target)).unwrap();
+        rel.target_input = Some("inputs".to_string());
+        let issues = Validator::check_ports(&canvas);
+        assert!(issues.iter().any(|i| i.message.contains("would hide")));
+        let rel = canvas.relationships_mut().get_mut(&(other, target)).unwrap();
+        rel.target_input = Some("COUNT".to_string());
+        let issues = Validator::check_ports(&canvas);
+        assert!(issues.iter().any(|i| i.message.contains("INPUT_COUNT")));
+    }
+
+    #[test]
+    fn test_python_type_annotations() {
+        let code = "input_0: float\ndata: list[int] = []\nresult: typing.Any\nif x: pass\n";
+        assert_eq!(
+            python_type_annotations(code),
+            vec![
+                ("input_0".to_string(), DataType::Number),
+                ("data".to_string(), DataType::Json),
+                ("result".to_string(), DataType::Any),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_conflicts() {
+        let mut canvas = Canvas::new();
+        let names = ["Load", "Left", "Right", "Join"];
+        let ids: Vec<Ulid> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let id = canvas.create_cell(
+                    CellType::Python,
+                    Rectangle::new(150.0 * i as f32, 0.0, 100.0, 100.0),
+                    CellContent::inline("pass"),
+                );
+                canvas.rename_cell(id, Some(name.to_string())).unwrap();
+                id
+            })
+            .collect();
+        let (load, left, right, join) = (ids[0], ids[1], ids[2], ids[3]);
+        canvas.create_relationship(load, left).unwrap();
+        canvas.create_relationship(load, right).unwrap();
+        canvas.create_relationship(left, join).unwrap();
+        canvas.set_start_point(load).unwrap();
+        assert!(Validator::check_write_conflicts(&canvas).is_empty());
+
+        // Left and Right both run in step 2 and both write Join
+        canvas.create_relationship(right, join).unwrap();
+        let conflicts = Validator::find_write_conflicts(&canvas, load);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].target, join);
+        assert_eq!(conflicts[0].step, 2);
+        let mut paths = conflicts[0].paths.clone();
+        paths.sort();
+        let mut expected = vec![vec![load, left, join], vec![load, right, join]];
+        expected.sort();
+        assert_eq!(paths, expected);
+
+        let result = Validator::validate(&canvas);
+        let issue = result
+            .errors()
+            .into_iter()
+            .find(|i| i.issue_type == ValidationIssueType::WriteConflict)
+            .unwrap();
+        assert!(issue.message.contains("Load → Left → Join"));
+        assert!(issue.message.contains("Load → Right → Join"));
+        assert_eq!(issue.affected_cells.len(), 3);
+
+        // Guarded branches may not both fire
+        canvas
+            .set_relationship_guard(right, join, Some(crate::Guard::Truthy))
+            .unwrap();
+        let issues = Validator::check_write_conflicts(&canvas);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, ValidationSeverity::Warning);
+    }
+
+    #[test]
+    fn test_json_schemas() {
+        let mut canvas = Canvas::new();
+        let source = canvas.create_cell(
+            CellType::Json,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline(r#"{"name": "a", "size": "big"}"#),
+        );
+        let target = canvas.create_cell(
+            CellType::Json,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline(r#"{"name": "b", "size": 3}"#),
+        );
+        canvas.rename_cell(source, Some("Source".to_string())).unwrap();
+        canvas.rename_cell(target, Some("Target".to_string())).unwrap();
+        canvas.create_relationship(source, target).unwrap();
+        assert!(Validator::check_json_cells(&canvas).is_empty());
+
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {"size": {"type": "number"}}
+        });
+        canvas.get_cell_mut(target).unwrap().json_schema = Some(schema);
+        let issues = Validator::check_json_cells(&canvas);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].issue_type, ValidationIssueType::SchemaViolation);
+        assert_eq!(issues[0].affected_cells, vec![source, target]);
+        assert!(issues[0]
+            .message
+            .starts_with("Data from Source into Target doesn't match the schema at /size: "));
+
+        canvas
+            .update_cell_content(target, CellContent::inline("{\"size\": }"))
+            .unwrap();
+        canvas.get_cell_mut(source).unwrap().json_schema =
+            Some(serde_json::json!({"type": "nope"}));
+        let messages: Vec<String> = Validator::check_json_cells(&canvas)
+            .into_iter()
+            .map(|issue| issue.message)
+            .collect();
+        assert_eq!(messages.len(), 3);
+        assert!(messages.iter().any(|m| m.starts_with("Cell Source: Invalid JSON Schema")));
+        assert!(messages.iter().any(|m| m.starts_with("Cell Target: Invalid JSON at line 1")));
+    }
+
+    #[test]
+    fn test_implicit_dependency_not_upstream() {
+        let mut canvas = Canvas::new();
+
+        let source = canvas.create_cell(
+            CellType::NumberInt,
+            Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            CellContent::inline("5"),
+        );
+        canvas.rename_cell(source, Some("Five".to_string())).unwrap();
+
+        let reader = canvas.create_cell(
+            CellType::Python,
+            Rectangle::new(150.0, 0.0, 100.0, 100.0),
+            CellContent::inline("set_output(cells['Five'].output)"),
+        );
+        canvas.set_start_point(reader).unwrap();
+
+        let result = Validator::validate(&canvas);
+        assert!(!result
+            .errors()
+            .iter()
+            .any(|e| e.issue_type == ValidationIssueType::MissingReference));
+        assert!(result
+            .warnings()
+            .iter()
+            .any(|w| w.issue_type == ValidationIssueType::ImplicitDependency));
+
+        // Once the source feeds the reader, the read is safe
+        canvas.create_relationship(source, reader).unwrap();
+        canvas.set_start_point(source).unwrap();
+        let result = Validator::validate(&canvas);
+        assert!(!result
+            .warnings()
+            .iter()
+            .any(|w| w.issue_type == ValidationIssueType::ImplicitDependency));
+    }
+
     #[test]
     fn test_valid_canvas() {
         let mut canvas = Canvas::with_root_cell(
@@ -0,0 +1,405 @@
+//! WASM cells: WebAssembly modules run in a sandbox
+
+use crate::execution::CapturedOutput;
+use crate::CellData;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use wasmi::core::TrapCode;
+use wasmi::{
+    Caller, Config, Engine, Extern, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+/// Module the host functions are imported from
+const HOST_MODULE: &str = "graph_cell";
+
+/// Limits and capabilities WASM cells run with
+///
+/// Modules can't reach the network, the clock or the filesystem, except for files under
+/// the project directory when `preopen_project_dir` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WasmSandbox {
+    /// Fuel a module may burn; roughly one unit per instruction executed
+    pub fuel: u64,
+    /// Largest linear memory a module may grow to, in bytes
+    pub max_memory_bytes: usize,
+    /// Let modules read and write files under the project directory
+    pub preopen_project_dir: bool,
+}
+
+impl Default for WasmSandbox {
+    fn default() -> Self {
+        Self {
+            fuel: 100_000_000,
+            max_memory_bytes: 64 << 20,
+            preopen_project_dir: false,
+        }
+    }
+}
+
+/// State host functions share with the running module
+struct HostState {
+    output: Option<Vec<u8>>,
+    stdout: String,
+    stderr: String,
+    limits: StoreLimits,
+    /// Preopened directory, if any
+    root: Option<PathBuf>,
+}
+
+/// Compile a module from WebAssembly binary or text format
+fn compile(engine: &Engine, source: &[u8]) -> Result<Module> {
+    let wasm = wat::parse_bytes(source).map_err(|e| anyhow!("Invalid WebAssembly: {}", e))?;
+    Module::new(engine, &wasm).map_err(|e| anyhow!("Invalid WebAssembly module: {}", e))
+}
+
+/// Check that a module compiles and has the exports WASM cells need (dry run)
+pub(crate) fn check_module(source: &[u8]) -> Result<()> {
+    let module = compile(&Engine::default(), source)?;
+    for export in ["memory", "alloc", "run"] {
+        if module.get_export(export).is_none() {
+            return Err(anyhow!("WASM module doesn't export '{}'", export));
+        }
+    }
+    Ok(())
+}
+
+/// Run a WASM cell's module
+///
+/// The module exports `memory`, `alloc(len) -> ptr` and `run(ptr, len)`. The host
+/// allocates room for the input JSON with `alloc`, writes it there and calls `run`. The
+/// module can import from `graph_cell`:
+///
+/// - `set_output(ptr, len)`: set the cell output to a JSON value
+/// - `log(ptr, len)`: write a line to the cell's stdout
+/// - `read_file(path_ptr, path_len, buf_ptr, buf_len) -> i64`: read a file under the
+///   preopened directory into the buffer if it fits; returns the file size, or -1
+/// - `write_file(path_ptr, path_len, data_ptr, data_len) -> i32`: write a file under
+///   the preopened directory; returns 0, or -1
+pub(crate) fn run_module(
+    source: &[u8],
+    input: &serde_json::Value,
+    sandbox: &WasmSandbox,
+    project_dir: Option<&Path>,
+    captured: &mut CapturedOutput,
+) -> Result<CellData> {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = compile(&engine, source)?;
+
+    let state = HostState {
+        output: None,
+        stdout: String::new(),
+        stderr: String::new(),
+        limits: StoreLimitsBuilder::new()
+            .memory_size(sandbox.max_memory_bytes)
+            .build(),
+        root: project_dir
+            .filter(|_| sandbox.preopen_project_dir)
+            .map(Path::to_path_buf),
+    };
+    let mut store = Store::new(&engine, state);
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(sandbox.fuel).map_err(|e| anyhow!("{}", e))?;
+
+    let mut linker = Linker::<HostState>::new(&engine);
+    link_host_functions(&mut linker).map_err(|e| anyhow!("{}", e))?;
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(|e| sandbox_error(e, sandbox))?;
+
+    let memory = instance
+        .get_memory(&store, "memory")
+        .ok_or_else(|| anyhow!("WASM module doesn't export 'memory'"))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, "alloc")
+        .map_err(|e| anyhow!("WASM module needs alloc(len: i32) -> i32: {}", e))?;
+    let run = instance
+        .get_typed_func::<(i32, i32), ()>(&store, "run")
+        .map_err(|e| anyhow!("WASM module needs run(ptr: i32, len: i32): {}", e))?;
+
+    let input = serde_json::to_vec(input)?;
+    let len = i32::try_from(input.len()).context("Cell input is too large for WASM")?;
+    let result = alloc.call(&mut store, len).and_then(|ptr| {
+        memory.write(&mut store, ptr as u32 as usize, &input)?;
+        run.call(&mut store, (ptr, len))
+    });
+
+    let state = store.into_data();
+    captured.stdout = state.stdout;
+    captured.stderr = state.stderr;
+    result.map_err(|e| sandbox_error(e, sandbox))?;
+
+    match state.output {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .map(crate::transform::json_to_celldata)
+            .context("WASM module output is not valid JSON"),
+        None => Ok(CellData::None),
+    }
+}
+
+fn link_host_functions(linker: &mut Linker<HostState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(
+        HOST_MODULE,
+        "set_output",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+            let bytes = guest_bytes(&caller, ptr, len)?;
+            caller.data_mut().output = Some(bytes);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+            let line = String::from_utf8_lossy(&guest_bytes(&caller, ptr, len)?).into_owned();
+            let stdout = &mut caller.data_mut().stdout;
+            stdout.push_str(&line);
+            stdout.push('\n');
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "read_file",
+        |mut caller: Caller<'_, HostState>,
+         path_ptr: i32,
+         path_len: i32,
+         buf_ptr: i32,
+         buf_len: i32|
+         -> Result<i64, wasmi::Error> {
+            let path = String::from_utf8_lossy(&guest_bytes(&caller, path_ptr, path_len)?)
+                .into_owned();
+            let content = sandboxed_path(caller.data().root.as_deref(), &path)
+                .and_then(std::fs::read);
+            match content {
+                Ok(content) => {
+                    if content.len() <= buf_len.max(0) as usize {
+                        let memory = guest_memory(&caller)?;
+                        memory.write(&mut caller, buf_ptr as u32 as usize, &content)?;
+                    }
+                    Ok(content.len() as i64)
+                }
+                Err(e) => {
+                    let message = format!("read_file {}: {}\n", path, e);
+                    caller.data_mut().stderr.push_str(&message);
+                    Ok(-1)
+                }
+            }
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "write_file",
+        |mut caller: Caller<'_, HostState>,
+         path_ptr: i32,
+         path_len: i32,
+         data_ptr: i32,
+         data_len: i32|
+         -> Result<i32, wasmi::Error> {
+            let path = String::from_utf8_lossy(&guest_bytes(&caller, path_ptr, path_len)?)
+                .into_owned();
+            let data = guest_bytes(&caller, data_ptr, data_len)?;
+            let written = sandboxed_path(caller.data().root.as_deref(), &path)
+                .and_then(|path| std::fs::write(path, data));
+            match written {
+                Ok(()) => Ok(0),
+                Err(e) => {
+                    let message = format!("write_file {}: {}\n", path, e);
+                    caller.data_mut().stderr.push_str(&message);
+                    Ok(-1)
+                }
+            }
+        },
+    )?;
+    Ok(())
+}
+
+fn guest_memory(caller: &Caller<'_, HostState>) -> Result<wasmi::Memory, wasmi::Error> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("module doesn't export 'memory'"))
+}
+
+/// Copy `len` bytes at `ptr` out of the module's memory
+fn guest_bytes(
+    caller: &Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> Result<Vec<u8>, wasmi::Error> {
+    let data = guest_memory(caller)?.data(caller);
+    let start = ptr as u32 as usize;
+    usize::try_from(len)
+        .ok()
+        .and_then(|len| data.get(start..start.checked_add(len)?))
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| wasmi::Error::from(TrapCode::MemoryOutOfBounds))
+}
+
+/// Resolve a path a module asked for inside the preopened directory
+///
+/// Absolute paths, `..` and symlinks leading outside the directory are refused.
+fn sandboxed_path(root: Option<&Path>, path: &str) -> io::Result<PathBuf> {
+    let denied = |reason: &str| io::Error::new(io::ErrorKind::PermissionDenied, reason);
+    let root = root.ok_or_else(|| denied("no directory is preopened"))?;
+    let relative = Path::new(path);
+    let inside = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside || path.is_empty() {
+        return Err(denied("path must be relative to the project directory"));
+    }
+
+    let root = root.canonicalize()?;
+    let full = root.join(relative);
+    let resolved = match full.canonicalize() {
+        Ok(resolved) => resolved,
+        // A file about to be created: resolve its directory instead. The name must not
+        // be a dangling symlink, which writing would follow wherever it points
+        Err(_) => {
+            let parent = full.parent().unwrap_or(&root).canonicalize()?;
+            let resolved = parent.join(full.file_name().unwrap_or_default());
+            if resolved.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()) {
+                return Err(denied("path is a symlink to a missing file"));
+            }
+            resolved
+        }
+    };
+    if resolved.starts_with(&root) {
+        Ok(resolved)
+    } else {
+        Err(denied("path leads outside the project directory"))
+    }
+}
+
+fn sandbox_error(error: wasmi::Error, sandbox: &WasmSandbox) -> anyhow::Error {
+    match error.as_trap_code() {
+        Some(TrapCode::OutOfFuel) => {
+            anyhow!("WASM module ran out of fuel ({} units)", sandbox.fuel)
+        }
+        _ => anyhow!("WASM module trapped: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    /// Bump allocator plus `run` bodies written in the text format
+    fn module(run_body: &str, data: &str) -> String {
+        format!(
+            r#"(module
+                (import "graph_cell" "set_output" (func $set_output (param i32 i32)))
+                (import "graph_cell" "log" (func $log (param i32 i32)))
+                (import "graph_cell" "read_file"
+                    (func $read_file (param i32 i32 i32 i32) (result i64)))
+                (import "graph_cell" "write_file"
+                    (func $write_file (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (global $next (mut i32) (i32.const 1024))
+                (data (i32.const 0) "{}")
+                (func (export "alloc") (param $len i32) (result i32)
+                    (global.get $next)
+                    (global.set $next (i32.add (global.get $next) (local.get $len))))
+                (func (export "run") (param $ptr i32) (param $len i32) {}))"#,
+            data, run_body
+        )
+    }
+
+    fn run(source: &str, sandbox: &WasmSandbox, dir: &Path) -> (Result<CellData>, CapturedOutput) {
+        let mut captured = CapturedOutput::default();
+        let input = json!({"inputs": [1.5], "named_inputs": {}, "params": {}});
+        let result = run_module(source.as_bytes(), &input, sandbox, Some(dir), &mut captured);
+        (result, captured)
+    }
+
+    #[test]
+    fn test_run_module() {
+        let dir = TempDir::new().unwrap();
+        let sandbox = WasmSandbox::default();
+
+        // Echo the input back as the output
+        let echo = module(
+            "(call $log (i32.const 0) (i32.const 5)) \
+             (call $set_output (local.get $ptr) (local.get $len))",
+            "hello",
+        );
+        let (output, captured) = run(&echo, &sandbox, dir.path());
+        assert_eq!(
+            output.unwrap(),
+            CellData::Json(json!({"inputs": [1.5], "named_inputs": {}, "params": {}}))
+        );
+        assert_eq!(captured.stdout, "hello\n");
+        assert!(check_module(echo.as_bytes()).is_ok());
+
+        let spin = module("(loop $forever (br $forever))", "");
+        let (output, _) = run(&spin, &sandbox, dir.path());
+        assert!(output.unwrap_err().to_string().contains("ran out of fuel"));
+
+        let grow = module("(drop (memory.grow (i32.const 2000)))", "");
+        let small = WasmSandbox {
+            max_memory_bytes: 1 << 20,
+            ..WasmSandbox::default()
+        };
+        let (output, _) = run(&grow, &small, dir.path());
+        assert_eq!(output.unwrap(), CellData::None);
+
+        assert!(check_module(b"(module)").unwrap_err().to_string().contains("memory"));
+        assert!(check_module(b"(module (func").is_err());
+    }
+
+    #[test]
+    fn test_preopened_directory() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("in.json"), "[1, 2]").unwrap();
+
+        // Read in.json into a buffer at 512 and output it, then copy it to out.json
+        let copy = module(
+            "(drop (call $read_file (i32.const 0) (i32.const 7) (i32.const 512) (i32.const 64))) \
+             (call $set_output (i32.const 512) (i32.const 6)) \
+             (drop (call $write_file (i32.const 7) (i32.const 8) (i32.const 512) (i32.const 6)))",
+            "in.jsonout.json",
+        );
+        let (output, captured) = run(&copy, &WasmSandbox::default(), dir.path());
+        assert!(output.is_err());
+        assert!(captured.stderr.contains("no directory is preopened"));
+        assert!(!dir.path().join("out.json").exists());
+
+        let sandbox = WasmSandbox {
+            preopen_project_dir: true,
+            ..WasmSandbox::default()
+        };
+        let (output, _) = run(&copy, &sandbox, dir.path());
+        assert_eq!(output.unwrap(), CellData::Json(json!([1, 2])));
+        assert_eq!(std::fs::read_to_string(dir.path().join("out.json")).unwrap(), "[1, 2]");
+
+        let root = Some(dir.path());
+        assert!(sandboxed_path(root, "../secret").is_err());
+        assert!(sandboxed_path(root, "/etc/passwd").is_err());
+        assert!(sandboxed_path(root, "sub/../../x").is_err());
+        assert!(sandboxed_path(root, "./new.txt").is_ok());
+
+        // A dangling symlink would let a write create a file outside the directory
+        #[cfg(unix)]
+        {
+            let outside = TempDir::new().unwrap();
+            let target = outside.path().join("planted.txt");
+            std::os::unix::fs::symlink(&target, dir.path().join("link.txt")).unwrap();
+            assert!(sandboxed_path(root, "link.txt").is_err());
+            let write = module(
+                "(drop (call $write_file (i32.const 0) (i32.const 8) (i32.const 0) (i32.const 8)))",
+                "link.txt",
+            );
+            let (output, _) = run(&write, &sandbox, dir.path());
+            assert!(output.is_ok());
+            assert!(!target.exists());
+        }
+    }
+}